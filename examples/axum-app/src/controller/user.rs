@@ -1,18 +1,18 @@
 use crate::service::user;
 use serde_json::json;
-use zino::{ExtractRejection, Map, Model, Query, Request, RequestContext, Response, Schema, Uuid};
+use zino::{
+    ExtractRejection, JsonObjectExt, Map, Query, Request, RequestContext, Response, Schema, Uuid,
+};
 use zino_model::User;
 
 pub(crate) async fn new(mut req: Request) -> zino::Result {
-    let mut user = User::new();
-    let mut res: Response = req.model_validation(&mut user).await?;
-
-    let rows = user.upsert().await.extract_with_context(&req)?;
-    let data = json!({
-        "method": req.request_method().as_ref(),
-        "path": req.request_path(),
-        "rows": rows,
-    });
+    let body: Map = req.parse_body().await?;
+    let (validation, mut data) = user::new(body).await.extract_with_context(&req)?;
+    if let Some(data) = data.as_object_mut() {
+        data.upsert("method", req.request_method().as_ref());
+        data.upsert("path", req.request_path());
+    }
+    let mut res = Response::from(validation).provide_context(&req);
     res.set_data(&data);
     Ok(res.into())
 }
@@ -1,8 +1,10 @@
 use crate::controller::{stats, task, user};
 use axum::{
+    middleware::from_fn_with_state,
     routing::{get, post},
     Router,
 };
+use zino_model::Authorizer;
 
 pub(crate) fn routes() -> Vec<Router> {
     let mut routes = Vec::new();
@@ -16,7 +18,12 @@ pub(crate) fn routes() -> Vec<Router> {
     routes.push(controller);
 
     // Task controller.
-    let controller = Router::new().route("/task/execute", post(task::execute));
+    let controller = Router::new()
+        .route("/task/execute", post(task::execute))
+        .route_layer(from_fn_with_state(
+            Authorizer::shared(),
+            zino::access_guard::<Authorizer>,
+        ));
     routes.push(controller);
 
     // Stats controller.
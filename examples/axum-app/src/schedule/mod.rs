@@ -1,4 +1,5 @@
 use zino::{AsyncCronJob, CronJob};
+use zino_core::connector::GlobalConnector;
 
 mod job;
 
@@ -10,5 +11,11 @@ pub(crate) fn jobs() -> Vec<(&'static str, CronJob)> {
 }
 
 pub(crate) fn async_jobs() -> Vec<(&'static str, AsyncCronJob)> {
-    vec![("0/30 * * * * *", job::every_30s as AsyncCronJob)]
+    vec![
+        ("0/30 * * * * *", job::every_30s as AsyncCronJob),
+        (
+            "0/30 * * * * *",
+            GlobalConnector::check_health as AsyncCronJob,
+        ),
+    ]
 }
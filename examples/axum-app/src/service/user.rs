@@ -7,13 +7,30 @@ use zino::{
 };
 use zino_model::{ModelAccessor, User};
 
+pub(crate) async fn new(body: Map) -> Result<(Validation, Value), Error> {
+    let mut user = User::new();
+    let validation = read_user(&mut user, &body).await;
+    if !validation.is_success() {
+        return Ok((validation, Value::Null));
+    }
+
+    let rows = user.upsert().await?;
+    let data = json!({
+        "rows": rows,
+    });
+    Ok((validation, data))
+}
+
 pub(crate) async fn update(user_id: Uuid, mut body: Map) -> Result<(Validation, Value), Error> {
     let user_id = user_id.to_string();
     let mut user = User::try_get_model(&user_id).await?;
-    let validation = user.read_map(&body);
+    let validation = read_user(&mut user, &body).await;
     if !validation.is_success() {
         return Ok((validation, Value::Null));
     }
+    if body.contains_key("password") {
+        body.upsert("password", user.password());
+    }
 
     let filters = user.current_version_filters();
     body.append(&mut user.next_version_updates());
@@ -43,3 +60,13 @@ pub(crate) async fn view(req: &Request, query: &Query) -> Result<(Duration, Valu
     });
     Ok((db_query_duration, data))
 }
+
+async fn read_user(user: &mut User, body: &Map) -> Validation {
+    let mut validation = user.read_map(body);
+    if let Some(password) = body.get_str("password") {
+        if let Err(err) = user.set_password_async(password).await {
+            validation.record_fail("password", err);
+        }
+    }
+    validation
+}
@@ -0,0 +1,340 @@
+use crate::{application, error::Error, extend::JsonObjectExt, format::base64, Map};
+use futures::TryStreamExt;
+use hmac::{Hmac, Mac};
+use opendal::{Metadata, Operator};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashSet,
+    time::{Duration, SystemTime},
+};
+
+/// Content-addressed storage on top of an operator.
+///
+/// Blobs are written under their SHA-256 digests, so identical contents are stored only once.
+/// The integrity of a blob is verified when it is read, and the digest can be signed
+/// with a subkey derived from the application secret key.
+///
+/// The garbage collection only deletes the unreferenced blobs which have not been written
+/// within the grace period, so the references to the blobs should be recorded within it.
+#[derive(Debug, Clone)]
+pub struct ContentStore {
+    /// Name of the accessor.
+    accessor: String,
+    /// Operator.
+    operator: Operator,
+    /// Prefix of the blob paths.
+    prefix: String,
+    /// A flag indicating whether the digests should be signed.
+    signing: bool,
+    /// Grace period to keep the unreferenced blobs.
+    grace_period: Duration,
+}
+
+/// An entry of a blob in the content-addressed storage.
+#[derive(Debug, Clone)]
+pub struct ContentEntry {
+    /// SHA-256 digest as a hex string.
+    digest: String,
+    /// Subresource integrity metadata.
+    integrity: String,
+    /// Signature of the digest.
+    signature: Option<String>,
+    /// Blob size in bytes.
+    size: u64,
+    /// Location of the blob.
+    location: String,
+    /// A flag indicating whether the blob has been stored before.
+    deduplicated: bool,
+}
+
+impl ContentStore {
+    /// Creates a new instance for the accessor.
+    pub fn new(accessor: impl Into<String>, operator: Operator) -> Self {
+        Self {
+            accessor: accessor.into(),
+            operator,
+            prefix: "blobs".to_owned(),
+            signing: false,
+            grace_period: Duration::from_secs(60 * 60),
+        }
+    }
+
+    /// Attempts to create a new instance for the accessor in `GlobalAccessor`.
+    pub fn try_new(accessor: &str) -> Result<Self, Error> {
+        let operator = super::GlobalAccessor::get(accessor)
+            .ok_or_else(|| Error::new(format!("accessor `{accessor}` does not exist")))?;
+        Ok(Self::new(accessor, operator.clone()))
+    }
+
+    /// Sets the prefix of the blob paths.
+    #[inline]
+    pub fn set_prefix(&mut self, prefix: impl Into<String>) {
+        self.prefix = prefix.into().trim_matches('/').to_owned();
+    }
+
+    /// Sets the flag whether the digests should be signed with the application secret key.
+    #[inline]
+    pub fn set_signing(&mut self, signing: bool) {
+        self.signing = signing;
+    }
+
+    /// Sets the grace period to keep the unreferenced blobs. It is `1h` by default.
+    #[inline]
+    pub fn set_grace_period(&mut self, grace_period: Duration) {
+        self.grace_period = grace_period;
+    }
+
+    /// Writes the blob if it does not exist, returning the content entry.
+    /// An existing blob is rewritten if it is about to exceed the grace period,
+    /// so that it will not be deleted by the garbage collection before being referenced.
+    pub async fn put(&self, data: Vec<u8>) -> Result<ContentEntry, Error> {
+        let digest = format!("{:x}", Sha256::digest(&data));
+        let path = self.blob_path(&digest)?;
+        let size = data.len() as u64;
+        let deduplicated = self.operator.is_exist(&path).await?;
+        let is_fresh = if deduplicated {
+            let metadata = self.operator.stat(&path).await?;
+            modified_elapsed(&metadata).is_some_and(|elapsed| elapsed < self.grace_period / 2)
+        } else {
+            false
+        };
+        if !is_fresh {
+            self.operator.write(&path, data).await?;
+        }
+        self.new_entry(digest, size, deduplicated)
+    }
+
+    /// Reads the blob with the digest and verifies its integrity.
+    pub async fn get(&self, digest: &str) -> Result<Vec<u8>, Error> {
+        let path = self.blob_path(digest)?;
+        let data = self.operator.read(&path).await?;
+        if format!("{:x}", Sha256::digest(&data)) != digest {
+            let message = format!("integrity check failed for the blob `{digest}`");
+            return Err(Error::new(message));
+        }
+        Ok(data)
+    }
+
+    /// Returns the content entry of the blob with the digest,
+    /// or `None` if it does not exist.
+    pub async fn stat(&self, digest: &str) -> Result<Option<ContentEntry>, Error> {
+        let path = self.blob_path(digest)?;
+        if !self.operator.is_exist(&path).await? {
+            return Ok(None);
+        }
+
+        let metadata = self.operator.stat(&path).await?;
+        self.new_entry(digest.to_owned(), metadata.content_length(), true)
+            .map(Some)
+    }
+
+    /// Deletes the blob with the digest.
+    pub async fn delete(&self, digest: &str) -> Result<(), Error> {
+        let path = self.blob_path(digest)?;
+        self.operator.delete(&path).await.map_err(Error::from)
+    }
+
+    /// Deletes the blobs which are not referenced and have not been written within
+    /// the grace period, returning the digests of deleted blobs.
+    pub async fn collect_garbage(
+        &self,
+        referenced: &HashSet<String>,
+    ) -> Result<Vec<String>, Error> {
+        let mut lister = self.operator.scan(&format!("{}/", self.prefix)).await?;
+        let mut deleted = Vec::new();
+        while let Some(entry) = lister.try_next().await? {
+            let path = entry.path();
+            if path.ends_with('/') {
+                continue;
+            }
+
+            let digest = path.rsplit('/').next().unwrap_or_default();
+            if is_digest(digest) && !referenced.contains(digest) {
+                let metadata = self.operator.stat(path).await?;
+                if modified_elapsed(&metadata).is_some_and(|elapsed| elapsed > self.grace_period) {
+                    self.operator.delete(path).await?;
+                    deleted.push(digest.to_owned());
+                }
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Signs the digest with a subkey derived from the application secret key.
+    pub fn sign(digest: &str) -> Result<String, Error> {
+        let signing_key = application::derive_subkey("content-store")?;
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&signing_key).expect("HMAC can take key of any size");
+        mac.update(digest.as_bytes());
+        Ok(base64::encode(mac.finalize().into_bytes()))
+    }
+
+    /// Verifies the signature of the digest with a subkey derived from the application secret key.
+    pub fn verify_signature(digest: &str, signature: &str) -> Result<(), Error> {
+        let signing_key = application::derive_subkey("content-store")?;
+        let signature = base64::decode(signature)?;
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&signing_key).expect("HMAC can take key of any size");
+        mac.update(digest.as_bytes());
+        mac.verify_slice(&signature)
+            .map_err(|_| Error::new(format!("invalid signature for the digest `{digest}`")))
+    }
+
+    /// Returns the path of the blob with the digest.
+    fn blob_path(&self, digest: &str) -> Result<String, Error> {
+        if !is_digest(digest) {
+            let message = format!("`{digest}` is not a valid SHA-256 digest");
+            return Err(Error::new(message));
+        }
+        Ok(format!(
+            "{}/{}/{}/{digest}",
+            self.prefix,
+            &digest[0..2],
+            &digest[2..4]
+        ))
+    }
+
+    /// Creates a new content entry.
+    fn new_entry(
+        &self,
+        digest: String,
+        size: u64,
+        deduplicated: bool,
+    ) -> Result<ContentEntry, Error> {
+        let bytes = (0..digest.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digest[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()?;
+        let integrity = format!("sha256-{}", base64::encode(bytes));
+        let signature = if self.signing {
+            Some(Self::sign(&digest)?)
+        } else {
+            None
+        };
+        let location = format!("opendal://{}/{}", self.accessor, self.blob_path(&digest)?);
+        Ok(ContentEntry {
+            digest,
+            integrity,
+            signature,
+            size,
+            location,
+            deduplicated,
+        })
+    }
+}
+
+impl ContentEntry {
+    /// Returns the SHA-256 digest as a hex string.
+    #[inline]
+    pub fn digest(&self) -> &str {
+        self.digest.as_str()
+    }
+
+    /// Returns the subresource integrity metadata, i.e. `sha256-` followed by
+    /// the base64-encoded digest.
+    #[inline]
+    pub fn integrity(&self) -> &str {
+        self.integrity.as_str()
+    }
+
+    /// Returns the signature of the digest.
+    #[inline]
+    pub fn signature(&self) -> Option<&str> {
+        self.signature.as_deref()
+    }
+
+    /// Returns the blob size in bytes.
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the location of the blob.
+    #[inline]
+    pub fn location(&self) -> &str {
+        self.location.as_str()
+    }
+
+    /// Returns `true` if the blob has been stored before.
+    #[inline]
+    pub fn is_deduplicated(&self) -> bool {
+        self.deduplicated
+    }
+
+    /// Converts `self` to a JSON object which can be read by the `Record` model.
+    pub fn to_record_map(&self) -> Map {
+        let mut content = Map::new();
+        content.upsert("digest", self.digest());
+        content.upsert("size", self.size());
+        content.upsert("location", self.location());
+
+        let mut map = Map::new();
+        map.upsert("name", self.digest());
+        map.upsert("integrity", self.integrity());
+        map.upsert("signature", self.signature().unwrap_or_default());
+        map.upsert("content", content);
+        map
+    }
+}
+
+/// Returns the elapsed time since the last modification,
+/// or `None` if it is not supported by the storage service.
+fn modified_elapsed(metadata: &Metadata) -> Option<Duration> {
+    metadata
+        .last_modified()
+        .map(|modified| SystemTime::from(modified).elapsed().unwrap_or_default())
+}
+
+/// Returns `true` if the string is a SHA-256 digest as a lowercase hex string.
+fn is_digest(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ContentStore;
+    use crate::Uuid;
+    use opendal::{services::Fs, Operator};
+    use std::{collections::HashSet, env, fs, thread, time::Duration};
+
+    #[test]
+    fn it_verifies_digests_and_collects_garbage() {
+        let root = env::temp_dir().join(format!("zino-content-store-{}", Uuid::new_v4()));
+        let mut builder = Fs::default();
+        builder.root(&root.to_string_lossy());
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let operator = Operator::new(builder).unwrap().finish();
+            let mut content_store = ContentStore::new("fs", operator.clone());
+            let entry = content_store.put(b"hello".to_vec()).await.unwrap();
+            let digest = entry.digest().to_owned();
+            assert!(!entry.is_deduplicated());
+            let entry = content_store.put(b"hello".to_vec()).await.unwrap();
+            assert!(entry.is_deduplicated());
+            assert_eq!(content_store.get(&digest).await.unwrap(), b"hello");
+            assert!(content_store.get("not-a-digest").await.is_err());
+
+            let path = entry.location().trim_start_matches("opendal://fs/");
+            operator.write(path, "tampered").await.unwrap();
+            assert!(content_store.get(&digest).await.is_err());
+
+            let unreferenced = content_store.put(b"world".to_vec()).await.unwrap();
+            let readme_path = "blobs/readme.txt";
+            operator.write(readme_path, "not a blob").await.unwrap();
+            let referenced = HashSet::from([digest.clone()]);
+            let deleted = content_store.collect_garbage(&referenced).await.unwrap();
+            assert!(deleted.is_empty());
+
+            thread::sleep(Duration::from_millis(20));
+            content_store.set_grace_period(Duration::from_millis(10));
+            let deleted = content_store.collect_garbage(&referenced).await.unwrap();
+            assert_eq!(deleted, vec![unreferenced.digest().to_owned()]);
+            assert!(content_store.stat(&digest).await.unwrap().is_some());
+            assert!(operator.is_exist(readme_path).await.unwrap());
+        });
+        fs::remove_dir_all(root).ok();
+    }
+}
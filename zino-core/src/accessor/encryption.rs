@@ -0,0 +1,798 @@
+use crate::{application, crypto, error::Error, extend::TomlTableExt, format::base64};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{AsyncReadExt, TryStreamExt};
+use opendal::{
+    raw::{oio, Accessor, Layer, LayeredAccessor, OpList, OpRead, OpScan, OpWrite, RpList},
+    raw::{RpRead, RpScan, RpWrite},
+    ErrorKind, Metadata, Operator, Reader, Writer,
+};
+use rand::Rng;
+use std::{borrow::Cow, collections::HashMap};
+use toml::Table;
+
+/// Magic bytes of the encrypted objects.
+const MAGIC: &[u8; 4] = b"ZENC";
+
+/// Version of the encryption format.
+const VERSION: u8 = 1;
+
+/// Size of the data keys and the wrapping keys in bytes.
+const KEY_SIZE: usize = 32;
+
+/// Max overhead of a sealed chunk in bytes.
+const MAX_CHUNK_OVERHEAD: usize = 64;
+
+/// Default size of the plaintext chunks in bytes.
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Id of the key derived from the application secret key.
+const APP_KEY_ID: &str = "app";
+
+/// An operator which encrypts the objects at rest.
+///
+/// Each object is encrypted with a random data key using AES-GCM-SIV,
+/// and the data key is wrapped by the current key of the key ring.
+/// The plaintext is split into chunks so that large files can be written as streams.
+#[derive(Debug, Clone)]
+pub struct EncryptedOperator {
+    /// Operator.
+    operator: Operator,
+    /// Id of the current key for wrapping the data keys.
+    key_id: String,
+    /// Keys for wrapping the data keys.
+    keys: HashMap<String, Vec<u8>>,
+    /// Size of the plaintext chunks in bytes.
+    chunk_size: usize,
+}
+
+/// A writer which encrypts the data in chunks.
+pub struct EncryptedWriter {
+    /// Writer of the operator.
+    writer: Writer,
+    /// Data key.
+    data_key: [u8; KEY_SIZE],
+    /// Buffered plaintext.
+    buffer: Vec<u8>,
+    /// Size of the plaintext chunks in bytes.
+    chunk_size: usize,
+    /// Index of the next chunk.
+    index: u64,
+}
+
+/// A reader which decrypts the data in chunks.
+pub struct EncryptedReader {
+    /// Reader of the operator.
+    reader: Reader,
+    /// Data key.
+    data_key: Vec<u8>,
+    /// Size of the plaintext chunks in bytes.
+    chunk_size: usize,
+    /// Length of the next sealed chunk, or `None` if all the chunks have been read.
+    next_len: Option<usize>,
+    /// Index of the next chunk.
+    index: u64,
+}
+
+/// Header of an encrypted object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Header {
+    /// Id of the key which wraps the data key.
+    key_id: String,
+    /// Wrapped data key.
+    wrapped_key: Vec<u8>,
+    /// Size of the plaintext chunks in bytes.
+    chunk_size: u32,
+}
+
+impl EncryptedOperator {
+    /// Creates a new instance with the key derived from the application secret key.
+    #[inline]
+    pub fn new(operator: Operator) -> Self {
+        Self {
+            operator,
+            key_id: APP_KEY_ID.to_owned(),
+            keys: HashMap::new(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Attempts to create a new instance with the encryption config.
+    ///
+    /// The `keys` table maps the key ids to base64-encoded 32-byte keys, and `key-id`
+    /// specifies the current key. The key `app` refers to a key derived from
+    /// the application secret key by default.
+    pub fn try_new(operator: Operator, config: &Table) -> Result<Self, Error> {
+        let mut encrypted_operator = Self::new(operator);
+        if let Some(keys) = config.get_table("keys") {
+            for (key_id, key) in keys {
+                let key = key
+                    .as_str()
+                    .ok_or_else(|| Error::new(format!("the key `{key_id}` should be a str")))?;
+                encrypted_operator.add_key(key_id, base64::decode(key)?)?;
+            }
+        }
+        if let Some(key_id) = config.get_str("key-id") {
+            encrypted_operator.set_key_id(key_id)?;
+        }
+        if let Some(chunk_size) = config.get_usize("chunk-size") {
+            encrypted_operator.set_chunk_size(chunk_size)?;
+        }
+        Ok(encrypted_operator)
+    }
+
+    /// Adds a key for wrapping the data keys. The key should have 32 bytes.
+    pub fn add_key(&mut self, key_id: impl Into<String>, key: Vec<u8>) -> Result<(), Error> {
+        let key_id = key_id.into();
+        if key.len() != KEY_SIZE {
+            let message = format!("the key `{key_id}` should have {KEY_SIZE} bytes");
+            return Err(Error::new(message));
+        }
+        self.keys.insert(key_id, key);
+        Ok(())
+    }
+
+    /// Sets the id of the current key. The objects written afterwards will use it.
+    pub fn set_key_id(&mut self, key_id: impl Into<String>) -> Result<(), Error> {
+        let key_id = key_id.into();
+        if key_id.is_empty() || key_id.len() > usize::from(u8::MAX) {
+            return Err(Error::new(format!("key id `{key_id}` is invalid")));
+        }
+        if key_id != APP_KEY_ID && !self.keys.contains_key(&key_id) {
+            return Err(Error::new(format!("key `{key_id}` does not exist")));
+        }
+        self.key_id = key_id;
+        Ok(())
+    }
+
+    /// Sets the size of the plaintext chunks in bytes.
+    pub fn set_chunk_size(&mut self, chunk_size: usize) -> Result<(), Error> {
+        if chunk_size == 0 || u32::try_from(chunk_size).is_err() {
+            return Err(Error::new(format!("chunk size `{chunk_size}` is invalid")));
+        }
+        self.chunk_size = chunk_size;
+        Ok(())
+    }
+
+    /// Returns the id of the current key.
+    #[inline]
+    pub fn key_id(&self) -> &str {
+        self.key_id.as_str()
+    }
+
+    /// Returns a reference to the underlying operator.
+    #[inline]
+    pub fn operator(&self) -> &Operator {
+        &self.operator
+    }
+
+    /// Encrypts the data and writes it into the path.
+    pub async fn write(&self, path: &str, data: &[u8]) -> Result<(), Error> {
+        let mut writer = self.writer(path).await?;
+        writer.append(data).await?;
+        writer.close().await
+    }
+
+    /// Creates a writer which encrypts the data into the path.
+    pub async fn writer(&self, path: &str) -> Result<EncryptedWriter, Error> {
+        let mut data_key = [0u8; KEY_SIZE];
+        rand::thread_rng().fill(&mut data_key);
+
+        let header = Header {
+            key_id: self.key_id.clone(),
+            wrapped_key: self.wrap_key(&self.key_id, &data_key)?,
+            chunk_size: self.chunk_size as u32,
+        };
+        let mut writer = self.operator.writer(path).await?;
+        writer.append(header.encode()).await?;
+        Ok(EncryptedWriter {
+            writer,
+            data_key,
+            buffer: Vec::with_capacity(self.chunk_size),
+            chunk_size: self.chunk_size,
+            index: 0,
+        })
+    }
+
+    /// Reads the object in the path and decrypts it.
+    #[inline]
+    pub async fn read(&self, path: &str) -> Result<Vec<u8>, Error> {
+        self.read_range(path, 0, None).await
+    }
+
+    /// Reads the range of the plaintext with the offset and an optional size.
+    /// Only the chunks overlapping with the range are kept in memory,
+    /// and the remaining chunks are not read.
+    pub async fn read_range(
+        &self,
+        path: &str,
+        offset: u64,
+        size: Option<u64>,
+    ) -> Result<Vec<u8>, Error> {
+        let end = size.map(|size| offset.saturating_add(size));
+        let mut reader = self.reader(path).await?;
+        let mut plaintext = Vec::new();
+        let mut position = 0;
+        while end.map_or(true, |end| position < end) {
+            let Some(chunk) = reader.next_chunk().await? else {
+                break;
+            };
+            let chunk_start = position;
+            position += chunk.len() as u64;
+            if position <= offset {
+                continue;
+            }
+
+            let start = offset.saturating_sub(chunk_start) as usize;
+            let stop = end.map_or(chunk.len(), |end| {
+                (end - chunk_start).min(chunk.len() as u64) as usize
+            });
+            plaintext.extend_from_slice(&chunk[start..stop]);
+        }
+        Ok(plaintext)
+    }
+
+    /// Creates a reader which decrypts the object in the path chunk by chunk.
+    pub async fn reader(&self, path: &str) -> Result<EncryptedReader, Error> {
+        let mut reader = self.operator.reader(path).await?;
+        let header = Header::read_from(&mut reader)
+            .await?
+            .ok_or_else(|| Error::new("the object is not encrypted"))?;
+        let data_key = self.unwrap_key(&header.key_id, &header.wrapped_key)?;
+        let next_len = read_chunk_len(&mut reader)
+            .await?
+            .ok_or_else(|| Error::new("the encrypted object is truncated"))?;
+        Ok(EncryptedReader {
+            reader,
+            data_key,
+            chunk_size: header.chunk_size as usize,
+            next_len: Some(next_len),
+            index: 0,
+        })
+    }
+
+    /// Rewraps the data key of the object with the current key,
+    /// returning `false` if it has been wrapped with the current key.
+    /// The encrypted chunks are kept as they are.
+    pub async fn rotate(&self, path: &str) -> Result<bool, Error> {
+        self.rewrap(path)
+            .await?
+            .ok_or_else(|| Error::new("the object is not encrypted"))
+    }
+
+    /// Rewraps the data keys of all the encrypted objects under the prefix,
+    /// returning the number of rotated objects. Objects which are not encrypted are skipped.
+    pub async fn rotate_all(&self, prefix: &str) -> Result<usize, Error> {
+        let mut lister = self.operator.scan(prefix).await?;
+        let mut num_rotated = 0;
+        while let Some(entry) = lister.try_next().await? {
+            let path = entry.path();
+            if path.ends_with('/') {
+                continue;
+            }
+
+            if self.rewrap(path).await? == Some(true) {
+                num_rotated += 1;
+            }
+        }
+        Ok(num_rotated)
+    }
+
+    /// Rewraps the data key of the object in the path, returning `None` if it is not encrypted.
+    ///
+    /// Only the header is read for checking the key. The object is rewritten
+    /// if it has not been modified since the header was read, which is checked by
+    /// the ETag, last modified time and content length right before the write.
+    async fn rewrap(&self, path: &str) -> Result<Option<bool>, Error> {
+        let metadata = self.operator.stat(path).await?;
+        let mut reader = self.operator.reader(path).await?;
+        let Some(header) = Header::read_from(&mut reader).await? else {
+            return Ok(None);
+        };
+        drop(reader);
+        if header.key_id == self.key_id {
+            return Ok(Some(false));
+        }
+
+        let data_key = self.unwrap_key(&header.key_id, &header.wrapped_key)?;
+        let rotated_header = Header {
+            key_id: self.key_id.clone(),
+            wrapped_key: self.wrap_key(&self.key_id, &data_key)?,
+            chunk_size: header.chunk_size,
+        };
+        let offset = header.encode().len() as u64;
+        let mut rotated_data = rotated_header.encode();
+        rotated_data.extend(self.operator.range_read(path, offset..).await?);
+        if is_modified(&metadata, &self.operator.stat(path).await?) {
+            let message = format!("the object `{path}` has been modified during the rotation");
+            return Err(Error::new(message));
+        }
+        self.operator.write(path, rotated_data).await?;
+        Ok(Some(true))
+    }
+
+    /// Returns the key with the id.
+    fn get_key(&self, key_id: &str) -> Result<Cow<'_, [u8]>, Error> {
+        if let Some(key) = self.keys.get(key_id) {
+            Ok(Cow::Borrowed(key))
+        } else if key_id == APP_KEY_ID {
+            let key = application::derive_subkey("accessor-encryption")?;
+            Ok(Cow::Owned(key.to_vec()))
+        } else {
+            Err(Error::new(format!("key `{key_id}` does not exist")))
+        }
+    }
+
+    /// Wraps the data key with the key.
+    fn wrap_key(&self, key_id: &str, data_key: &[u8]) -> Result<Vec<u8>, Error> {
+        let key = self.get_key(key_id)?;
+        crypto::encrypt_with_aad(&key, data_key, key_id.as_bytes())
+            .map_err(|_| Error::new("fail to wrap the data key"))
+    }
+
+    /// Unwraps the data key with the key.
+    fn unwrap_key(&self, key_id: &str, wrapped_key: &[u8]) -> Result<Vec<u8>, Error> {
+        let key = self.get_key(key_id)?;
+        crypto::decrypt_with_aad(&key, wrapped_key, key_id.as_bytes())
+            .map_err(|_| Error::new(format!("fail to unwrap the data key with `{key_id}`")))
+    }
+}
+
+impl EncryptedWriter {
+    /// Appends the data, which is encrypted once a chunk is filled.
+    pub async fn append(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.buffer.extend_from_slice(data);
+        while self.buffer.len() > self.chunk_size {
+            let remaining = self.buffer.split_off(self.chunk_size);
+            let chunk = std::mem::replace(&mut self.buffer, remaining);
+            let sealed_chunk = seal_chunk(&self.data_key, self.index, false, &chunk)?;
+            self.writer.append(sealed_chunk).await?;
+            self.index += 1;
+        }
+        Ok(())
+    }
+
+    /// Encrypts the remaining data as the last chunk and closes the writer.
+    pub async fn close(mut self) -> Result<(), Error> {
+        let sealed_chunk = seal_chunk(&self.data_key, self.index, true, &self.buffer)?;
+        self.writer.append(sealed_chunk).await?;
+        self.writer.close().await.map_err(Error::from)
+    }
+
+    /// Aborts the writer and discards the data written.
+    pub async fn abort(mut self) -> Result<(), Error> {
+        self.writer.abort().await.map_err(Error::from)
+    }
+}
+
+impl EncryptedReader {
+    /// Reads and decrypts the next chunk, returning `None` if all the chunks have been read.
+    /// Reordered, truncated or extended chunks are rejected.
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        let Some(len) = self.next_len else {
+            return Ok(None);
+        };
+        if len > self.chunk_size + MAX_CHUNK_OVERHEAD {
+            return Err(Error::new("the encrypted chunk is too large"));
+        }
+
+        let mut ciphertext = vec![0; len];
+        self.reader.read_exact(&mut ciphertext).await?;
+        self.next_len = read_chunk_len(&mut self.reader).await?;
+
+        let last = self.next_len.is_none();
+        let chunk = open_chunk(&self.data_key, self.index, last, &ciphertext)?;
+        self.index += 1;
+        Ok(Some(chunk))
+    }
+}
+
+impl Header {
+    /// Returns `true` if the data starts with the magic bytes.
+    #[inline]
+    fn is_encrypted(data: &[u8]) -> bool {
+        data.starts_with(MAGIC)
+    }
+
+    /// Encodes the header as bytes.
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12 + self.key_id.len() + self.wrapped_key.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.key_id.len() as u8);
+        bytes.extend_from_slice(self.key_id.as_bytes());
+        bytes.extend_from_slice(&(self.wrapped_key.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(&self.wrapped_key);
+        bytes.extend_from_slice(&self.chunk_size.to_be_bytes());
+        bytes
+    }
+
+    /// Reads the header from the reader, returning `None` if the object is not encrypted.
+    async fn read_from(reader: &mut Reader) -> Result<Option<Self>, Error> {
+        let mut bytes = vec![0; MAGIC.len()];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            let num_bytes = reader.read(&mut bytes[filled..]).await?;
+            if num_bytes == 0 {
+                break;
+            }
+            filled += num_bytes;
+        }
+        if !Self::is_encrypted(&bytes[..filled]) {
+            return Ok(None);
+        }
+
+        read_more(reader, &mut bytes, 2).await?;
+        let key_id_len = usize::from(bytes[MAGIC.len() + 1]);
+        read_more(reader, &mut bytes, key_id_len + 2).await?;
+
+        let wrapped_key_len = u16::from_be_bytes(bytes[bytes.len() - 2..].try_into()?);
+        read_more(reader, &mut bytes, usize::from(wrapped_key_len) + 4).await?;
+        Self::parse(&bytes).map(|(header, _)| Some(header))
+    }
+
+    /// Parses the header, returning the length of it in bytes.
+    fn parse(data: &[u8]) -> Result<(Self, usize), Error> {
+        if !Self::is_encrypted(data) {
+            return Err(Error::new("the object is not encrypted"));
+        }
+
+        let mut cursor = MAGIC.len();
+        let version = read_bytes(data, &mut cursor, 1)?[0];
+        if version != VERSION {
+            let message = format!("encryption version `{version}` is unsupported");
+            return Err(Error::new(message));
+        }
+
+        let key_id_len = usize::from(read_bytes(data, &mut cursor, 1)?[0]);
+        let key_id = String::from_utf8(read_bytes(data, &mut cursor, key_id_len)?.to_vec())?;
+        let wrapped_key_len = u16::from_be_bytes(read_bytes(data, &mut cursor, 2)?.try_into()?);
+        let wrapped_key = read_bytes(data, &mut cursor, wrapped_key_len.into())?.to_vec();
+        let chunk_size = u32::from_be_bytes(read_bytes(data, &mut cursor, 4)?.try_into()?);
+        let header = Self {
+            key_id,
+            wrapped_key,
+            chunk_size,
+        };
+        Ok((header, cursor))
+    }
+}
+
+/// Reads the bytes with the length and advances the cursor.
+fn read_bytes<'a>(data: &'a [u8], cursor: &mut usize, len: usize) -> Result<&'a [u8], Error> {
+    let bytes = data
+        .get(*cursor..*cursor + len)
+        .ok_or_else(|| Error::new("the encrypted object is truncated"))?;
+    *cursor += len;
+    Ok(bytes)
+}
+
+/// Reads more bytes with the length from the reader.
+async fn read_more(reader: &mut Reader, bytes: &mut Vec<u8>, len: usize) -> Result<(), Error> {
+    let start = bytes.len();
+    bytes.resize(start + len, 0);
+    reader.read_exact(&mut bytes[start..]).await?;
+    Ok(())
+}
+
+/// Reads the length of the next sealed chunk, returning `None` at the end of the object.
+async fn read_chunk_len(reader: &mut Reader) -> Result<Option<usize>, Error> {
+    let mut bytes = [0u8; 4];
+    let mut filled = 0;
+    while filled < bytes.len() {
+        let num_bytes = reader.read(&mut bytes[filled..]).await?;
+        if num_bytes == 0 {
+            break;
+        }
+        filled += num_bytes;
+    }
+    match filled {
+        0 => Ok(None),
+        4 => Ok(Some(u32::from_be_bytes(bytes) as usize)),
+        _ => Err(Error::new("the encrypted object is truncated")),
+    }
+}
+
+/// Returns `true` if the object has been modified according to the metadata.
+fn is_modified(metadata: &Metadata, current_metadata: &Metadata) -> bool {
+    metadata.etag() != current_metadata.etag()
+        || metadata.last_modified() != current_metadata.last_modified()
+        || metadata.content_length() != current_metadata.content_length()
+}
+
+/// Returns the associated data of a chunk.
+fn chunk_aad(index: u64, last: bool) -> [u8; 9] {
+    let mut aad = [0u8; 9];
+    aad[..8].copy_from_slice(&index.to_be_bytes());
+    aad[8] = u8::from(last);
+    aad
+}
+
+/// Encrypts a chunk with the data key, prefixed by the length of the ciphertext.
+fn seal_chunk(data_key: &[u8], index: u64, last: bool, chunk: &[u8]) -> Result<Vec<u8>, Error> {
+    let ciphertext = crypto::encrypt_with_aad(data_key, chunk, &chunk_aad(index, last))
+        .map_err(|_| Error::new("fail to encrypt the data"))?;
+    let mut sealed_chunk = Vec::with_capacity(4 + ciphertext.len());
+    sealed_chunk.extend_from_slice(&(ciphertext.len() as u32).to_be_bytes());
+    sealed_chunk.extend_from_slice(&ciphertext);
+    Ok(sealed_chunk)
+}
+
+/// Decrypts a chunk with the data key.
+fn open_chunk(
+    data_key: &[u8],
+    index: u64,
+    last: bool,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, Error> {
+    crypto::decrypt_with_aad(data_key, ciphertext, &chunk_aad(index, last))
+        .map_err(|_| Error::new("fail to decrypt the data"))
+}
+
+/// A layer which encrypts the objects written and decrypts the objects read
+/// through the operator transparently.
+///
+/// The appended data is encrypted as a stream, while a read buffers the requested range
+/// of the plaintext in memory. Large objects should be read by ranges,
+/// or as streams via [`EncryptedOperator::reader`].
+#[derive(Debug, Clone)]
+pub(super) struct EncryptionLayer {
+    /// Encrypted operator.
+    encrypted_operator: EncryptedOperator,
+}
+
+impl EncryptionLayer {
+    /// Creates a new instance.
+    #[inline]
+    pub(super) fn new(encrypted_operator: EncryptedOperator) -> Self {
+        Self { encrypted_operator }
+    }
+}
+
+impl<A: Accessor> Layer<A> for EncryptionLayer {
+    type LayeredAccessor = EncryptionAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        EncryptionAccessor {
+            inner,
+            encrypted_operator: self.encrypted_operator.clone(),
+        }
+    }
+}
+
+/// An accessor layered by [`EncryptionLayer`].
+#[derive(Debug)]
+pub(super) struct EncryptionAccessor<A: Accessor> {
+    /// Inner accessor.
+    inner: A,
+    /// Encrypted operator.
+    encrypted_operator: EncryptedOperator,
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for EncryptionAccessor<A> {
+    type Inner = A;
+    type Reader = oio::Cursor;
+    type BlockingReader = A::BlockingReader;
+    type Writer = EncryptionWriter;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+        let range = args.range();
+        let plaintext = match (range.offset(), range.size()) {
+            (None, Some(len)) => {
+                let mut plaintext = self.encrypted_operator.read(path).await;
+                if let Ok(plaintext) = plaintext.as_mut() {
+                    let start = plaintext.len().saturating_sub(len as usize);
+                    plaintext.drain(..start);
+                }
+                plaintext
+            }
+            (offset, size) => {
+                let offset = offset.unwrap_or_default();
+                self.encrypted_operator.read_range(path, offset, size).await
+            }
+        }
+        .map_err(into_opendal_error)?;
+        Ok((
+            RpRead::new(plaintext.len() as u64),
+            oio::Cursor::from(plaintext),
+        ))
+    }
+
+    async fn write(&self, path: &str, _args: OpWrite) -> opendal::Result<(RpWrite, Self::Writer)> {
+        let writer = EncryptionWriter {
+            encrypted_operator: self.encrypted_operator.clone(),
+            path: path.to_owned(),
+            writer: None,
+            written: false,
+        };
+        Ok((RpWrite::default(), writer))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn scan(&self, path: &str, args: OpScan) -> opendal::Result<(RpScan, Self::Pager)> {
+        self.inner.scan(path, args).await
+    }
+
+    fn blocking_read(
+        &self,
+        _path: &str,
+        _args: OpRead,
+    ) -> opendal::Result<(RpRead, Self::BlockingReader)> {
+        Err(blocking_unsupported())
+    }
+
+    fn blocking_write(
+        &self,
+        _path: &str,
+        _args: OpWrite,
+    ) -> opendal::Result<(RpWrite, Self::BlockingWriter)> {
+        Err(blocking_unsupported())
+    }
+
+    fn blocking_list(
+        &self,
+        path: &str,
+        args: OpList,
+    ) -> opendal::Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+
+    fn blocking_scan(
+        &self,
+        path: &str,
+        args: OpScan,
+    ) -> opendal::Result<(RpScan, Self::BlockingPager)> {
+        self.inner.blocking_scan(path, args)
+    }
+}
+
+/// A writer which encrypts the appended data as a stream.
+pub(super) struct EncryptionWriter {
+    /// Encrypted operator.
+    encrypted_operator: EncryptedOperator,
+    /// Path of the object.
+    path: String,
+    /// Writer for the appended data.
+    writer: Option<EncryptedWriter>,
+    /// A flag indicating whether the object has been written or aborted.
+    written: bool,
+}
+
+#[async_trait]
+impl oio::Write for EncryptionWriter {
+    async fn write(&mut self, bs: Bytes) -> opendal::Result<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.abort().await.map_err(into_opendal_error)?;
+        }
+        self.written = true;
+        self.encrypted_operator
+            .write(&self.path, &bs)
+            .await
+            .map_err(into_opendal_error)
+    }
+
+    async fn append(&mut self, bs: Bytes) -> opendal::Result<()> {
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => {
+                let writer = self
+                    .encrypted_operator
+                    .writer(&self.path)
+                    .await
+                    .map_err(into_opendal_error)?;
+                self.writer.insert(writer)
+            }
+        };
+        writer.append(&bs).await.map_err(into_opendal_error)
+    }
+
+    async fn abort(&mut self) -> opendal::Result<()> {
+        self.written = true;
+        if let Some(writer) = self.writer.take() {
+            writer.abort().await.map_err(into_opendal_error)?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> opendal::Result<()> {
+        if let Some(writer) = self.writer.take() {
+            self.written = true;
+            writer.close().await.map_err(into_opendal_error)?;
+        } else if !self.written {
+            self.written = true;
+            self.encrypted_operator
+                .write(&self.path, &[])
+                .await
+                .map_err(into_opendal_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts the error into an opendal error.
+fn into_opendal_error(err: Error) -> opendal::Error {
+    opendal::Error::new(ErrorKind::Unexpected, &err.to_string())
+}
+
+/// Returns an error for the blocking operations.
+fn blocking_unsupported() -> opendal::Error {
+    let message = "blocking operations are unsupported for the encrypted accessor";
+    opendal::Error::new(ErrorKind::Unsupported, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open_chunk, seal_chunk, EncryptedOperator, Header};
+    use opendal::{services::Memory, Operator};
+
+    #[test]
+    fn it_encodes_headers() {
+        let header = Header {
+            key_id: "2023-03".to_owned(),
+            wrapped_key: vec![7; 60],
+            chunk_size: 65536,
+        };
+        let bytes = header.encode();
+        assert_eq!(Header::parse(&bytes).unwrap(), (header, bytes.len()));
+        assert!(Header::parse(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn it_seals_and_opens_chunks() {
+        let data_key = [1u8; 32];
+        let sealed_chunk = seal_chunk(&data_key, 0, false, b"hello ").unwrap();
+        let ciphertext = &sealed_chunk[4..];
+        assert_eq!(
+            open_chunk(&data_key, 0, false, ciphertext).unwrap(),
+            b"hello "
+        );
+        assert!(open_chunk(&data_key, 0, true, ciphertext).is_err());
+        assert!(open_chunk(&data_key, 1, false, ciphertext).is_err());
+    }
+
+    #[test]
+    fn it_reads_ranges_and_rotates_keys() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let operator = Operator::new(Memory::default()).unwrap().finish();
+            let mut encrypted_operator = EncryptedOperator::new(operator.clone());
+            encrypted_operator.add_key("2023-03", vec![1; 32]).unwrap();
+            encrypted_operator.add_key("2023-04", vec![2; 32]).unwrap();
+            encrypted_operator.set_key_id("2023-03").unwrap();
+            encrypted_operator.set_chunk_size(4).unwrap();
+            encrypted_operator
+                .write("data/hello.txt", b"hello, world")
+                .await
+                .unwrap();
+            operator.write("data/plain.txt", "hi").await.unwrap();
+
+            let path = "data/hello.txt";
+            let plaintext = encrypted_operator.read_range(path, 3, Some(6)).await;
+            assert_eq!(plaintext.unwrap(), b"lo, wo");
+            let plaintext = encrypted_operator.read_range(path, 8, None).await;
+            assert_eq!(plaintext.unwrap(), b"orld");
+            let plaintext = encrypted_operator.read_range(path, 20, Some(2)).await;
+            assert!(plaintext.unwrap().is_empty());
+
+            encrypted_operator.set_key_id("2023-04").unwrap();
+            assert_eq!(encrypted_operator.rotate_all("data/").await.unwrap(), 1);
+            assert!(!encrypted_operator.rotate(path).await.unwrap());
+            assert!(encrypted_operator.rotate("data/plain.txt").await.is_err());
+            let plaintext = encrypted_operator.read(path).await;
+            assert_eq!(plaintext.unwrap(), b"hello, world");
+        });
+    }
+}
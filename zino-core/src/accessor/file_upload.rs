@@ -0,0 +1,337 @@
+use super::{EncryptedOperator, EncryptedWriter};
+use crate::{error::Error, extend::JsonObjectExt, Map, Uuid};
+use bytes::Bytes;
+use multer::Multipart;
+use opendal::{Operator, Writer};
+use sha2::{Digest, Sha256};
+
+/// Options for uploading files to a storage accessor.
+#[derive(Debug, Clone, Default)]
+pub struct UploadOptions {
+    /// Prefix of the object paths.
+    path_prefix: String,
+    /// Maximum size of a file in bytes.
+    max_size: Option<u64>,
+    /// Allowed content types. The wildcard subtype such as `image/*` is supported.
+    allowed_types: Vec<String>,
+}
+
+impl UploadOptions {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the prefix of the object paths.
+    #[inline]
+    pub fn set_path_prefix(&mut self, path_prefix: impl Into<String>) {
+        self.path_prefix = path_prefix.into();
+    }
+
+    /// Sets the maximum size of a file in bytes.
+    #[inline]
+    pub fn set_max_size(&mut self, max_size: u64) {
+        self.max_size = Some(max_size);
+    }
+
+    /// Sets the allowed content types.
+    #[inline]
+    pub fn set_allowed_types(&mut self, allowed_types: Vec<String>) {
+        self.allowed_types = allowed_types;
+    }
+
+    /// Returns `true` if the content type is allowed.
+    pub fn is_allowed_type(&self, content_type: &str) -> bool {
+        self.allowed_types.is_empty()
+            || self.allowed_types.iter().any(|allowed_type| {
+                if let Some(prefix) = allowed_type.strip_suffix("/*") {
+                    content_type
+                        .split_once('/')
+                        .map_or(false, |(main_type, _)| main_type == prefix)
+                } else {
+                    allowed_type == content_type
+                }
+            })
+    }
+
+    /// Formats the object path for the file.
+    fn format_path(&self, file_name: &str) -> String {
+        let prefix = self.path_prefix.trim_matches('/');
+        let file_name = match file_name.rsplit(['/', '\\']).next() {
+            Some("" | "." | "..") | None => "file",
+            Some(file_name) => file_name,
+        };
+        let id = Uuid::new_v4();
+        if prefix.is_empty() {
+            format!("{id}/{file_name}")
+        } else {
+            format!("{prefix}/{id}/{file_name}")
+        }
+    }
+}
+
+/// A file which has been uploaded to a storage accessor.
+#[derive(Debug, Clone)]
+pub struct UploadedFile {
+    /// Field name in the multipart form.
+    field_name: String,
+    /// File name.
+    file_name: String,
+    /// Content type.
+    content_type: String,
+    /// Name of the accessor.
+    accessor: String,
+    /// Object path in the accessor.
+    path: String,
+    /// File size in bytes.
+    size: u64,
+    /// SHA-256 checksum as a hex string.
+    checksum: String,
+}
+
+impl UploadedFile {
+    /// Returns the field name in the multipart form.
+    #[inline]
+    pub fn field_name(&self) -> &str {
+        self.field_name.as_str()
+    }
+
+    /// Returns the file name.
+    #[inline]
+    pub fn file_name(&self) -> &str {
+        self.file_name.as_str()
+    }
+
+    /// Returns the content type.
+    #[inline]
+    pub fn content_type(&self) -> &str {
+        self.content_type.as_str()
+    }
+
+    /// Returns the name of the accessor.
+    #[inline]
+    pub fn accessor(&self) -> &str {
+        self.accessor.as_str()
+    }
+
+    /// Returns the object path in the accessor.
+    #[inline]
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    /// Returns the file size in bytes.
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the SHA-256 checksum as a hex string.
+    #[inline]
+    pub fn checksum(&self) -> &str {
+        self.checksum.as_str()
+    }
+
+    /// Returns the location as an URL with the `opendal` scheme.
+    #[inline]
+    pub fn location(&self) -> String {
+        format!("opendal://{}/{}", self.accessor, self.path)
+    }
+
+    /// Converts `self` to a JSON object which can be read by the `Resource` model.
+    pub fn to_resource_map(&self) -> Map {
+        let mut content = Map::new();
+        content.upsert("accessor", self.accessor());
+        content.upsert("path", self.path());
+        content.upsert("size", self.size());
+        content.upsert("checksum", self.checksum());
+
+        let category = self
+            .content_type
+            .split_once('/')
+            .map_or("application", |(main_type, _)| main_type);
+        let mut map = Map::new();
+        map.upsert("name", self.file_name());
+        map.upsert("category", category);
+        map.upsert("mime_type", self.content_type());
+        map.upsert("location", self.location());
+        map.upsert("content", content);
+        map
+    }
+}
+
+/// Writer of an uploaded file.
+enum FileWriter {
+    /// Writer for the plaintext.
+    Plain(Writer),
+    /// Writer which encrypts the data.
+    Encrypted(EncryptedWriter),
+}
+
+impl FileWriter {
+    /// Appends the chunk of the file.
+    async fn append(&mut self, chunk: Bytes) -> Result<(), Error> {
+        match self {
+            Self::Plain(writer) => writer.append(chunk).await.map_err(Error::from),
+            Self::Encrypted(writer) => writer.append(&chunk).await,
+        }
+    }
+
+    /// Closes the writer.
+    async fn close(self) -> Result<(), Error> {
+        match self {
+            Self::Plain(mut writer) => writer.close().await.map_err(Error::from),
+            Self::Encrypted(writer) => writer.close().await,
+        }
+    }
+
+    /// Aborts the writer and discards the data written.
+    async fn abort(self) -> Result<(), Error> {
+        match self {
+            Self::Plain(mut writer) => writer.abort().await.map_err(Error::from),
+            Self::Encrypted(writer) => writer.abort().await,
+        }
+    }
+}
+
+/// Streams the files in the multipart form into the operator.
+/// The files are encrypted if the encrypted operator is provided.
+/// If any file is rejected, the files uploaded from the same form will be removed.
+pub(super) async fn upload_multipart(
+    accessor: &str,
+    operator: &Operator,
+    encrypted_operator: Option<&EncryptedOperator>,
+    multipart: Multipart<'_>,
+    options: &UploadOptions,
+) -> Result<Vec<UploadedFile>, Error> {
+    let mut files = Vec::new();
+    let result = upload_fields(
+        accessor,
+        operator,
+        encrypted_operator,
+        multipart,
+        options,
+        &mut files,
+    )
+    .await;
+    if let Err(err) = result {
+        for file in files.iter() {
+            let path = file.path();
+            if let Err(err) = operator.delete(path).await {
+                tracing::warn!("fail to remove the uploaded file `{path}`: {err}");
+            }
+        }
+        return Err(err);
+    }
+    Ok(files)
+}
+
+/// Streams the fields of files in the multipart form and pushes the uploaded files.
+async fn upload_fields(
+    accessor: &str,
+    operator: &Operator,
+    encrypted_operator: Option<&EncryptedOperator>,
+    mut multipart: Multipart<'_>,
+    options: &UploadOptions,
+    files: &mut Vec<UploadedFile>,
+) -> Result<(), Error> {
+    while let Some(mut field) = multipart.next_field().await? {
+        let Some(file_name) = field.file_name().map(|s| s.to_owned()) else {
+            continue;
+        };
+        let field_name = field.name().unwrap_or_default().to_owned();
+        let content_type = field
+            .content_type()
+            .map(|mime| mime.essence_str().to_owned())
+            .unwrap_or_else(|| "application/octet-stream".to_owned());
+        if !options.is_allowed_type(&content_type) {
+            let message = format!("content type `{content_type}` of `{file_name}` is not allowed");
+            return Err(Error::new(message));
+        }
+
+        let path = options.format_path(&file_name);
+        let mut writer = if let Some(encrypted_operator) = encrypted_operator {
+            FileWriter::Encrypted(encrypted_operator.writer(&path).await?)
+        } else {
+            FileWriter::Plain(operator.writer(&path).await?)
+        };
+        let mut hasher = Sha256::new();
+        let mut size = 0;
+        let result: Result<(), Error> = async {
+            while let Some(chunk) = field.chunk().await? {
+                size += chunk.len() as u64;
+                if let Some(max_size) = options.max_size
+                    && size > max_size
+                {
+                    let message =
+                        format!("size of `{file_name}` exceeds the limit of {max_size} bytes");
+                    return Err(Error::new(message));
+                }
+                hasher.update(&chunk);
+                writer.append(chunk).await?;
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(err) = result {
+            if let Err(err) = writer.abort().await {
+                tracing::warn!("fail to abort the upload of `{path}`: {err}");
+            }
+            operator.delete(&path).await.ok();
+            return Err(err);
+        }
+        writer.close().await?;
+
+        files.push(UploadedFile {
+            field_name,
+            file_name,
+            content_type,
+            accessor: accessor.to_owned(),
+            path,
+            size,
+            checksum: format!("{:x}", hasher.finalize()),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UploadOptions;
+
+    #[test]
+    fn it_checks_allowed_types() {
+        let mut options = UploadOptions::new();
+        assert!(options.is_allowed_type("application/x-msdownload"));
+
+        options.set_allowed_types(vec!["image/*".to_owned(), "application/pdf".to_owned()]);
+        assert!(options.is_allowed_type("image/png"));
+        assert!(options.is_allowed_type("application/pdf"));
+        assert!(!options.is_allowed_type("application/pdf+zip"));
+        assert!(!options.is_allowed_type("imagex/png"));
+        assert!(!options.is_allowed_type("image"));
+        assert!(!options.is_allowed_type("text/plain"));
+    }
+
+    #[test]
+    fn it_formats_paths() {
+        let mut options = UploadOptions::new();
+        let path = options.format_path("avatar.png");
+        let (id, file_name) = path.split_once('/').unwrap();
+        assert_eq!(id.len(), 36);
+        assert_eq!(file_name, "avatar.png");
+
+        options.set_path_prefix("/uploads/");
+        let path = options.format_path("../../etc/passwd");
+        assert!(path.starts_with("uploads/"));
+        assert!(path.ends_with("/passwd"));
+        assert_eq!(path.split('/').count(), 3);
+
+        let path = options.format_path("C:\\Users\\alice\\report.pdf");
+        assert!(path.ends_with("/report.pdf"));
+
+        let path = options.format_path("uploads/..");
+        assert!(path.ends_with("/file"));
+    }
+}
@@ -24,18 +24,80 @@
 //! | `webdav`      | WebDAV services.                         | `accessor`            |
 //! | `webhdfs`     | WebHDFS services.                        | `accessor`            |
 //!
+//! ## Layers
+//!
+//! The operators are constructed from the `[[accessor]]` entries of the config at boot,
+//! and can be fetched by [`GlobalAccessor::get`] with the `name`. Layers are configured
+//! with the following fields in each entry:
+//!
+//! - `timeout` and `connect-timeout`: timeouts of HTTP requests for `azblob`, `azdfs`,
+//!   `gcs`, `obs`, `oss` and `s3` services;
+//! - `max-retries` (3 by default), `retry-min-delay`, `retry-max-delay` and `retry-jitter`:
+//!   retrying with exponential backoff, which is disabled if `max-retries` is `0`;
+//! - `concurrency-limit`: maximum number of concurrent operations;
+//! - `tracing` (`true` by default), `metrics` (`true` by default) and `logging`:
+//!   toggles of the tracing, metrics and logging layers.
+//!
+//! ## Content-addressed storage
+//!
+//! [`ContentStore`] writes blobs under their SHA-256 digests on top of an operator,
+//! so identical uploads are deduplicated. The integrity is verified when a blob is read,
+//! and the digest can be signed with the application secret key. The resulting
+//! [`ContentEntry`] provides the `integrity` and `signature` of a `Record` model.
+//! Unreferenced blobs can be removed by [`ContentStore::collect_garbage`].
+//!
+//! ## Encryption at rest
+//!
+//! An `[accessor.encryption]` table in the entry enables the server-side encryption
+//! for the operator. Each object is encrypted in chunks with a random data key
+//! using AES-GCM-SIV, and the data key is wrapped by the key with the id `key-id`.
+//! The keys are specified in the `keys` table as base64-encoded 32-byte strings,
+//! and the id `app` refers to a key derived from the application secret key by default.
+//! After changing `key-id`, the data keys can be rewrapped by
+//! [`EncryptedOperator::rotate_all`] without reencrypting the data.
+//!
+//! The objects are encrypted and decrypted transparently by the operator fetched by
+//! [`GlobalAccessor::get`]. The appended data is encrypted as a stream, but a read
+//! buffers the requested range in memory, so large files should be read by ranges or
+//! as streams via the [`EncryptedOperator`] fetched by [`GlobalAccessor::get_encrypted`].
+//! The rotation reads only the headers for checking the keys, and an object modified
+//! during its rotation is not overwritten. Presigned requests are rejected for the encrypted
+//! accessors since the clients would access the ciphertext directly.
+//!
+//! ## File uploads
+//!
+//! The files in a multipart form can be streamed into a named operator by
+//! [`GlobalAccessor::upload_multipart`], with the size limit and content types
+//! specified by [`UploadOptions`]. The uploaded file can be recorded as a `Resource` model
+//! via [`UploadedFile::to_resource_map`]. For the services supporting presigned requests,
+//! such as `azblob`, `gcs`, `obs`, `oss` and `s3`, the files can also be downloaded
+//! or uploaded directly by the clients via [`GlobalAccessor::presign_download`]
+//! and [`GlobalAccessor::presign_upload`].
+//!
 
 use crate::{extend::TomlTableExt, state::State};
+use multer::Multipart;
 use opendal::{
-    layers::{MetricsLayer, RetryLayer, TracingLayer},
+    layers::{ConcurrentLimitLayer, LoggingLayer, MetricsLayer, RetryLayer, TracingLayer},
+    raw::{HttpClient, PresignedRequest},
     services::{Azblob, Azdfs, Fs, Gcs, Ghac, Ipmfs, Memory, Obs, Oss, Webdav, Webhdfs, S3},
     Error,
-    ErrorKind::Unsupported,
+    ErrorKind::{NotFound, Unsupported},
     Operator,
 };
-use std::{collections::HashMap, sync::LazyLock};
+use std::{collections::HashMap, sync::LazyLock, time::Duration};
 use toml::Table;
 
+mod content_store;
+mod encryption;
+mod file_upload;
+
+use encryption::EncryptionLayer;
+
+pub use content_store::{ContentEntry, ContentStore};
+pub use encryption::{EncryptedOperator, EncryptedReader, EncryptedWriter};
+pub use file_upload::{UploadOptions, UploadedFile};
+
 #[cfg(feature = "accessor-dashmap")]
 use opendal::services::Dashmap;
 #[cfg(feature = "accessor-ftp")]
@@ -57,6 +119,7 @@ impl GlobalAccessor {
     /// Constructs a new operator with the configuration for the specific storage service,
     /// returning an error if it fails.
     pub fn try_new_operator(scheme: &'static str, config: &Table) -> Result<Operator, Error> {
+        let http_client = Self::build_http_client(config)?;
         let operator = match scheme {
             "azblob" => {
                 let mut builder = Azblob::default();
@@ -78,6 +141,9 @@ impl GlobalAccessor {
                 if let Some(sas_token) = config.get_str("sas-token") {
                     builder.sas_token(sas_token);
                 }
+                if let Some(http_client) = http_client {
+                    builder.http_client(http_client);
+                }
                 Ok(Operator::new(builder)?.finish())
             }
             "azdfs" => {
@@ -97,6 +163,9 @@ impl GlobalAccessor {
                 if let Some(account_key) = config.get_str("account-key") {
                     builder.account_key(account_key);
                 }
+                if let Some(http_client) = http_client {
+                    builder.http_client(http_client);
+                }
                 Ok(Operator::new(builder)?.finish())
             }
             #[cfg(feature = "accessor-dashmap")]
@@ -151,6 +220,9 @@ impl GlobalAccessor {
                 if let Some(credential_path) = config.get_str("credential-path") {
                     builder.credential_path(credential_path);
                 }
+                if let Some(http_client) = http_client {
+                    builder.http_client(http_client);
+                }
                 Ok(Operator::new(builder)?.finish())
             }
             "ghac" => {
@@ -242,6 +314,9 @@ impl GlobalAccessor {
                 if let Some(secret_access_key) = config.get_str("secret_access_key") {
                     builder.secret_access_key(secret_access_key);
                 }
+                if let Some(http_client) = http_client {
+                    builder.http_client(http_client);
+                }
                 Ok(Operator::new(builder)?.finish())
             }
             "oss" => {
@@ -264,6 +339,9 @@ impl GlobalAccessor {
                 if let Some(access_key_secret) = config.get_str("access-key-secret") {
                     builder.access_key_secret(access_key_secret);
                 }
+                if let Some(http_client) = http_client {
+                    builder.http_client(http_client);
+                }
                 Ok(Operator::new(builder)?.finish())
             }
             #[cfg(feature = "accessor-redis")]
@@ -315,6 +393,9 @@ impl GlobalAccessor {
                 if let Some(external_id) = config.get_str("external-id") {
                     builder.external_id(external_id);
                 }
+                if let Some(http_client) = http_client {
+                    builder.http_client(http_client);
+                }
                 Ok(Operator::new(builder)?.finish())
             }
             "webdav" => {
@@ -348,39 +429,196 @@ impl GlobalAccessor {
             }
             _ => Err(Error::new(Unsupported, "scheme is unsupported")),
         };
-        operator.map(|op| {
-            op.layer(TracingLayer)
-                .layer(MetricsLayer)
-                .layer(RetryLayer::new())
-        })
+        operator.map(|op| Self::apply_layers(op, config))
+    }
+
+    /// Builds an HTTP client with the timeouts for the storage service.
+    fn build_http_client(config: &Table) -> Result<Option<HttpClient>, Error> {
+        let timeout = config.get_duration("timeout");
+        let connect_timeout = config.get_duration("connect-timeout");
+        if timeout.is_none() && connect_timeout.is_none() {
+            return Ok(None);
+        }
+
+        let mut client_builder = reqwest::Client::builder();
+        if let Some(timeout) = timeout {
+            client_builder = client_builder.timeout(timeout);
+        }
+        if let Some(connect_timeout) = connect_timeout {
+            client_builder = client_builder.connect_timeout(connect_timeout);
+        }
+        HttpClient::build(client_builder).map(Some)
+    }
+
+    /// Applies the layers to the operator according to the configuration.
+    fn apply_layers(mut operator: Operator, config: &Table) -> Operator {
+        if let Some(permits) = config.get_usize("concurrency-limit") {
+            operator = operator.layer(ConcurrentLimitLayer::new(permits));
+        }
+        if config.get_bool("tracing").unwrap_or(true) {
+            operator = operator.layer(TracingLayer);
+        }
+        if config.get_bool("metrics").unwrap_or(true) {
+            operator = operator.layer(MetricsLayer);
+        }
+        if config.get_bool("logging").unwrap_or(false) {
+            operator = operator.layer(LoggingLayer::default());
+        }
+
+        let max_retries = config.get_usize("max-retries").unwrap_or(3);
+        if max_retries > 0 {
+            let mut retry_layer = RetryLayer::new().with_max_times(max_retries);
+            if let Some(min_delay) = config.get_duration("retry-min-delay") {
+                retry_layer = retry_layer.with_min_delay(min_delay);
+            }
+            if let Some(max_delay) = config.get_duration("retry-max-delay") {
+                retry_layer = retry_layer.with_max_delay(max_delay);
+            }
+            if config.get_bool("retry-jitter").unwrap_or(false) {
+                retry_layer = retry_layer.with_jitter();
+            }
+            operator = operator.layer(retry_layer);
+        }
+        operator
     }
 
     /// Gets the operator for the specific storage service.
+    /// The objects are encrypted and decrypted transparently if the encryption is enabled.
     #[inline]
-    pub fn get(name: &'static str) -> Option<&'static Operator> {
+    pub fn get(name: &str) -> Option<&'static Operator> {
         GLOBAL_ACCESSOR.get(name)
     }
+
+    /// Attempts to get the operator for the specific storage service.
+    #[inline]
+    pub fn try_get(name: &str) -> Result<&'static Operator, Error> {
+        Self::get(name).ok_or_else(|| Error::new(NotFound, "accessor does not exist"))
+    }
+
+    /// Gets the encrypted operator for the specific storage service,
+    /// returning `None` if the encryption is not enabled.
+    #[inline]
+    pub fn get_encrypted(name: &str) -> Option<&'static EncryptedOperator> {
+        GLOBAL_ENCRYPTED_ACCESSOR.get(name)
+    }
+
+    /// Returns the names of all the operators.
+    pub fn names() -> Vec<&'static str> {
+        let mut names = GLOBAL_ACCESSOR.keys().copied().collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Streams the files in the multipart form into the operator for the storage service.
+    /// The size and SHA-256 checksum of each file are computed while uploading.
+    pub async fn upload_multipart(
+        name: &str,
+        multipart: Multipart<'_>,
+        options: &UploadOptions,
+    ) -> Result<Vec<UploadedFile>, crate::error::Error> {
+        let operator = Self::get(name)
+            .ok_or_else(|| crate::error::Error::new(format!("accessor `{name}` does not exist")))?;
+        let encrypted_operator = Self::get_encrypted(name);
+        file_upload::upload_multipart(name, operator, encrypted_operator, multipart, options).await
+    }
+
+    /// Generates a presigned request to download the object,
+    /// returning an error if the storage service does not support it
+    /// or the encryption is enabled.
+    pub fn presign_download(
+        name: &str,
+        path: &str,
+        expires_in: Duration,
+    ) -> Result<PresignedRequest, Error> {
+        Self::check_presign(name)?;
+        Self::try_get(name)?.presign_read(path, expires_in)
+    }
+
+    /// Generates a presigned request to upload the object,
+    /// returning an error if the storage service does not support it
+    /// or the encryption is enabled.
+    pub fn presign_upload(
+        name: &str,
+        path: &str,
+        expires_in: Duration,
+    ) -> Result<PresignedRequest, Error> {
+        Self::check_presign(name)?;
+        Self::try_get(name)?.presign_write(path, expires_in)
+    }
+
+    /// Checks whether the presigned requests are supported for the accessor.
+    fn check_presign(name: &str) -> Result<(), Error> {
+        if Self::get_encrypted(name).is_some() {
+            let message = "presigned requests are unsupported for the encrypted accessor";
+            return Err(Error::new(Unsupported, message));
+        }
+        Ok(())
+    }
+}
+
+/// Initializes the global storage accessor.
+pub(crate) fn init() {
+    let names = GlobalAccessor::names();
+    tracing::info!("storage accessors `{}` are ready", names.join("`, `"));
+
+    let mut encrypted_names = GLOBAL_ENCRYPTED_ACCESSOR
+        .keys()
+        .copied()
+        .collect::<Vec<_>>();
+    if !encrypted_names.is_empty() {
+        encrypted_names.sort_unstable();
+        tracing::info!(
+            "encryption is enabled for `{}`",
+            encrypted_names.join("`, `")
+        );
+    }
 }
 
 /// Global storage accessor.
 static GLOBAL_ACCESSOR: LazyLock<HashMap<&'static str, Operator>> = LazyLock::new(|| {
     let mut operators = HashMap::new();
-    let memory_operator = Operator::new(Memory::default())
-        .expect("fail to create an operator for the memory accessor")
-        .layer(TracingLayer)
-        .layer(MetricsLayer)
-        .layer(RetryLayer::new())
-        .finish();
+    let memory_operator = GlobalAccessor::try_new_operator("memory", &Table::new())
+        .expect("fail to create an operator for the memory accessor");
     operators.insert("memory", memory_operator);
 
     if let Some(accessors) = State::shared().config().get_array("accessor") {
         for accessor in accessors.iter().filter_map(|v| v.as_table()) {
             let scheme = accessor.get_str("scheme").unwrap_or("unkown");
             let name = accessor.get_str("name").unwrap_or(scheme);
-            let operator = GlobalAccessor::try_new_operator(scheme, accessor)
-                .unwrap_or_else(|err| panic!("fail to build `{scheme}` operator: {err}"));
+            let operator = if let Some(encrypted_operator) = GlobalAccessor::get_encrypted(name) {
+                let encryption_layer = EncryptionLayer::new(encrypted_operator.clone());
+                encrypted_operator
+                    .operator()
+                    .clone()
+                    .layer(encryption_layer)
+            } else {
+                GlobalAccessor::try_new_operator(scheme, accessor)
+                    .unwrap_or_else(|err| panic!("fail to build `{scheme}` operator: {err}"))
+            };
             operators.insert(name, operator);
         }
     }
     operators
 });
+
+/// Global encrypted storage accessor.
+static GLOBAL_ENCRYPTED_ACCESSOR: LazyLock<HashMap<&'static str, EncryptedOperator>> =
+    LazyLock::new(|| {
+        let mut encrypted_operators = HashMap::new();
+        if let Some(accessors) = State::shared().config().get_array("accessor") {
+            for accessor in accessors.iter().filter_map(|v| v.as_table()) {
+                let scheme = accessor.get_str("scheme").unwrap_or("unkown");
+                let name = accessor.get_str("name").unwrap_or(scheme);
+                if let Some(config) = accessor.get_table("encryption") {
+                    let operator = GlobalAccessor::try_new_operator(scheme, accessor)
+                        .unwrap_or_else(|err| panic!("fail to build `{scheme}` operator: {err}"));
+                    let encrypted_operator = EncryptedOperator::try_new(operator, config)
+                        .unwrap_or_else(|err| {
+                            panic!("fail to enable encryption for `{name}`: {err}")
+                        });
+                    encrypted_operators.insert(name, encrypted_operator);
+                }
+            }
+        }
+        encrypted_operators
+    });
@@ -21,7 +21,7 @@ mod tracing_subscriber;
 
 pub(crate) mod http_client;
 
-pub(crate) use secret_key::SECRET_KEY;
+pub(crate) use secret_key::{derive_subkey, SECRET_KEY};
 
 /// Application.
 pub trait Application {
@@ -35,7 +35,8 @@ pub trait Application {
     fn run(self, async_jobs: Vec<(&'static str, AsyncCronJob)>);
 
     /// Boots the application. It also setups the default secret key,
-    /// the tracing subscriber, the metrics exporter and a global HTTP client.
+    /// the tracing subscriber, the metrics exporter, a global HTTP client
+    /// and the storage accessors.
     fn boot() -> Self
     where
         Self: Default,
@@ -45,6 +46,11 @@ pub trait Application {
         metrics_exporter::init::<Self>();
         http_client::init::<Self>();
 
+        #[cfg(feature = "accessor")]
+        {
+            crate::accessor::init();
+        }
+
         #[cfg(feature = "view")]
         {
             crate::view::init::<Self>();
@@ -1,5 +1,5 @@
 use super::Application;
-use crate::extend::TomlTableExt;
+use crate::{error::Error, extend::TomlTableExt};
 use hkdf::Hkdf;
 use sha2::{Digest, Sha256};
 use std::{env, sync::OnceLock};
@@ -31,5 +31,19 @@ pub(super) fn init<APP: Application + ?Sized>() {
         .expect("fail to set the secret key");
 }
 
+/// Derives a subkey of the secret key for the specific purpose,
+/// so that the keys for different purposes are independent of each other.
+pub(crate) fn derive_subkey(purpose: &str) -> Result<[u8; 32], Error> {
+    let secret_key = SECRET_KEY
+        .get()
+        .ok_or_else(|| Error::new("the secret key has not been initialized"))?;
+    let mut subkey = [0; 32];
+    let info = format!("ZINO:SUBKEY;PURPOSE:{purpose}");
+    Hkdf::<Sha256>::new(None, secret_key)
+        .expand(info.as_bytes(), &mut subkey)
+        .map_err(|_| Error::new("invalid length for Sha256 to output"))?;
+    Ok(subkey)
+}
+
 /// Secret key.
 pub(crate) static SECRET_KEY: OnceLock<[u8; 64]> = OnceLock::new();
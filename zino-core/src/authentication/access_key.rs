@@ -93,3 +93,17 @@ impl AsRef<[u8]> for SecretAccessKey {
         self.0.as_ref()
     }
 }
+
+impl From<String> for SecretAccessKey {
+    #[inline]
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl From<&str> for SecretAccessKey {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Self(s.to_owned())
+    }
+}
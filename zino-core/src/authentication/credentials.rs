@@ -0,0 +1,278 @@
+use crate::{error::Error, extend::TomlTableExt, request::Validation, state::State};
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    sync::{Arc, LazyLock},
+    thread,
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use toml::Table;
+
+/// Password credentials hashed with Argon2id.
+/// See [the spec](https://datatracker.ietf.org/doc/html/rfc9106).
+///
+/// The hashing is CPU and memory intensive, so the async variants
+/// such as [`hash_password_async()`](Credentials::hash_password_async) should be used
+/// in the async contexts, which run the Argon2 computation on the blocking threads
+/// with the number of concurrent computations limited.
+#[derive(Clone)]
+pub struct Credentials {
+    /// Argon2 parameters.
+    params: Params,
+    /// Min length of the passwords.
+    min_length: usize,
+    /// Max number of the consecutive failures before the account is locked.
+    max_failures: u32,
+    /// Duration of the account lockout.
+    lockout_duration: Duration,
+    /// Permits for the concurrent hashing on the blocking threads.
+    hashing_permits: Arc<Semaphore>,
+}
+
+impl Credentials {
+    /// Creates a new instance with the default Argon2 parameters,
+    /// which are `19 MiB` of memory, `2` iterations and `1` degree of parallelism.
+    pub fn new() -> Self {
+        Self {
+            params: Params::default(),
+            min_length: 8,
+            max_failures: 5,
+            lockout_duration: Duration::from_secs(15 * 60),
+            hashing_permits: Arc::new(Semaphore::new(default_max_concurrency())),
+        }
+    }
+
+    /// Attempts to create a new instance with the config.
+    ///
+    /// The Argon2 parameters are specified by `memory-cost` in KiB, `time-cost` and
+    /// `parallelism`. The passwords should have at least `min-length` characters,
+    /// and the account is locked for `lockout-duration` after `max-failures` consecutive
+    /// failures of the password verification. The number of concurrent hashing is limited
+    /// by `max-concurrency`, which is the available parallelism by default.
+    pub fn try_new(config: &Table) -> Result<Self, Error> {
+        let memory_cost = config
+            .get_u32("memory-cost")
+            .unwrap_or(Params::DEFAULT_M_COST);
+        let time_cost = config
+            .get_u32("time-cost")
+            .unwrap_or(Params::DEFAULT_T_COST);
+        let parallelism = config
+            .get_u32("parallelism")
+            .unwrap_or(Params::DEFAULT_P_COST);
+        let params = Params::new(memory_cost, time_cost, parallelism, None)
+            .map_err(|err| Error::new(format!("invalid Argon2 parameters: {err}")))?;
+        Ok(Self {
+            params,
+            min_length: config.get_usize("min-length").unwrap_or(8),
+            max_failures: config.get_u32("max-failures").unwrap_or(5),
+            lockout_duration: config
+                .get_duration("lockout-duration")
+                .unwrap_or(Duration::from_secs(15 * 60)),
+            hashing_permits: Arc::new(Semaphore::new(
+                config
+                    .get_usize("max-concurrency")
+                    .unwrap_or_else(default_max_concurrency)
+                    .max(1),
+            )),
+        })
+    }
+
+    /// Returns a reference to the shared instance configured by the `[credentials]` table.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if the config is invalid.
+    #[inline]
+    pub fn shared() -> &'static Self {
+        LazyLock::force(&SHARED_CREDENTIALS)
+    }
+
+    /// Returns the max number of the consecutive failures before the account is locked.
+    #[inline]
+    pub fn max_failures(&self) -> u32 {
+        self.max_failures
+    }
+
+    /// Returns the duration of the account lockout.
+    #[inline]
+    pub fn lockout_duration(&self) -> Duration {
+        self.lockout_duration
+    }
+
+    /// Checks the strength of the password.
+    #[inline]
+    pub fn check_strength(&self, password: &str) -> Result<(), Error> {
+        Validation::check_password_strength(password, self.min_length)
+    }
+
+    /// Hashes the password with a random salt and returns the hash in the PHC string format.
+    pub fn hash_password(&self, password: &str) -> Result<String, Error> {
+        let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>())
+            .map_err(|err| Error::new(format!("fail to generate the salt: {err}")))?;
+        self.argon2()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|err| Error::new(format!("fail to hash the password: {err}")))
+    }
+
+    /// Verifies the password against the hash in the PHC string format.
+    /// The comparison of the hashes is performed in constant time.
+    ///
+    /// A legacy plaintext password, which does not start with `$`, is compared
+    /// in constant time, and it should be rehashed after a successful verification.
+    pub fn verify_password(&self, password: &str, hash: &str) -> Result<bool, Error> {
+        if hash.is_empty() {
+            return Err(Error::new("the password hash should be nonempty"));
+        } else if !hash.starts_with('$') {
+            return Ok(constant_time_eq(password.as_bytes(), hash.as_bytes()));
+        }
+
+        let hash = PasswordHash::new(hash)
+            .map_err(|err| Error::new(format!("invalid password hash: {err}")))?;
+        match self.argon2().verify_password(password.as_bytes(), &hash) {
+            Ok(()) => Ok(true),
+            Err(argon2::password_hash::Error::Password) => Ok(false),
+            Err(err) => Err(Error::new(format!("fail to verify the password: {err}"))),
+        }
+    }
+
+    /// Hashes the password on a blocking thread without blocking the async runtime.
+    pub async fn hash_password_async(&self, password: &str) -> Result<String, Error> {
+        let password = password.to_owned();
+        self.spawn_blocking(move |credentials| credentials.hash_password(&password))
+            .await
+    }
+
+    /// Verifies the password on a blocking thread without blocking the async runtime.
+    pub async fn verify_password_async(&self, password: &str, hash: &str) -> Result<bool, Error> {
+        let password = password.to_owned();
+        let hash = hash.to_owned();
+        self.spawn_blocking(move |credentials| credentials.verify_password(&password, &hash))
+            .await
+    }
+
+    /// Returns `true` if the value is a password hash in the PHC string format.
+    #[inline]
+    pub fn is_password_hash(&self, value: &str) -> bool {
+        value.starts_with('$') && PasswordHash::new(value).is_ok()
+    }
+
+    /// Returns `true` if the hash is not generated by Argon2id with the current parameters,
+    /// which means the password should be rehashed after a successful verification.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(hash) else {
+            return true;
+        };
+        if hash.algorithm != Algorithm::Argon2id.ident()
+            || hash.version != Some(Version::V0x13.into())
+        {
+            return true;
+        }
+        match Params::try_from(&hash) {
+            Ok(params) => {
+                params.m_cost() != self.params.m_cost()
+                    || params.t_cost() != self.params.t_cost()
+                    || params.p_cost() != self.params.p_cost()
+            }
+            Err(_) => true,
+        }
+    }
+
+    /// Runs the operation on a blocking thread after a hashing permit is acquired.
+    async fn spawn_blocking<T: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&Self) -> Result<T, Error> + Send + 'static,
+    ) -> Result<T, Error> {
+        let permit = self
+            .hashing_permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|err| Error::new(format!("fail to acquire the hashing permit: {err}")))?;
+        let credentials = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = operation(&credentials);
+            drop(permit);
+            result
+        })
+        .await
+        .map_err(|err| Error::new(format!("fail to run the blocking task: {err}")))?
+    }
+
+    /// Returns an Argon2id context with the parameters.
+    #[inline]
+    fn argon2(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+}
+
+impl Default for Credentials {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the default max number of the concurrent hashing.
+fn default_max_concurrency() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Compares the bytes in constant time by comparing their SHA-256 digests,
+/// so that the running time does not depend on the contents or the lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let a = Sha256::digest(a);
+    let b = Sha256::digest(b);
+    a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Shared credentials.
+static SHARED_CREDENTIALS: LazyLock<Credentials> = LazyLock::new(|| {
+    if let Some(config) = State::shared().config().get_table("credentials") {
+        Credentials::try_new(config)
+            .unwrap_or_else(|err| panic!("fail to create the credentials: {err}"))
+    } else {
+        Credentials::new()
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::Credentials;
+    use argon2::Params;
+
+    #[test]
+    fn it_hashes_passwords() {
+        let credentials = Credentials::new();
+        let hash = credentials.hash_password("Zino#2023").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(credentials.is_password_hash(&hash));
+        assert!(!credentials.is_password_hash("Zino#2023"));
+        assert!(credentials.verify_password("Zino#2023", &hash).unwrap());
+        assert!(!credentials.verify_password("zino#2023", &hash).unwrap());
+        assert!(!credentials.needs_rehash(&hash));
+
+        let mut credentials = Credentials::new();
+        credentials.params = Params::new(8 * 1024, 3, 1, None).unwrap();
+        assert!(credentials.needs_rehash(&hash));
+        assert!(credentials.verify_password("Zino#2023", &hash).unwrap());
+
+        assert!(credentials
+            .verify_password("Zino#2023", "Zino#2023")
+            .unwrap());
+        assert!(!credentials
+            .verify_password("Zino#2023", "zino#2023")
+            .unwrap());
+        assert!(credentials.needs_rehash("Zino#2023"));
+
+        assert!(credentials.check_strength("Zino#2023").is_ok());
+        assert!(credentials.check_strength("zino2023").is_err());
+        assert!(credentials.check_strength("Zi#23").is_err());
+    }
+}
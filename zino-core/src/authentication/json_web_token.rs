@@ -0,0 +1,334 @@
+use crate::{
+    application::{self, PROJECT_DIR},
+    datetime::DateTime,
+    error::Error,
+    extend::{JsonObjectExt, TomlTableExt},
+    state::State,
+    Map,
+};
+use jsonwebtoken::{
+    jwk::{AlgorithmParameters, Jwk, JwkSet},
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{fs, sync::LazyLock, time::Duration};
+use toml::Table;
+
+/// JSON Web Token (JWT) signed with HS256, RS256, ES256 or EdDSA.
+/// See [the spec](https://datatracker.ietf.org/doc/html/rfc7519).
+pub struct JsonWebToken {
+    /// Algorithm for signing tokens.
+    algorithm: Algorithm,
+    /// Key ID for signing tokens.
+    key_id: Option<String>,
+    /// Encoding key.
+    encoding_key: Option<EncodingKey>,
+    /// Decoding keys with the key IDs and algorithms.
+    decoding_keys: Vec<(Option<String>, Algorithm, DecodingKey)>,
+    /// Issuer.
+    issuer: Option<String>,
+    /// Audience.
+    audience: Vec<String>,
+    /// Leeway for validating the `exp` and `nbf` claims.
+    leeway: Duration,
+    /// Max age of the issued tokens.
+    max_age: Duration,
+}
+
+impl JsonWebToken {
+    /// Creates a new instance with the algorithm. No keys are specified.
+    pub fn new(algorithm: Algorithm) -> Self {
+        Self {
+            algorithm,
+            key_id: None,
+            encoding_key: None,
+            decoding_keys: Vec::new(),
+            issuer: None,
+            audience: Vec::new(),
+            leeway: Duration::from_secs(60),
+            max_age: Duration::from_secs(60 * 60),
+        }
+    }
+
+    /// Creates a new instance with the HS256 algorithm and the secret.
+    pub fn with_secret(secret: &[u8]) -> Self {
+        let mut jwt = Self::new(Algorithm::HS256);
+        jwt.encoding_key = Some(EncodingKey::from_secret(secret));
+        jwt.decoding_keys
+            .push((None, Algorithm::HS256, DecodingKey::from_secret(secret)));
+        jwt
+    }
+
+    /// Attempts to create a new instance with the config.
+    ///
+    /// The `algorithm` is `HS256` by default, whose secret is derived from
+    /// the application secret key with HKDF if `secret` is not specified.
+    /// The `key-id` is set as the `kid` of the issued tokens, and the tokens with it
+    /// are verified by the secret or the public key. For the other algorithms,
+    /// the keys are loaded from the PEM files `private-key` and `public-key`.
+    /// Additional verification keys can be specified by the `jwks` array
+    /// or the JSON Web Key Set file `jwks-file`.
+    pub fn try_new(config: &Table) -> Result<Self, Error> {
+        let algorithm = config.get_str("algorithm").unwrap_or("HS256");
+        let algorithm = parse_algorithm(algorithm)?;
+        let key_id = config.get_str("key-id").map(|s| s.to_owned());
+        let mut jwt = if algorithm == Algorithm::HS256 {
+            let secret = if let Some(secret) = config.get_str("secret") {
+                secret.as_bytes().to_vec()
+            } else {
+                application::derive_subkey("jwt")?.to_vec()
+            };
+            let mut jwt = Self::new(algorithm);
+            jwt.encoding_key = Some(EncodingKey::from_secret(&secret));
+            jwt.decoding_keys
+                .push((key_id.clone(), algorithm, DecodingKey::from_secret(&secret)));
+            jwt
+        } else {
+            let mut jwt = Self::new(algorithm);
+            if let Some(private_key) = config.get_str("private-key") {
+                let pem = fs::read(PROJECT_DIR.join(private_key))?;
+                let encoding_key = match algorithm {
+                    Algorithm::RS256 => EncodingKey::from_rsa_pem(&pem)?,
+                    Algorithm::ES256 => EncodingKey::from_ec_pem(&pem)?,
+                    _ => EncodingKey::from_ed_pem(&pem)?,
+                };
+                jwt.encoding_key = Some(encoding_key);
+            }
+            if let Some(public_key) = config.get_str("public-key") {
+                let pem = fs::read(PROJECT_DIR.join(public_key))?;
+                let decoding_key = match algorithm {
+                    Algorithm::RS256 => DecodingKey::from_rsa_pem(&pem)?,
+                    Algorithm::ES256 => DecodingKey::from_ec_pem(&pem)?,
+                    _ => DecodingKey::from_ed_pem(&pem)?,
+                };
+                jwt.decoding_keys
+                    .push((key_id.clone(), algorithm, decoding_key));
+            }
+            jwt
+        };
+        jwt.key_id = key_id;
+        if let Some(jwks) = config.get_array("jwks") {
+            let jwks = serde_json::from_value::<Vec<Jwk>>(serde_json::to_value(jwks)?)?;
+            jwt.add_jwks(&JwkSet { keys: jwks })?;
+        }
+        if let Some(jwks_file) = config.get_str("jwks-file") {
+            let jwks = fs::read(PROJECT_DIR.join(jwks_file))?;
+            jwt.add_jwks(&serde_json::from_slice(&jwks)?)?;
+        }
+        if let Some(issuer) = config.get_str("issuer") {
+            jwt.issuer = Some(issuer.to_owned());
+        }
+        if let Some(audience) = config.get_str("audience") {
+            jwt.audience = vec![audience.to_owned()];
+        } else if let Some(audience) = config.get_array("audience") {
+            jwt.audience = audience
+                .iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_owned()))
+                .collect();
+        }
+        if let Some(leeway) = config.get_duration("leeway") {
+            jwt.leeway = leeway;
+        }
+        if let Some(max_age) = config.get_duration("max-age") {
+            jwt.max_age = max_age;
+        }
+        Ok(jwt)
+    }
+
+    /// Returns a reference to the shared instance configured by the `[jwt]` table.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if the config is invalid.
+    #[inline]
+    pub fn shared() -> &'static Self {
+        LazyLock::force(&SHARED_JSON_WEB_TOKEN)
+    }
+
+    /// Adds the keys in the JSON Web Key Set for verifying tokens.
+    /// The algorithm of a key is inferred from the key type if it is not specified.
+    pub fn add_jwks(&mut self, jwks: &JwkSet) -> Result<(), Error> {
+        for jwk in jwks.keys.iter() {
+            let algorithm = if let Some(algorithm) = jwk.common.algorithm {
+                algorithm
+            } else {
+                match &jwk.algorithm {
+                    AlgorithmParameters::EllipticCurve(_) => Algorithm::ES256,
+                    AlgorithmParameters::RSA(_) => Algorithm::RS256,
+                    AlgorithmParameters::OctetKey(_) => Algorithm::HS256,
+                    AlgorithmParameters::OctetKeyPair(_) => Algorithm::EdDSA,
+                }
+            };
+            let decoding_key = DecodingKey::from_jwk(jwk)?;
+            self.decoding_keys
+                .push((jwk.common.key_id.clone(), algorithm, decoding_key));
+        }
+        Ok(())
+    }
+
+    /// Sets the issuer.
+    #[inline]
+    pub fn set_issuer(&mut self, issuer: impl Into<String>) {
+        self.issuer = Some(issuer.into());
+    }
+
+    /// Sets the audience.
+    #[inline]
+    pub fn set_audience(&mut self, audience: Vec<String>) {
+        self.audience = audience;
+    }
+
+    /// Sets the max age of the issued tokens.
+    #[inline]
+    pub fn set_max_age(&mut self, max_age: Duration) {
+        self.max_age = max_age;
+    }
+
+    /// Returns `true` if there is a key with the key ID for verifying tokens.
+    #[inline]
+    pub fn contains_key_id(&self, key_id: &str) -> bool {
+        self.decoding_keys
+            .iter()
+            .any(|(kid, ..)| kid.as_deref() == Some(key_id))
+    }
+
+    /// Returns the algorithm for signing tokens.
+    #[inline]
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Signs the claims as a token.
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, Error> {
+        let encoding_key = self
+            .encoding_key
+            .as_ref()
+            .ok_or_else(|| Error::new("the private key for signing tokens is not specified"))?;
+        let mut header = Header::new(self.algorithm);
+        header.kid = self.key_id.clone();
+        jsonwebtoken::encode(&header, claims, encoding_key).map_err(Error::from)
+    }
+
+    /// Issues a token for the subject with the custom claims.
+    /// The registered claims `iat`, `nbf`, `exp`, `iss` and `aud` are filled automatically.
+    pub fn issue(&self, subject: impl Into<String>, mut claims: Map) -> Result<String, Error> {
+        let now = DateTime::now().timestamp();
+        let max_age = i64::try_from(self.max_age.as_secs()).unwrap_or(i64::MAX);
+        claims.upsert("sub", subject.into());
+        claims.upsert("iat", now);
+        claims.upsert("nbf", now);
+        claims.upsert("exp", now.saturating_add(max_age));
+        if let Some(issuer) = &self.issuer {
+            claims.upsert("iss", issuer.as_str());
+        }
+        match self.audience.as_slice() {
+            [] => (),
+            [audience] => {
+                claims.upsert("aud", audience.as_str());
+            }
+            audience => {
+                claims.upsert("aud", audience.to_vec());
+            }
+        }
+        self.sign(&claims)
+    }
+
+    /// Verifies the token and returns the claims.
+    /// The `exp` claim is required, and the `nbf`, `iss` and `aud` claims
+    /// are validated if the issuer and audience are specified.
+    pub fn verify<T: DeserializeOwned>(&self, token: &str) -> Result<T, Error> {
+        let header = jsonwebtoken::decode_header(token)?;
+        let key_id = header.kid.as_deref();
+        let (_, algorithm, decoding_key) = self
+            .decoding_keys
+            .iter()
+            .find(|(kid, algorithm, _)| {
+                *algorithm == header.alg && (key_id.is_none() || kid.as_deref() == key_id)
+            })
+            .ok_or_else(|| Error::new("no matched key for verifying the token"))?;
+
+        let mut validation = Validation::new(*algorithm);
+        validation.leeway = self.leeway.as_secs();
+        validation.validate_nbf = true;
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if self.audience.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(self.audience.as_slice());
+        }
+        jsonwebtoken::decode::<T>(token, decoding_key, &validation)
+            .map(|data| data.claims)
+            .map_err(Error::from)
+    }
+}
+
+/// Parses the algorithm.
+fn parse_algorithm(algorithm: &str) -> Result<Algorithm, Error> {
+    match algorithm {
+        "HS256" => Ok(Algorithm::HS256),
+        "RS256" => Ok(Algorithm::RS256),
+        "ES256" => Ok(Algorithm::ES256),
+        "EdDSA" => Ok(Algorithm::EdDSA),
+        _ => {
+            let message = format!("algorithm `{algorithm}` is unsupported");
+            Err(Error::new(message))
+        }
+    }
+}
+
+/// Shared JSON Web Token.
+static SHARED_JSON_WEB_TOKEN: LazyLock<JsonWebToken> = LazyLock::new(|| {
+    let config = State::shared().config();
+    let result = if let Some(jwt) = config.get_table("jwt") {
+        JsonWebToken::try_new(jwt)
+    } else {
+        JsonWebToken::try_new(&Table::new())
+    };
+    result.unwrap_or_else(|err| panic!("fail to create the JSON Web Token: {err}"))
+});
+
+#[cfg(test)]
+mod tests {
+    use super::JsonWebToken;
+    use crate::Map;
+    use toml::Table;
+
+    #[test]
+    fn it_issues_and_verifies_tokens() {
+        let mut jwt = JsonWebToken::with_secret(b"secret");
+        jwt.set_issuer("zino");
+        jwt.set_audience(vec!["app".to_owned()]);
+
+        let mut claims = Map::new();
+        claims.insert("role".to_owned(), "admin".into());
+        let token = jwt.issue("alice", claims).unwrap();
+        let claims = jwt.verify::<Map>(&token).unwrap();
+        assert_eq!(claims.get("sub").and_then(|v| v.as_str()), Some("alice"));
+        assert_eq!(claims.get("role").and_then(|v| v.as_str()), Some("admin"));
+
+        let other_jwt = JsonWebToken::with_secret(b"other");
+        assert!(other_jwt.verify::<Map>(&token).is_err());
+
+        jwt.set_issuer("other");
+        assert!(jwt.verify::<Map>(&token).is_err());
+    }
+
+    #[test]
+    fn it_verifies_tokens_with_key_id() {
+        let config = "secret = \"secret\"\nkey-id = \"2023-03\""
+            .parse::<Table>()
+            .unwrap();
+        let jwt = JsonWebToken::try_new(&config).unwrap();
+        let token = jwt.issue("alice", Map::new()).unwrap();
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(header.kid.as_deref(), Some("2023-03"));
+        assert!(jwt.verify::<Map>(&token).is_ok());
+
+        let other_token = JsonWebToken::with_secret(b"secret")
+            .issue("alice", Map::new())
+            .unwrap();
+        assert!(jwt.verify::<Map>(&other_token).is_ok());
+    }
+}
@@ -8,12 +8,18 @@ use hmac::{
 use std::time::Duration;
 
 mod access_key;
+mod credentials;
+mod json_web_token;
+mod oauth2;
 mod security_token;
 mod session_id;
 
 pub(crate) use security_token::ParseSecurityTokenError;
 
 pub use access_key::{AccessKeyId, SecretAccessKey};
+pub use credentials::Credentials;
+pub use json_web_token::JsonWebToken;
+pub use oauth2::{OAuth2Client, OAuth2Identity, OAuth2Token};
 pub use security_token::SecurityToken;
 pub use session_id::SessionId;
 
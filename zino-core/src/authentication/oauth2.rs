@@ -0,0 +1,569 @@
+use super::JsonWebToken;
+use crate::{
+    application::http_client,
+    error::Error,
+    extend::{JsonObjectExt, TomlTableExt},
+    format::base64,
+    request::RequestContext,
+    state::State,
+    Map,
+};
+use jsonwebtoken::{jwk::JwkSet, Algorithm};
+use parking_lot::RwLock;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+    time::{Duration, Instant},
+};
+use toml::Table;
+use url::Url;
+
+/// Max age of the cookie for keeping the `state`, `nonce` and PKCE code verifier.
+const AUTHORIZATION_COOKIE_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Min interval of refetching the JSON Web Key Set for an unknown key ID.
+const JWKS_MIN_REFETCH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// OAuth 2.0 client for the authorization code flow with PKCE,
+/// which also validates the ID tokens if the OpenID Connect `issuer` is specified.
+/// See [RFC 6749](https://datatracker.ietf.org/doc/html/rfc6749),
+/// [RFC 7636](https://datatracker.ietf.org/doc/html/rfc7636) and
+/// [OpenID Connect Core](https://openid.net/specs/openid-connect-core-1_0.html).
+pub struct OAuth2Client {
+    /// Provider name.
+    name: String,
+    /// Client ID.
+    client_id: String,
+    /// Client secret.
+    client_secret: Option<String>,
+    /// Authorization endpoint.
+    authorization_endpoint: String,
+    /// Token endpoint.
+    token_endpoint: String,
+    /// UserInfo endpoint.
+    userinfo_endpoint: Option<String>,
+    /// Redirection URI.
+    redirect_uri: String,
+    /// Scopes of the access request.
+    scopes: Vec<String>,
+    /// Issuer of the ID tokens.
+    issuer: Option<String>,
+    /// JSON Web Key Set endpoint.
+    jwks_uri: Option<String>,
+    /// Roles assigned to the users.
+    roles: Vec<String>,
+    /// Cached verifier of the ID tokens and the time when it was fetched.
+    verifier: RwLock<Option<(Arc<JsonWebToken>, Instant)>>,
+}
+
+impl OAuth2Client {
+    /// Attempts to create a new instance with the config.
+    pub fn try_new(config: &Table) -> Result<Self, Error> {
+        let get_required_str = |key: &str| {
+            config
+                .get_str(key)
+                .map(|s| s.to_owned())
+                .ok_or_else(|| Error::new(format!("the `{key}` field should be a str")))
+        };
+        let get_strings = |key: &str| {
+            config.get_array(key).map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_owned()))
+                    .collect::<Vec<_>>()
+            })
+        };
+        let issuer = config.get_str("issuer").map(|s| s.to_owned());
+        let scopes = get_strings("scopes").unwrap_or_else(|| {
+            if issuer.is_some() {
+                vec![
+                    "openid".to_owned(),
+                    "profile".to_owned(),
+                    "email".to_owned(),
+                ]
+            } else {
+                Vec::new()
+            }
+        });
+        Ok(Self {
+            name: get_required_str("name")?,
+            client_id: get_required_str("client-id")?,
+            client_secret: config.get_str("client-secret").map(|s| s.to_owned()),
+            authorization_endpoint: get_required_str("authorization-endpoint")?,
+            token_endpoint: get_required_str("token-endpoint")?,
+            userinfo_endpoint: config.get_str("userinfo-endpoint").map(|s| s.to_owned()),
+            redirect_uri: get_required_str("redirect-uri")?,
+            scopes,
+            issuer,
+            jwks_uri: config.get_str("jwks-uri").map(|s| s.to_owned()),
+            roles: get_strings("roles").unwrap_or_else(|| vec!["user".to_owned()]),
+            verifier: RwLock::new(None),
+        })
+    }
+
+    /// Returns a reference to the client configured by the `[[oauth2]]` entry with the name.
+    #[inline]
+    pub fn get(name: &str) -> Option<&'static Self> {
+        SHARED_OAUTH2_CLIENTS.get(name)
+    }
+
+    /// Returns the provider name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the client ID.
+    #[inline]
+    pub fn client_id(&self) -> &str {
+        self.client_id.as_str()
+    }
+
+    /// Returns the redirection URI.
+    #[inline]
+    pub fn redirect_uri(&self) -> &str {
+        self.redirect_uri.as_str()
+    }
+
+    /// Returns the URL to redirect the user agent to the authorization endpoint.
+    /// The `state`, `nonce` and PKCE code verifier are kept in a signed cookie.
+    pub fn authorize_url<Ctx: RequestContext + ?Sized>(&self, ctx: &Ctx) -> Result<String, Error> {
+        let state = base64::encode_url_safe(rand::random::<[u8; 16]>());
+        let nonce = base64::encode_url_safe(rand::random::<[u8; 16]>());
+        let code_verifier = base64::encode_url_safe(rand::random::<[u8; 32]>());
+        let code_challenge = base64::encode_url_safe(Sha256::digest(&code_verifier));
+
+        let mut url = Url::parse(&self.authorization_endpoint)?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_uri)
+            .append_pair("scope", &self.scopes.join(" "))
+            .append_pair("state", &state)
+            .append_pair("code_challenge", &code_challenge)
+            .append_pair("code_challenge_method", "S256");
+        if self.issuer.is_some() {
+            url.query_pairs_mut().append_pair("nonce", &nonce);
+        }
+
+        let value = format!("{state}.{nonce}.{code_verifier}");
+        let max_age = Some(AUTHORIZATION_COOKIE_MAX_AGE);
+        let mut cookie = ctx.new_cookie(self.cookie_name(), value, max_age);
+        cookie.set_path("/");
+        ctx.add_cookie(cookie);
+        Ok(url.into())
+    }
+
+    /// Handles the authorization response on the redirection URI.
+    /// It checks the `state`, exchanges the authorization code for the tokens,
+    /// and resolves the identity from the ID token or the UserInfo endpoint.
+    pub async fn handle_callback<Ctx: RequestContext + ?Sized>(
+        &self,
+        ctx: &Ctx,
+    ) -> Result<(OAuth2Token, OAuth2Identity), Error> {
+        let cookie = ctx
+            .get_cookie(&self.cookie_name())
+            .ok_or_else(|| Error::new("the authorization cookie does not exist"))?;
+        let mut removal_cookie = ctx.new_cookie(self.cookie_name(), "", Some(Duration::ZERO));
+        removal_cookie.set_path("/");
+        ctx.add_cookie(removal_cookie);
+
+        let mut parts = cookie.value().splitn(3, '.');
+        let (Some(state), Some(nonce), Some(code_verifier)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(Error::new("the authorization cookie is invalid"));
+        };
+        let query = ctx.parse_query::<Map>().unwrap_or_default();
+        let code = check_authorization_response(&query, state)?;
+
+        let token = self.exchange_code(code, code_verifier).await?;
+        let claims = if self.issuer.is_some() {
+            let id_token = token
+                .id_token()
+                .ok_or_else(|| Error::new("the ID token does not exist"))?;
+            let mut claims = self.validate_id_token(id_token, Some(nonce)).await?;
+            if self.userinfo_endpoint.is_some() {
+                let userinfo = self.fetch_userinfo(token.access_token()).await?;
+                if userinfo.get_str("sub") != claims.get_str("sub") {
+                    return Err(Error::new("the `sub` claim of the UserInfo does not match"));
+                }
+                claims.extend(userinfo);
+            }
+            claims
+        } else {
+            self.fetch_userinfo(token.access_token()).await?
+        };
+        let subject = claims
+            .get_str("sub")
+            .or_else(|| claims.get_str("id"))
+            .map(|s| s.to_owned())
+            .or_else(|| claims.get_u64("id").map(|id| id.to_string()))
+            .ok_or_else(|| Error::new("the `sub` claim should be a str"))?;
+        let identity = OAuth2Identity {
+            provider: self.name.clone(),
+            subject,
+            claims,
+            roles: self.roles.clone(),
+        };
+        Ok((token, identity))
+    }
+
+    /// Exchanges the authorization code for the tokens.
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<OAuth2Token, Error> {
+        let mut params = Map::new();
+        params.upsert("grant_type", "authorization_code");
+        params.upsert("code", code);
+        params.upsert("redirect_uri", self.redirect_uri.as_str());
+        params.upsert("code_verifier", code_verifier);
+        self.request_token(params).await
+    }
+
+    /// Refreshes the access token with the refresh token.
+    pub async fn refresh_token(&self, refresh_token: &str) -> Result<OAuth2Token, Error> {
+        let mut params = Map::new();
+        params.upsert("grant_type", "refresh_token");
+        params.upsert("refresh_token", refresh_token);
+        let mut token = self.request_token(params).await?;
+        if token.refresh_token.is_none() {
+            token.refresh_token = Some(refresh_token.to_owned());
+        }
+        Ok(token)
+    }
+
+    /// Fetches the claims about the user from the UserInfo endpoint.
+    pub async fn fetch_userinfo(&self, access_token: &str) -> Result<Map, Error> {
+        let userinfo_endpoint = self
+            .userinfo_endpoint
+            .as_deref()
+            .ok_or_else(|| Error::new("the `userinfo-endpoint` field should be a str"))?;
+        let mut headers = Map::new();
+        headers.upsert("accept", "application/json");
+        headers.upsert("authorization", format!("Bearer {access_token}"));
+
+        let mut options = Map::new();
+        options.upsert("headers", headers);
+        send_json_request(userinfo_endpoint, &options).await
+    }
+
+    /// Validates the ID token and returns the claims.
+    /// The `iss`, `aud`, `exp` and `nonce` claims are checked.
+    ///
+    /// The JSON Web Key Set is refetched only if the key ID of the token is unknown,
+    /// and at most once in the min refetch interval.
+    pub async fn validate_id_token(
+        &self,
+        id_token: &str,
+        nonce: Option<&str>,
+    ) -> Result<Map, Error> {
+        let header = jsonwebtoken::decode_header(id_token)?;
+        let cached_verifier = self.verifier.read().clone();
+        let verifier = match cached_verifier {
+            Some((verifier, fetched_at)) => {
+                let is_unknown_key = header
+                    .kid
+                    .as_deref()
+                    .is_some_and(|key_id| !verifier.contains_key_id(key_id));
+                if is_unknown_key && fetched_at.elapsed() >= JWKS_MIN_REFETCH_INTERVAL {
+                    // The signing keys may have been rotated.
+                    self.fetch_verifier().await?
+                } else {
+                    verifier
+                }
+            }
+            None => self.fetch_verifier().await?,
+        };
+        let claims = verifier.verify::<Map>(id_token)?;
+        if let Some(nonce) = nonce {
+            check_nonce(&claims, nonce)?;
+        }
+        Ok(claims)
+    }
+
+    /// Fetches the JSON Web Key Set and caches the verifier of the ID tokens.
+    async fn fetch_verifier(&self) -> Result<Arc<JsonWebToken>, Error> {
+        let issuer = self
+            .issuer
+            .as_deref()
+            .ok_or_else(|| Error::new("the `issuer` field should be a str"))?;
+        let jwks_uri = match self.jwks_uri.as_deref() {
+            Some(jwks_uri) => jwks_uri.to_owned(),
+            None => {
+                let discovery_url = format!(
+                    "{}/.well-known/openid-configuration",
+                    issuer.trim_end_matches('/')
+                );
+                let metadata = send_json_request::<Map>(&discovery_url, &Map::new()).await?;
+                metadata
+                    .get_str("jwks_uri")
+                    .map(|s| s.to_owned())
+                    .ok_or_else(|| Error::new("the `jwks_uri` field should be a str"))?
+            }
+        };
+        let jwks = send_json_request::<JwkSet>(&jwks_uri, &Map::new()).await?;
+        let mut verifier = JsonWebToken::new(Algorithm::RS256);
+        verifier.add_jwks(&jwks)?;
+        verifier.set_issuer(issuer);
+        verifier.set_audience(vec![self.client_id.clone()]);
+
+        let verifier = Arc::new(verifier);
+        *self.verifier.write() = Some((verifier.clone(), Instant::now()));
+        Ok(verifier)
+    }
+
+    /// Sends a request to the token endpoint.
+    async fn request_token(&self, mut params: Map) -> Result<OAuth2Token, Error> {
+        params.upsert("client_id", self.client_id.as_str());
+        if let Some(client_secret) = &self.client_secret {
+            params.upsert("client_secret", client_secret.as_str());
+        }
+
+        let mut headers = Map::new();
+        headers.upsert("accept", "application/json");
+
+        let mut options = Map::new();
+        options.upsert("method", "POST");
+        options.upsert("body", params);
+        options.upsert("data_type", "form");
+        options.upsert("headers", headers);
+        send_json_request(&self.token_endpoint, &options).await
+    }
+
+    /// Returns the name of the authorization cookie.
+    #[inline]
+    fn cookie_name(&self) -> String {
+        format!("oauth2-{}", self.name)
+    }
+}
+
+/// Tokens issued by the token endpoint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OAuth2Token {
+    /// Access token.
+    access_token: String,
+    /// Token type.
+    token_type: String,
+    /// Lifetime in seconds of the access token.
+    expires_in: Option<u64>,
+    /// Refresh token.
+    refresh_token: Option<String>,
+    /// ID token.
+    id_token: Option<String>,
+    /// Scope of the access token.
+    scope: Option<String>,
+}
+
+impl OAuth2Token {
+    /// Returns the access token.
+    #[inline]
+    pub fn access_token(&self) -> &str {
+        self.access_token.as_str()
+    }
+
+    /// Returns the token type.
+    #[inline]
+    pub fn token_type(&self) -> &str {
+        self.token_type.as_str()
+    }
+
+    /// Returns the lifetime of the access token.
+    #[inline]
+    pub fn expires_in(&self) -> Option<Duration> {
+        self.expires_in.map(Duration::from_secs)
+    }
+
+    /// Returns the refresh token.
+    #[inline]
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh_token.as_deref()
+    }
+
+    /// Returns the ID token.
+    #[inline]
+    pub fn id_token(&self) -> Option<&str> {
+        self.id_token.as_deref()
+    }
+
+    /// Returns the scope of the access token.
+    #[inline]
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+}
+
+/// Identity of the user authenticated by the OAuth 2.0 provider.
+#[derive(Debug, Clone)]
+pub struct OAuth2Identity {
+    /// Provider name.
+    provider: String,
+    /// Subject identifier.
+    subject: String,
+    /// Claims about the user.
+    claims: Map,
+    /// Roles assigned to the user.
+    roles: Vec<String>,
+}
+
+impl OAuth2Identity {
+    /// Returns the provider name.
+    #[inline]
+    pub fn provider(&self) -> &str {
+        self.provider.as_str()
+    }
+
+    /// Returns the subject identifier.
+    #[inline]
+    pub fn subject(&self) -> &str {
+        self.subject.as_str()
+    }
+
+    /// Returns a reference to the claims.
+    #[inline]
+    pub fn claims(&self) -> &Map {
+        &self.claims
+    }
+
+    /// Returns the account name in the format `{provider}:{subject}`.
+    #[inline]
+    pub fn account(&self) -> String {
+        format!("{}:{}", self.provider, self.subject)
+    }
+
+    /// Converts the identity into the data of a `User` model.
+    pub fn to_user_map(&self) -> Map {
+        let claims = &self.claims;
+        let name = ["name", "preferred_username", "nickname", "email"]
+            .into_iter()
+            .find_map(|key| claims.get_str(key))
+            .unwrap_or(self.subject.as_str());
+        let mut data = Map::new();
+        data.upsert("name", name);
+        data.upsert("account", self.account());
+        if let Some(email) = claims.get_str("email") {
+            data.upsert("email", email);
+        }
+        if let Some(avatar) = claims
+            .get_str("picture")
+            .or_else(|| claims.get_str("avatar_url"))
+        {
+            data.upsert("avatar", avatar);
+        }
+        data.upsert("roles", self.roles.clone());
+
+        let mut content = Map::new();
+        content.upsert("provider", self.provider.as_str());
+        content.upsert("subject", self.subject.as_str());
+        content.upsert("claims", claims.clone());
+        data.upsert("content", content);
+        data
+    }
+}
+
+/// Checks the authorization response with the `state` kept in the cookie,
+/// returning the authorization code.
+fn check_authorization_response<'a>(query: &'a Map, state: &str) -> Result<&'a str, Error> {
+    if let Some(error) = query.get_str("error") {
+        let message = format!("the authorization request is denied: {error}");
+        return Err(Error::new(message));
+    }
+    if query.get_str("state") != Some(state) {
+        return Err(Error::new("the `state` parameter does not match"));
+    }
+    query
+        .get_str("code")
+        .ok_or_else(|| Error::new("the `code` parameter should be a str"))
+}
+
+/// Checks the `nonce` claim of the ID token.
+fn check_nonce(claims: &Map, nonce: &str) -> Result<(), Error> {
+    if claims.get_str("nonce") != Some(nonce) {
+        return Err(Error::new("the `nonce` claim does not match"));
+    }
+    Ok(())
+}
+
+/// Sends a request and deserializes the response body as JSON.
+async fn send_json_request<T: DeserializeOwned>(resource: &str, options: &Map) -> Result<T, Error> {
+    let response = http_client::request_builder(resource, Some(options))?
+        .send()
+        .await?
+        .error_for_status()?;
+    let data = response.json::<Value>().await?;
+    serde_json::from_value(data).map_err(Error::from)
+}
+
+/// Shared OAuth 2.0 clients.
+static SHARED_OAUTH2_CLIENTS: LazyLock<HashMap<String, OAuth2Client>> = LazyLock::new(|| {
+    let mut clients = HashMap::new();
+    if let Some(entries) = State::shared().config().get_array("oauth2") {
+        for config in entries.iter().filter_map(|v| v.as_table()) {
+            let client = OAuth2Client::try_new(config)
+                .unwrap_or_else(|err| panic!("fail to create the OAuth 2.0 client: {err}"));
+            clients.insert(client.name.clone(), client);
+        }
+    }
+    clients
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{check_authorization_response, check_nonce, OAuth2Identity};
+    use crate::{extend::JsonObjectExt, Map};
+
+    #[test]
+    fn it_checks_authorization_responses() {
+        let mut query = Map::new();
+        query.upsert("code", "abc");
+        query.upsert("state", "xyz");
+        assert_eq!(check_authorization_response(&query, "xyz").unwrap(), "abc");
+        assert!(check_authorization_response(&query, "uvw").is_err());
+
+        query.upsert("error", "access_denied");
+        assert!(check_authorization_response(&query, "xyz").is_err());
+
+        let mut claims = Map::new();
+        claims.upsert("nonce", "n-0S6_WzA2Mj");
+        assert!(check_nonce(&claims, "n-0S6_WzA2Mj").is_ok());
+        assert!(check_nonce(&claims, "n-1S6_WzA2Mj").is_err());
+        assert!(check_nonce(&Map::new(), "n-0S6_WzA2Mj").is_err());
+    }
+
+    #[test]
+    fn it_converts_identities_to_users() {
+        let mut claims = Map::new();
+        claims.upsert("preferred_username", "alice");
+        claims.upsert("email", "alice@example.com");
+        claims.upsert("picture", "https://example.com/alice.png");
+        let identity = OAuth2Identity {
+            provider: "github".to_owned(),
+            subject: "1024".to_owned(),
+            claims,
+            roles: vec!["user".to_owned()],
+        };
+        let data = identity.to_user_map();
+        assert_eq!(data.get_str("name"), Some("alice"));
+        assert_eq!(data.get_str("account"), Some("github:1024"));
+        assert_eq!(data.get_str("email"), Some("alice@example.com"));
+        assert_eq!(
+            data.get_str("avatar"),
+            Some("https://example.com/alice.png")
+        );
+        assert_eq!(data.get("roles"), Some(&vec!["user"].into()));
+
+        let identity = OAuth2Identity {
+            claims: Map::new(),
+            ..identity
+        };
+        let data = identity.to_user_map();
+        assert_eq!(data.get_str("name"), Some("1024"));
+        assert!(data.get_str("email").is_none());
+    }
+}
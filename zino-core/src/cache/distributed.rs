@@ -0,0 +1,110 @@
+use crate::{accessor::GlobalAccessor, datetime::DateTime, error::Error};
+use opendal::{ErrorKind::NotFound, Operator};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+/// A distributed cache backed by a key-value service of the storage accessor,
+/// such as `redis`, `memcached` or `dashmap`. Values are serialized via MessagePack.
+#[derive(Debug, Clone)]
+pub struct DistributedCache {
+    /// Name of the accessor.
+    accessor: String,
+    /// Operator.
+    operator: Operator,
+    /// Prefix of the keys.
+    prefix: String,
+}
+
+/// Cache record stored in the key-value service.
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheRecord {
+    /// Value.
+    value: Value,
+    /// Expiration time as a Unix timestamp in milliseconds.
+    expires_at: Option<i64>,
+}
+
+impl DistributedCache {
+    /// Creates a new instance for the accessor.
+    #[inline]
+    pub fn new(accessor: impl Into<String>, operator: Operator) -> Self {
+        Self {
+            accessor: accessor.into(),
+            operator,
+            prefix: "cache".to_owned(),
+        }
+    }
+
+    /// Attempts to create a new instance for the accessor in `GlobalAccessor`.
+    pub fn try_new(accessor: &str) -> Result<Self, Error> {
+        let operator = GlobalAccessor::get(accessor)
+            .ok_or_else(|| Error::new(format!("accessor `{accessor}` does not exist")))?;
+        Ok(Self::new(accessor, operator.clone()))
+    }
+
+    /// Sets the prefix of the keys.
+    #[inline]
+    pub fn set_prefix(&mut self, prefix: impl Into<String>) {
+        self.prefix = prefix.into().trim_matches('/').to_owned();
+    }
+
+    /// Returns the name of the accessor.
+    #[inline]
+    pub fn accessor(&self) -> &str {
+        self.accessor.as_str()
+    }
+
+    /// Returns the value of the key and its remaining time-to-live,
+    /// or `None` if it does not exist or has expired.
+    pub async fn get(&self, key: &str) -> Result<Option<(Value, Option<Duration>)>, Error> {
+        let path = self.format_path(key);
+        let bytes = match self.operator.read(&path).await {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let record = rmp_serde::from_slice::<CacheRecord>(&bytes)?;
+        let ttl = if let Some(expires_at) = record.expires_at {
+            let millis = expires_at - DateTime::now().timestamp_millis();
+            if millis <= 0 {
+                self.operator.delete(&path).await?;
+                return Ok(None);
+            }
+            Some(Duration::from_millis(millis as u64))
+        } else {
+            None
+        };
+        Ok(Some((record.value, ttl)))
+    }
+
+    /// Sets the value of the key with the TTL.
+    pub async fn set(&self, key: &str, value: &Value, ttl: Option<Duration>) -> Result<(), Error> {
+        let expires_at = ttl.map(|ttl| {
+            let millis = i64::try_from(ttl.as_millis()).unwrap_or(i64::MAX);
+            DateTime::now().timestamp_millis().saturating_add(millis)
+        });
+        let record = CacheRecord {
+            value: value.clone(),
+            expires_at,
+        };
+        let bytes = rmp_serde::to_vec_named(&record)?;
+        self.operator.write(&self.format_path(key), bytes).await?;
+        Ok(())
+    }
+
+    /// Deletes the key.
+    pub async fn delete(&self, key: &str) -> Result<(), Error> {
+        self.operator.delete(&self.format_path(key)).await?;
+        Ok(())
+    }
+
+    /// Formats the path of the key.
+    fn format_path(&self, key: &str) -> String {
+        if self.prefix.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}/{key}", self.prefix)
+        }
+    }
+}
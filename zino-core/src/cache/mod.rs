@@ -1,23 +1,214 @@
 //! Global cache for the application.
+//!
+//! ## Namespaces
+//!
+//! The default namespace is configured by the `[cache]` table, and named namespaces
+//! with their own limits can be specified by the `[cache.namespaces.<name>]` tables.
+//! The name `default` is reserved for the default namespace.
+//! Each namespace supports the following fields:
+//!
+//! - `capacity`: maximum number of entries (10000 by default);
+//! - `ttl`: default time-to-live of the entries, such as `"10m"`;
+//! - `max-weight`: maximum total weight of the entries by the serialized size in bytes.
+//!
+//! The least recently used entries are evicted when the capacity or weight limit is exceeded,
+//! and expired entries are removed lazily. The hits, misses and evictions are recorded
+//! as the metrics `zino_cache_hits_total`, `zino_cache_misses_total` and
+//! `zino_cache_evictions_total` with the `namespace` label.
+//!
+//! ## Distributed cache
+//!
+//! When the service runs as several instances, a namespace can have a second tier
+//! backed by a key-value service of the storage accessor, such as `redis`, `memcached`
+//! or `dashmap`, by specifying the `accessor` field and an optional `prefix`.
+//! Values are serialized via MessagePack. The methods [`GlobalCache::load`],
+//! [`GlobalCache::store`] and [`GlobalCache::invalidate`] operate on both tiers.
+//!
+//! Invalidations are published as cloud events with the topic `zino.cache.invalidate`
+//! to the HTTP endpoints specified by the `event-sinks` array of the `[cache]` table,
+//! which can be the `/cache/events` endpoints of the other instances or a broker
+//! forwarding the events to them. The request body is signed with HMAC-SHA256 by a key
+//! derived from the application secret key in the `x-zino-signature` header, and
+//! the received events are verified and applied by [`GlobalCache::handle_signed_event`].
+//! The signed events should have a `time` within 5 minutes of the receiver's clock,
+//! and the ones which have been received are rejected as replays.
+//! Other transports can be plugged in via [`GlobalCache::set_event_emitter`]
+//! and [`GlobalCache::handle_event`]. The events from this instance are ignored.
+//!
 
-use crate::state::State;
-use lru::LruCache;
-use parking_lot::RwLock;
-use serde_json::Value;
-use std::{num::NonZeroUsize, sync::LazyLock};
+use crate::{
+    application::{self, http_client},
+    channel::CloudEvent,
+    datetime::DateTime,
+    error::Error,
+    extend::{JsonObjectExt, TomlTableExt},
+    format::base64,
+    state::State,
+    Map, Uuid,
+};
+use hmac::{Hmac, Mac};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    future::Future,
+    num::NonZeroUsize,
+    sync::{LazyLock, OnceLock},
+    time::Duration,
+};
+use toml::Table;
 
-/// Global cache built on the top of [`LruCache`](lru::LruCache).
+mod namespace;
+
+#[cfg(feature = "accessor")]
+mod distributed;
+
+pub use namespace::{CacheNamespace, CacheStats};
+
+#[cfg(feature = "accessor")]
+pub use distributed::DistributedCache;
+
+/// Topic of the cache invalidation events.
+pub const INVALIDATION_TOPIC: &str = "zino.cache.invalidate";
+
+/// Header of the signature of the cache invalidation events.
+pub const EVENT_SIGNATURE_HEADER: &str = "x-zino-signature";
+
+/// Global cache built on the top of [`LruCache`](lru::LruCache),
+/// which delegates to the default [`CacheNamespace`].
 #[derive(Debug, Clone, Copy, Default)]
 pub struct GlobalCache;
 
 impl GlobalCache {
+    /// Gets the cache namespace with the name.
+    #[inline]
+    pub fn namespace(name: &str) -> Option<&'static CacheNamespace> {
+        GLOBAL_CACHE_NAMESPACES.get(name)
+    }
+
+    /// Returns a cloned value of the key in the global cache, or loads it from
+    /// the distributed cache if it is missing locally.
+    #[inline]
+    pub async fn load(key: &str) -> Result<Option<Value>, Error> {
+        GLOBAL_CACHE.load(key).await
+    }
+
+    /// Puts a key-value pair into the global cache and the distributed cache.
+    #[inline]
+    pub async fn store(key: &str, value: impl Into<Value>) -> Result<(), Error> {
+        GLOBAL_CACHE.store(key, value).await
+    }
+
+    /// Removes the key from the global cache and the distributed cache,
+    /// and broadcasts an invalidation event to the other instances.
+    #[inline]
+    pub async fn invalidate(key: &str) -> Result<(), Error> {
+        GLOBAL_CACHE.invalidate(key).await
+    }
+
+    /// Sets the emitter for broadcasting the cache invalidation events
+    /// in addition to the event sinks. It can be only set once.
+    pub fn set_event_emitter(emitter: fn(CloudEvent)) {
+        if EVENT_EMITTER.set(emitter).is_err() {
+            tracing::warn!("the event emitter of the global cache has been set");
+        }
+    }
+
+    /// Handles the cloud event, returning `true` if it is a cache invalidation event
+    /// from another instance and has been applied.
+    pub fn handle_event(event: &CloudEvent) -> bool {
+        let Some((namespace, keys)) = parse_invalidation(event) else {
+            return false;
+        };
+        let namespace = match namespace {
+            "default" => LazyLock::force(&GLOBAL_CACHE),
+            name => match GlobalCache::namespace(name) {
+                Some(namespace) => namespace,
+                None => return false,
+            },
+        };
+        for key in keys {
+            namespace.pop(key);
+        }
+        true
+    }
+
+    /// Verifies the signature and the `time` of the cloud event received from an event sink
+    /// and handles it, returning `true` if it is a cache invalidation event from another instance
+    /// and has been applied. An event which is stale or has been received is rejected.
+    pub fn handle_signed_event(body: &[u8], signature: &str) -> Result<bool, Error> {
+        let signature = base64::decode(signature)?;
+        new_event_mac()?
+            .chain_update(body)
+            .verify_slice(&signature)
+            .map_err(|_| Error::new("invalid signature for the cache invalidation event"))?;
+
+        let event = serde_json::from_slice::<Map>(body)?;
+        let time = event
+            .get_str("time")
+            .and_then(|s| s.parse::<DateTime>().ok())
+            .ok_or_else(|| Error::new("invalid `time` for the cache invalidation event"))?;
+        let id = event.get_str("id").unwrap_or_default();
+        check_event_replay(
+            &mut RECEIVED_EVENTS.lock(),
+            id,
+            time.timestamp_millis(),
+            DateTime::now().timestamp_millis(),
+        )?;
+
+        let event = serde_json::from_value::<CloudEvent>(event.into())?;
+        Ok(Self::handle_event(&event))
+    }
+
+    /// Puts a key-value pair with the TTL into the global cache.
+    /// If the key already exists in the cache, then it updates the key’s value and
+    /// returns the old value. Otherwise, `None` is returned.
+    #[inline]
+    pub fn put_with_ttl(
+        key: impl Into<String>,
+        value: impl Into<Value>,
+        ttl: Duration,
+    ) -> Option<Value> {
+        GLOBAL_CACHE.put_with_ttl(key, value, Some(ttl))
+    }
+
+    /// Returns a cloned value of the key in the global cache, or loads it with the loader
+    /// if it is missing. Concurrent misses for the same key are coalesced.
+    #[inline]
+    pub async fn get_or_insert_with<F, Fut, E>(key: &str, loader: F) -> Result<Value, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value, E>>,
+    {
+        GLOBAL_CACHE.get_or_insert_with(key, loader).await
+    }
+
+    /// Removes the expired entries from the global cache,
+    /// returning the number of removed entries.
+    #[inline]
+    pub fn purge_expired() -> usize {
+        GLOBAL_CACHE.purge_expired()
+    }
+
+    /// Returns the total weight of the entries in the global cache.
+    #[inline]
+    pub fn weight() -> usize {
+        GLOBAL_CACHE.weight()
+    }
+
+    /// Returns the statistics of the global cache.
+    #[inline]
+    pub fn stats() -> CacheStats {
+        GLOBAL_CACHE.stats()
+    }
+
     /// Puts a key-value pair into the global cache.
     /// If the key already exists in the cache, then it updates the key’s value and
     /// returns the old value. Otherwise, `None` is returned.
     #[inline]
     pub fn put(key: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.put(key.into(), value.into())
+        GLOBAL_CACHE.put(key.into(), value.into())
     }
 
     /// Pushes a key-value pair into the global cache. If an entry with the key already
@@ -25,16 +216,14 @@ impl GlobalCache {
     /// then it returns the old entry’s key-value pair. Otherwise, returns `None`.
     #[inline]
     pub fn push(key: impl Into<String>, value: impl Into<Value>) -> Option<(String, Value)> {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.push(key.into(), value.into())
+        GLOBAL_CACHE.push(key.into(), value.into())
     }
 
     /// Returns a cloned value of the key in the global cache or `None`
     /// if it is not present in the cache. Moves the key to the head of the LRU list if it exists.
     #[inline]
     pub fn get(key: &str) -> Option<Value> {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.get(key).cloned()
+        GLOBAL_CACHE.get(key)
     }
 
     /// Returns a cloned value of the key in the global cache or `None`
@@ -42,108 +231,303 @@ impl GlobalCache {
     /// so the key’s position will be unchanged.
     #[inline]
     pub fn peek(key: &str) -> Option<Value> {
-        let cache = GLOBAL_CACHE.read();
-        cache.peek(key).cloned()
+        GLOBAL_CACHE.peek(key)
     }
 
     /// Returns a bool indicating whether the given key is in the global cache.
     /// Does not update the LRU list.
     #[inline]
     pub fn contains(key: &str) -> bool {
-        let cache = GLOBAL_CACHE.read();
-        cache.contains(key)
+        GLOBAL_CACHE.contains(key)
     }
 
     /// Removes and returns the value corresponding to the key from the global cache or
     /// `None` if it does not exist.
     #[inline]
     pub fn pop(key: &str) -> Option<Value> {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.pop(key)
+        GLOBAL_CACHE.pop(key)
     }
 
     /// Removes and returns the key-value pair from the global cache or
     /// `None` if it does not exist.
     #[inline]
     pub fn pop_entry(key: &str) -> Option<(String, Value)> {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.pop_entry(key)
+        GLOBAL_CACHE.pop_entry(key)
     }
 
     /// Removes and returns the key-value pair corresponding to the least recently used item
     /// or `None` if the global cache is empty.
     #[inline]
     pub fn pop_lru() -> Option<(String, Value)> {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.pop_lru()
+        GLOBAL_CACHE.pop_lru()
     }
 
     /// Marks the key as the most recently used one.
     #[inline]
     pub fn promote(key: &str) {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.promote(key)
+        GLOBAL_CACHE.promote(key)
     }
 
     /// Marks the key as the least recently used one.
     #[inline]
     pub fn demote(key: &str) {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.demote(key)
+        GLOBAL_CACHE.demote(key)
     }
 
     /// Returns the number of key-value pairs that are currently in the global cache.
     #[inline]
     pub fn len() -> usize {
-        let cache = GLOBAL_CACHE.read();
-        cache.len()
+        GLOBAL_CACHE.len()
     }
 
     /// Returns a bool indicating whether the global cache is empty or not.
     #[inline]
     pub fn is_empty() -> bool {
-        let cache = GLOBAL_CACHE.read();
-        cache.is_empty()
+        GLOBAL_CACHE.is_empty()
     }
 
     /// Returns the maximum number of key-value pairs the global cache can hold.
     #[inline]
     pub fn cap() -> NonZeroUsize {
-        let cache = GLOBAL_CACHE.read();
-        cache.cap()
+        GLOBAL_CACHE.cap()
     }
 
     /// Resizes the global cache. If the new capacity is smaller than the size of
     /// the current cache any entries past the new capacity are discarded.
     #[inline]
     pub fn resize(cap: NonZeroUsize) {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.resize(cap)
+        GLOBAL_CACHE.resize(cap)
     }
 
     /// Clears the contents of the global cache.
     pub fn clear() {
-        let mut cache = GLOBAL_CACHE.write();
-        cache.clear()
+        GLOBAL_CACHE.clear()
     }
 }
 
+/// Broadcasts the invalidation event for the keys in the namespace.
+async fn broadcast_invalidation(namespace: &str, keys: &[&str]) {
+    let event = new_invalidation_event(namespace, keys);
+    if let Some(emitter) = EVENT_EMITTER.get() {
+        emitter(event.clone());
+    }
+    if EVENT_SINKS.is_empty() {
+        return;
+    }
+
+    let body = match serde_json::to_string(&event) {
+        Ok(body) => body,
+        Err(err) => {
+            tracing::error!("fail to serialize the cache invalidation: {err}");
+            return;
+        }
+    };
+    let signature = match new_event_mac() {
+        Ok(mac) => base64::encode(mac.chain_update(&body).finalize().into_bytes()),
+        Err(err) => {
+            tracing::error!("fail to sign the cache invalidation: {err}");
+            return;
+        }
+    };
+    let requests = EVENT_SINKS
+        .iter()
+        .map(|sink| publish_event(sink, &body, &signature));
+    for (sink, result) in EVENT_SINKS
+        .iter()
+        .zip(futures::future::join_all(requests).await)
+    {
+        if let Err(err) = result {
+            tracing::error!("fail to publish the cache invalidation to `{sink}`: {err}");
+        }
+    }
+}
+
+/// Publishes the signed event to the sink.
+async fn publish_event(sink: &str, body: &str, signature: &str) -> Result<(), Error> {
+    let mut headers = Map::new();
+    headers.upsert("content-type", "application/cloudevents+json");
+    headers.upsert(EVENT_SIGNATURE_HEADER, signature);
+
+    let mut options = Map::new();
+    options.upsert("method", "POST");
+    options.upsert("body", body);
+    options.upsert("headers", headers);
+    http_client::request_builder(sink, Some(&options))?
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Creates an invalidation event for the keys in the namespace.
+fn new_invalidation_event(namespace: &str, keys: &[&str]) -> CloudEvent {
+    let data = json!({
+        "namespace": namespace,
+        "keys": keys,
+    });
+    CloudEvent::new(
+        Uuid::new_v4().to_string(),
+        EVENT_SOURCE.clone(),
+        INVALIDATION_TOPIC.to_owned(),
+        data,
+    )
+}
+
+/// Parses the namespace and keys of the invalidation event,
+/// returning `None` if it is not an invalidation event from another instance.
+fn parse_invalidation(event: &CloudEvent) -> Option<(&str, Vec<&str>)> {
+    if event.topic() != INVALIDATION_TOPIC || event.source() == EVENT_SOURCE.as_str() {
+        return None;
+    }
+
+    let data = event.data();
+    let namespace = data
+        .get("namespace")
+        .and_then(|v| v.as_str())
+        .unwrap_or("default");
+    let keys = data
+        .get("keys")
+        .and_then(|v| v.as_array())
+        .map(|keys| keys.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+    Some((namespace, keys))
+}
+
+/// Checks the time of the event and records its ID,
+/// returning an error if it is stale or has been received.
+fn check_event_replay(
+    received_events: &mut HashMap<String, i64>,
+    id: &str,
+    time: i64,
+    now: i64,
+) -> Result<(), Error> {
+    if (now - time).abs() > EVENT_MAX_AGE {
+        let message = format!("the cache invalidation event `{id}` is stale");
+        return Err(Error::new(message));
+    }
+    received_events.retain(|_, time| now - *time <= EVENT_MAX_AGE);
+    if received_events.insert(id.to_owned(), time).is_some() {
+        let message = format!("the cache invalidation event `{id}` has been received");
+        return Err(Error::new(message));
+    }
+    Ok(())
+}
+
+/// Creates a MAC for signing the cache invalidation events.
+fn new_event_mac() -> Result<Hmac<Sha256>, Error> {
+    let signing_key = application::derive_subkey("cache-invalidation")?;
+    Ok(Hmac::<Sha256>::new_from_slice(&signing_key).expect("HMAC can take key of any size"))
+}
+
+/// Maximum clock skew of the signed cache invalidation events in milliseconds.
+const EVENT_MAX_AGE: i64 = 5 * 60 * 1000;
+
+/// IDs and times of the signed cache invalidation events which have been received.
+static RECEIVED_EVENTS: LazyLock<Mutex<HashMap<String, i64>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Emitter of the cache invalidation events.
+static EVENT_EMITTER: OnceLock<fn(CloudEvent)> = OnceLock::new();
+
+/// Sinks of the cache invalidation events.
+static EVENT_SINKS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    State::shared()
+        .config()
+        .get_table("cache")
+        .and_then(|cache| cache.get_array("event-sinks"))
+        .map(|sinks| {
+            sinks
+                .iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_owned()))
+                .collect()
+        })
+        .unwrap_or_default()
+});
+
+/// Source of the cache invalidation events emitted by this instance.
+static EVENT_SOURCE: LazyLock<String> = LazyLock::new(|| format!("zino-cache/{}", Uuid::new_v4()));
+
 /// Global cache.
-static GLOBAL_CACHE: LazyLock<RwLock<LruCache<String, Value>>> = LazyLock::new(|| {
-    let capacity = if let Some(cache) = State::shared().config().get("cache") {
-        cache
+static GLOBAL_CACHE: LazyLock<CacheNamespace> = LazyLock::new(|| {
+    let config = State::shared().config();
+    if let Some(cache) = config.get("cache") {
+        let cache = cache
             .as_table()
-            .expect("the `cache` field should be a table")
-            .get("capacity")
-            .expect("the `cache.capacity` field is missing")
-            .as_integer()
-            .expect("the `cache.capacity` field should be an integer")
-            .try_into()
-            .expect("the `cache.capacity` field should be a positive integer")
+            .expect("the `cache` field should be a table");
+        CacheNamespace::with_config("default", cache)
     } else {
-        10000
-    };
-    RwLock::new(LruCache::new(
-        NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN),
-    ))
+        CacheNamespace::with_config("default", &Table::new())
+    }
 });
+
+/// Global cache namespaces.
+static GLOBAL_CACHE_NAMESPACES: LazyLock<HashMap<&'static str, CacheNamespace>> =
+    LazyLock::new(|| {
+        let mut namespaces = HashMap::new();
+        if let Some(cache) = State::shared().config().get_table("cache")
+            && let Some(tables) = cache.get_table("namespaces")
+        {
+            for (name, config) in tables {
+                if name == "default" {
+                    panic!("the `cache.namespaces.default` name is reserved");
+                }
+                let config = config.as_table().unwrap_or_else(|| {
+                    panic!("the `cache.namespaces.{name}` field should be a table")
+                });
+                namespaces.insert(name.as_str(), CacheNamespace::with_config(name, config));
+            }
+        }
+        namespaces
+    });
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_event_replay, new_invalidation_event, parse_invalidation, EVENT_MAX_AGE,
+        INVALIDATION_TOPIC,
+    };
+    use crate::{channel::CloudEvent, Uuid};
+    use std::collections::HashMap;
+
+    #[test]
+    fn it_parses_invalidation_events() {
+        let event = new_invalidation_event("sessions", &["alice", "bob"]);
+        assert!(parse_invalidation(&event).is_none());
+
+        let foreign_event = CloudEvent::new(
+            Uuid::new_v4().to_string(),
+            format!("zino-cache/{}", Uuid::new_v4()),
+            INVALIDATION_TOPIC.to_owned(),
+            event.data().clone(),
+        );
+        assert_eq!(
+            parse_invalidation(&foreign_event),
+            Some(("sessions", vec!["alice", "bob"]))
+        );
+
+        let other_event = CloudEvent::new(
+            Uuid::new_v4().to_string(),
+            foreign_event.source().to_owned(),
+            "zino.cache.update".to_owned(),
+            event.data().clone(),
+        );
+        assert!(parse_invalidation(&other_event).is_none());
+    }
+
+    #[test]
+    fn it_rejects_replayed_events() {
+        let mut received_events = HashMap::new();
+        let now = 1_700_000_000_000;
+        assert!(check_event_replay(&mut received_events, "a", now - 1000, now).is_ok());
+        assert!(check_event_replay(&mut received_events, "a", now - 1000, now).is_err());
+
+        let skew = EVENT_MAX_AGE + 1;
+        assert!(check_event_replay(&mut received_events, "b", now - skew, now).is_err());
+        assert!(check_event_replay(&mut received_events, "c", now + skew, now).is_err());
+
+        let later = now + EVENT_MAX_AGE;
+        assert!(check_event_replay(&mut received_events, "d", later, later).is_ok());
+        assert!(!received_events.contains_key("a"));
+        assert!(received_events.contains_key("d"));
+    }
+}
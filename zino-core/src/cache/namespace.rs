@@ -0,0 +1,592 @@
+use crate::{error::Error, extend::TomlTableExt};
+use lru::LruCache;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    future::Future,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::OnceCell;
+use toml::Table;
+
+/// Default capacity of a cache namespace.
+const DEFAULT_CAPACITY: usize = 10000;
+
+/// A cache namespace with the LRU eviction policy, per-entry TTLs
+/// and the weight limit by the serialized size of values.
+#[derive(Debug)]
+pub struct CacheNamespace {
+    /// Namespace name.
+    name: String,
+    /// Cache store.
+    store: RwLock<CacheStore>,
+    /// Default time-to-live of the entries.
+    ttl: Option<Duration>,
+    /// Maximum total weight of the entries in bytes.
+    max_weight: Option<usize>,
+    /// Number of hits.
+    hits: AtomicU64,
+    /// Number of misses.
+    misses: AtomicU64,
+    /// Number of evictions, including the expired entries.
+    evictions: AtomicU64,
+    /// Loaders in flight for the missing keys.
+    loaders: Mutex<HashMap<String, Arc<OnceCell<Value>>>>,
+    /// Distributed cache as the second tier.
+    #[cfg(feature = "accessor")]
+    distributed: Option<super::DistributedCache>,
+}
+
+/// Statistics of a cache namespace.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheStats {
+    /// Number of hits.
+    pub hits: u64,
+    /// Number of misses.
+    pub misses: u64,
+    /// Number of evictions, including the expired entries.
+    pub evictions: u64,
+    /// Number of entries.
+    pub len: usize,
+    /// Total weight of the entries in bytes.
+    pub weight: usize,
+}
+
+/// Cache store.
+#[derive(Debug)]
+struct CacheStore {
+    /// LRU cache.
+    entries: LruCache<String, CacheEntry>,
+    /// Total weight of the entries in bytes.
+    weight: usize,
+}
+
+/// Cache entry.
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    /// Value.
+    value: Value,
+    /// Serialized size of the value in bytes.
+    weight: usize,
+    /// Expiration time.
+    expires_at: Option<Instant>,
+}
+
+impl CacheNamespace {
+    /// Creates a new instance with the capacity.
+    pub fn new(name: impl Into<String>, cap: NonZeroUsize) -> Self {
+        Self {
+            name: name.into(),
+            store: RwLock::new(CacheStore {
+                entries: LruCache::new(cap),
+                weight: 0,
+            }),
+            ttl: None,
+            max_weight: None,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            loaders: Mutex::new(HashMap::new()),
+            #[cfg(feature = "accessor")]
+            distributed: None,
+        }
+    }
+
+    /// Creates a new instance with the config.
+    pub fn with_config(name: impl Into<String>, config: &Table) -> Self {
+        let capacity = config.get_usize("capacity").unwrap_or(DEFAULT_CAPACITY);
+        let mut namespace = Self::new(
+            name,
+            NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN),
+        );
+        namespace.ttl = config.get_duration("ttl");
+        namespace.max_weight = config.get_usize("max-weight");
+
+        #[cfg(feature = "accessor")]
+        if let Some(accessor) = config.get_str("accessor") {
+            let mut distributed = super::DistributedCache::try_new(accessor)
+                .unwrap_or_else(|err| panic!("fail to create the distributed cache: {err}"));
+            let prefix = config.get_str("prefix").unwrap_or("cache");
+            distributed.set_prefix(format!("{prefix}/{}", namespace.name));
+            namespace.distributed = Some(distributed);
+        }
+        namespace
+    }
+
+    /// Sets the default time-to-live of the entries.
+    #[inline]
+    pub fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = Some(ttl);
+    }
+
+    /// Sets the maximum total weight of the entries in bytes.
+    #[inline]
+    pub fn set_max_weight(&mut self, max_weight: usize) {
+        self.max_weight = Some(max_weight);
+    }
+
+    /// Sets the distributed cache as the second tier.
+    #[cfg(feature = "accessor")]
+    #[inline]
+    pub fn set_distributed(&mut self, distributed: super::DistributedCache) {
+        self.distributed = Some(distributed);
+    }
+
+    /// Returns the namespace name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the default time-to-live of the entries.
+    #[inline]
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Returns the maximum total weight of the entries in bytes.
+    #[inline]
+    pub fn max_weight(&self) -> Option<usize> {
+        self.max_weight
+    }
+
+    /// Puts a key-value pair with the default TTL into the cache.
+    /// If the key already exists in the cache, then it updates the key’s value and
+    /// returns the old value. Otherwise, `None` is returned.
+    #[inline]
+    pub fn put(&self, key: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+        self.put_with_ttl(key, value, self.ttl)
+    }
+
+    /// Puts a key-value pair with the TTL into the cache.
+    /// If the key already exists in the cache, then it updates the key’s value and
+    /// returns the old value. Otherwise, `None` is returned.
+    pub fn put_with_ttl(
+        &self,
+        key: impl Into<String>,
+        value: impl Into<Value>,
+        ttl: Option<Duration>,
+    ) -> Option<Value> {
+        let key = key.into();
+        let entry = CacheEntry::new(value.into(), ttl);
+        let mut store = self.store.write();
+        if self
+            .max_weight
+            .map_or(false, |max_weight| entry.weight > max_weight)
+        {
+            self.record_evictions(1);
+            return store.remove(&key).and_then(|entry| entry.into_value());
+        }
+
+        let weight = entry.weight;
+        let old_entry = store.entries.push(key.clone(), entry);
+        store.weight += weight;
+        let old_value = match old_entry {
+            Some((old_key, old_entry)) => {
+                store.weight -= old_entry.weight;
+                if old_key == key {
+                    old_entry.into_value()
+                } else {
+                    self.record_evictions(1);
+                    None
+                }
+            }
+            None => None,
+        };
+        self.evict_overweight(&mut store);
+        old_value
+    }
+
+    /// Pushes a key-value pair with the default TTL into the cache. If an entry with the key
+    /// already exists in the cache or another cache entry is removed (due to the LRU’s capacity),
+    /// then it returns the old entry’s key-value pair. Otherwise, returns `None`.
+    pub fn push(&self, key: impl Into<String>, value: impl Into<Value>) -> Option<(String, Value)> {
+        let key = key.into();
+        let entry = CacheEntry::new(value.into(), self.ttl);
+        let mut store = self.store.write();
+        if self
+            .max_weight
+            .map_or(false, |max_weight| entry.weight > max_weight)
+        {
+            self.record_evictions(1);
+            return Some((key, entry.value));
+        }
+
+        let weight = entry.weight;
+        let old_entry = store.entries.push(key.clone(), entry);
+        store.weight += weight;
+        if let Some((old_key, old_entry)) = &old_entry {
+            store.weight -= old_entry.weight;
+            if *old_key != key {
+                self.record_evictions(1);
+            }
+        }
+        self.evict_overweight(&mut store);
+        old_entry.map(|(key, entry)| (key, entry.value))
+    }
+
+    /// Returns a cloned value of the key in the cache or `None` if it is not present
+    /// or has expired. Moves the key to the head of the LRU list if it exists.
+    pub fn get(&self, key: &str) -> Option<Value> {
+        let mut store = self.store.write();
+        let value = store
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.value.clone());
+        if value.is_some() {
+            self.hits.fetch_add(1, Relaxed);
+            metrics::increment_counter!("zino_cache_hits_total", "namespace" => self.name.clone());
+        } else {
+            if store.remove(key).is_some() {
+                self.record_evictions(1);
+            }
+            self.misses.fetch_add(1, Relaxed);
+            metrics::increment_counter!("zino_cache_misses_total", "namespace" => self.name.clone());
+        }
+        value
+    }
+
+    /// Returns a cloned value of the key in the cache or `None` if it is not present
+    /// or has expired. It does not update the LRU list so the key’s position will be unchanged.
+    pub fn peek(&self, key: &str) -> Option<Value> {
+        let store = self.store.read();
+        store
+            .entries
+            .peek(key)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.value.clone())
+    }
+
+    /// Returns a bool indicating whether the given key is in the cache and has not expired.
+    /// Does not update the LRU list.
+    pub fn contains(&self, key: &str) -> bool {
+        let store = self.store.read();
+        store
+            .entries
+            .peek(key)
+            .map_or(false, |entry| !entry.is_expired())
+    }
+
+    /// Removes and returns the value corresponding to the key from the cache or
+    /// `None` if it does not exist or has expired.
+    #[inline]
+    pub fn pop(&self, key: &str) -> Option<Value> {
+        let mut store = self.store.write();
+        store.remove(key).and_then(|entry| entry.into_value())
+    }
+
+    /// Removes and returns the key-value pair from the cache or
+    /// `None` if it does not exist or has expired.
+    pub fn pop_entry(&self, key: &str) -> Option<(String, Value)> {
+        let mut store = self.store.write();
+        let (key, entry) = store.entries.pop_entry(key)?;
+        store.weight -= entry.weight;
+        entry.into_value().map(|value| (key, value))
+    }
+
+    /// Removes and returns the key-value pair corresponding to the least recently used item
+    /// or `None` if the cache is empty.
+    pub fn pop_lru(&self) -> Option<(String, Value)> {
+        let mut store = self.store.write();
+        let (key, entry) = store.entries.pop_lru()?;
+        store.weight -= entry.weight;
+        Some((key, entry.value))
+    }
+
+    /// Marks the key as the most recently used one.
+    #[inline]
+    pub fn promote(&self, key: &str) {
+        let mut store = self.store.write();
+        store.entries.promote(key)
+    }
+
+    /// Marks the key as the least recently used one.
+    #[inline]
+    pub fn demote(&self, key: &str) {
+        let mut store = self.store.write();
+        store.entries.demote(key)
+    }
+
+    /// Returns the number of key-value pairs that are currently in the cache,
+    /// including the expired entries which have not been removed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        let store = self.store.read();
+        store.entries.len()
+    }
+
+    /// Returns a bool indicating whether the cache is empty or not.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        let store = self.store.read();
+        store.entries.is_empty()
+    }
+
+    /// Returns the total weight of the entries in bytes.
+    #[inline]
+    pub fn weight(&self) -> usize {
+        let store = self.store.read();
+        store.weight
+    }
+
+    /// Returns the maximum number of key-value pairs the cache can hold.
+    #[inline]
+    pub fn cap(&self) -> NonZeroUsize {
+        let store = self.store.read();
+        store.entries.cap()
+    }
+
+    /// Resizes the cache. If the new capacity is smaller than the size of
+    /// the current cache any entries past the new capacity are discarded.
+    pub fn resize(&self, cap: NonZeroUsize) {
+        let mut store = self.store.write();
+        let mut num_evicted = 0;
+        while store.entries.len() > cap.get() {
+            if let Some((_, entry)) = store.entries.pop_lru() {
+                store.weight -= entry.weight;
+                num_evicted += 1;
+            }
+        }
+        store.entries.resize(cap);
+        self.record_evictions(num_evicted);
+    }
+
+    /// Removes the expired entries, returning the number of removed entries.
+    pub fn purge_expired(&self) -> usize {
+        let mut store = self.store.write();
+        let expired_keys = store
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.is_expired())
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in expired_keys.iter() {
+            store.remove(key);
+        }
+
+        let num_expired = expired_keys.len();
+        self.record_evictions(num_expired as u64);
+        num_expired
+    }
+
+    /// Clears the contents of the cache.
+    pub fn clear(&self) {
+        let mut store = self.store.write();
+        store.entries.clear();
+        store.weight = 0;
+    }
+
+    /// Returns the statistics of the cache.
+    pub fn stats(&self) -> CacheStats {
+        let store = self.store.read();
+        CacheStats {
+            hits: self.hits.load(Relaxed),
+            misses: self.misses.load(Relaxed),
+            evictions: self.evictions.load(Relaxed),
+            len: store.entries.len(),
+            weight: store.weight,
+        }
+    }
+
+    /// Returns a cloned value of the key in the cache, or loads it with the loader
+    /// and puts it into the cache if it is missing. Concurrent misses for the same key
+    /// are coalesced so that the loader is called only once. If the loader fails,
+    /// the error is returned and the waiting callers will try their own loaders.
+    pub async fn get_or_insert_with<F, Fut, E>(&self, key: &str, loader: F) -> Result<Value, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Value, E>>,
+    {
+        if let Some(value) = self.get(key) {
+            return Ok(value);
+        }
+
+        let cell = self
+            .loaders
+            .lock()
+            .entry(key.to_owned())
+            .or_insert_with(|| Arc::new(OnceCell::new()))
+            .clone();
+        let mut loaded = false;
+        let result = cell
+            .get_or_try_init(|| {
+                loaded = true;
+                loader()
+            })
+            .await
+            .cloned();
+        if loaded {
+            if let Ok(value) = &result {
+                self.put(key, value.clone());
+            }
+
+            let mut loaders = self.loaders.lock();
+            if loaders.get(key).map_or(false, |c| Arc::ptr_eq(c, &cell)) {
+                loaders.remove(key);
+            }
+        }
+        result
+    }
+
+    /// Returns a cloned value of the key in the cache, or loads it from the distributed cache
+    /// if it is missing locally. The loaded value is put into the local cache.
+    pub async fn load(&self, key: &str) -> Result<Option<Value>, Error> {
+        if let Some(value) = self.get(key) {
+            return Ok(Some(value));
+        }
+
+        #[cfg(feature = "accessor")]
+        if let Some(distributed) = &self.distributed
+            && let Some((value, ttl)) = distributed.get(key).await?
+        {
+            let ttl = match (ttl, self.ttl) {
+                (Some(ttl), Some(default_ttl)) => Some(ttl.min(default_ttl)),
+                (ttl, default_ttl) => ttl.or(default_ttl),
+            };
+            self.put_with_ttl(key, value.clone(), ttl);
+            return Ok(Some(value));
+        }
+        Ok(None)
+    }
+
+    /// Puts a key-value pair with the default TTL into the cache
+    /// and writes it into the distributed cache.
+    #[inline]
+    pub async fn store(&self, key: &str, value: impl Into<Value>) -> Result<(), Error> {
+        self.store_with_ttl(key, value, self.ttl).await
+    }
+
+    /// Puts a key-value pair with the TTL into the cache
+    /// and writes it into the distributed cache.
+    pub async fn store_with_ttl(
+        &self,
+        key: &str,
+        value: impl Into<Value>,
+        ttl: Option<Duration>,
+    ) -> Result<(), Error> {
+        let value = value.into();
+
+        #[cfg(feature = "accessor")]
+        if let Some(distributed) = &self.distributed {
+            distributed.set(key, &value, ttl).await?;
+        }
+
+        self.put_with_ttl(key, value, ttl);
+        Ok(())
+    }
+
+    /// Removes the key from the cache and the distributed cache,
+    /// and broadcasts an invalidation event to the other instances.
+    pub async fn invalidate(&self, key: &str) -> Result<(), Error> {
+        self.pop(key);
+
+        #[cfg(feature = "accessor")]
+        if let Some(distributed) = &self.distributed {
+            distributed.delete(key).await?;
+        }
+
+        super::broadcast_invalidation(self.name(), &[key]).await;
+        Ok(())
+    }
+
+    /// Evicts the least recently used entries until the weight limit is satisfied.
+    fn evict_overweight(&self, store: &mut CacheStore) {
+        if let Some(max_weight) = self.max_weight {
+            let mut num_evicted = 0;
+            while store.weight > max_weight {
+                match store.entries.pop_lru() {
+                    Some((_, entry)) => {
+                        store.weight -= entry.weight;
+                        num_evicted += 1;
+                    }
+                    None => break,
+                }
+            }
+            self.record_evictions(num_evicted);
+        }
+    }
+
+    /// Records the number of evictions.
+    fn record_evictions(&self, num_evicted: u64) {
+        if num_evicted > 0 {
+            self.evictions.fetch_add(num_evicted, Relaxed);
+            metrics::counter!(
+                "zino_cache_evictions_total",
+                num_evicted,
+                "namespace" => self.name.clone(),
+            );
+        }
+    }
+}
+
+impl CacheStore {
+    /// Removes the entry with the key and updates the total weight.
+    fn remove(&mut self, key: &str) -> Option<CacheEntry> {
+        let entry = self.entries.pop(key)?;
+        self.weight -= entry.weight;
+        Some(entry)
+    }
+}
+
+impl CacheEntry {
+    /// Creates a new instance with the TTL.
+    fn new(value: Value, ttl: Option<Duration>) -> Self {
+        let weight = serde_json::to_vec(&value).map_or(0, |bytes| bytes.len());
+        Self {
+            value,
+            weight,
+            expires_at: ttl.and_then(|ttl| Instant::now().checked_add(ttl)),
+        }
+    }
+
+    /// Returns `true` if the entry has expired.
+    #[inline]
+    fn is_expired(&self) -> bool {
+        self.expires_at
+            .map_or(false, |expires_at| expires_at <= Instant::now())
+    }
+
+    /// Consumes the entry and returns the value if it has not expired.
+    #[inline]
+    fn into_value(self) -> Option<Value> {
+        if self.is_expired() {
+            None
+        } else {
+            Some(self.value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CacheNamespace;
+    use std::{num::NonZeroUsize, time::Duration};
+
+    #[test]
+    fn it_evicts_entries_by_weight_and_ttl() {
+        let mut namespace = CacheNamespace::new("test", NonZeroUsize::new(10).unwrap());
+        namespace.set_max_weight(16);
+        namespace.put("a", "aaaa");
+        namespace.put("b", "bbbb");
+        namespace.put("c", "cccc");
+        assert_eq!(namespace.weight(), 12);
+        assert!(!namespace.contains("a"));
+        assert_eq!(namespace.get("c"), Some("cccc".into()));
+
+        namespace.put_with_ttl("d", 1, Some(Duration::ZERO));
+        assert_eq!(namespace.get("d"), None);
+        assert_eq!(namespace.put("e", "a value which is too large"), None);
+        assert!(!namespace.contains("e"));
+
+        let stats = namespace.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 1, 3));
+    }
+}
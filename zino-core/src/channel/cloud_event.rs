@@ -75,6 +75,12 @@ impl CloudEvent {
         self.topic.as_str()
     }
 
+    /// Returns a reference to the event data.
+    #[inline]
+    pub fn data(&self) -> &Value {
+        &self.data
+    }
+
     /// Stringifies the event data as `String`.
     #[inline]
     pub fn stringify_data(&self) -> String {
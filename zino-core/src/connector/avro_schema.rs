@@ -0,0 +1,202 @@
+use crate::{error::Error, Record};
+use apache_avro::{types::Value, Schema, Writer};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde_json::Value as JsonValue;
+use std::io::Write;
+
+/// Resolves the record with the reader schema according to the Avro schema evolution rules.
+///
+/// Fields are matched by the name or aliases, missing fields are filled with the defaults,
+/// and values are promoted to the reader types. Fields whose schema contains references
+/// are kept as they are since the named types can not be resolved without the definitions.
+pub(super) fn resolve_record(mut record: Record, schema: &Schema) -> Result<Record, Error> {
+    let Schema::Record { fields, .. } = schema else {
+        return Err(Error::new("the reader schema should be a record"));
+    };
+
+    let aliases = parse_field_aliases(schema);
+    let mut resolved_record = Record::with_capacity(fields.len());
+    for field in fields {
+        let name = field.name.as_str();
+        let position = record.iter().position(|(key, _)| key == name).or_else(|| {
+            aliases
+                .iter()
+                .filter(|(field_name, _)| field_name == name)
+                .find_map(|(_, alias)| record.iter().position(|(key, _)| key == alias))
+        });
+        let value = if let Some(index) = position {
+            let (_, value) = record.swap_remove(index);
+            value
+        } else if let Some(default) = field.default.clone() {
+            Value::from(default)
+        } else if is_nullable(&field.schema) {
+            Value::Null
+        } else {
+            let message = format!("field `{name}` is missing and has no default value");
+            return Err(Error::new(message));
+        };
+        let value = if is_resolvable(&field.schema) {
+            coerce_value(value, &field.schema).resolve(&field.schema)?
+        } else {
+            value
+        };
+        resolved_record.push((name.to_owned(), value));
+    }
+    Ok(resolved_record)
+}
+
+/// Writes the records as an Avro object container with the schema,
+/// returning the number of bytes written.
+pub(super) fn write_records<W: Write>(
+    records: Vec<Record>,
+    schema: &Schema,
+    writer: W,
+) -> Result<usize, Error> {
+    let mut writer = Writer::new(schema, writer);
+    let mut num_bytes = writer.extend(records.into_iter().map(Value::Record))?;
+    num_bytes += writer.flush()?;
+    Ok(num_bytes)
+}
+
+/// Parses the field aliases from the JSON representation of the record schema.
+fn parse_field_aliases(schema: &Schema) -> Vec<(String, String)> {
+    let mut aliases = Vec::new();
+    if let Ok(JsonValue::Object(map)) = serde_json::to_value(schema)
+        && let Some(JsonValue::Array(fields)) = map.get("fields")
+    {
+        for field in fields {
+            if let Some(name) = field.get("name").and_then(|v| v.as_str())
+                && let Some(JsonValue::Array(field_aliases)) = field.get("aliases")
+            {
+                for alias in field_aliases.iter().filter_map(|v| v.as_str()) {
+                    aliases.push((name.to_owned(), alias.to_owned()));
+                }
+            }
+        }
+    }
+    aliases
+}
+
+/// Returns `true` if the schema is a union with `null`.
+fn is_nullable(schema: &Schema) -> bool {
+    if let Schema::Union(union_schema) = schema {
+        union_schema.variants().contains(&Schema::Null)
+    } else {
+        false
+    }
+}
+
+/// Returns `true` if the schema does not contain any references.
+fn is_resolvable(schema: &Schema) -> bool {
+    match schema {
+        Schema::Ref { .. } => false,
+        Schema::Array(schema) | Schema::Map(schema) => is_resolvable(schema),
+        Schema::Union(union_schema) => union_schema.variants().iter().all(is_resolvable),
+        Schema::Record { fields, .. } => fields.iter().all(|field| is_resolvable(&field.schema)),
+        _ => true,
+    }
+}
+
+/// Coerces the value represented by a string into the logical type of the schema,
+/// since date and time values are returned as strings by some data sources.
+fn coerce_value(value: Value, schema: &Schema) -> Value {
+    let Value::String(ref s) = value else {
+        return value;
+    };
+    match schema {
+        Schema::Union(union_schema) => union_schema
+            .variants()
+            .iter()
+            .find(|schema| **schema != Schema::Null)
+            .map_or(value.clone(), |schema| coerce_value(value.clone(), schema)),
+        Schema::Date => s
+            .get(..10)
+            .and_then(|s| s.parse::<NaiveDate>().ok())
+            .and_then(|date| {
+                let days = date.signed_duration_since(NaiveDate::default()).num_days();
+                i32::try_from(days).ok()
+            })
+            .map_or(value, Value::Date),
+        Schema::TimeMillis => s.parse::<NaiveTime>().map_or(value, |time| {
+            let millis = time.num_seconds_from_midnight() * 1000 + time.nanosecond() / 1_000_000;
+            Value::TimeMillis(millis as i32)
+        }),
+        Schema::TimeMicros => s.parse::<NaiveTime>().map_or(value, |time| {
+            let micros = i64::from(time.num_seconds_from_midnight()) * 1_000_000
+                + i64::from(time.nanosecond() / 1_000);
+            Value::TimeMicros(micros)
+        }),
+        Schema::TimestampMillis => {
+            parse_timestamp(s).map_or(value, |dt| Value::TimestampMillis(dt.timestamp_millis()))
+        }
+        Schema::TimestampMicros => {
+            parse_timestamp(s).map_or(value, |dt| Value::TimestampMicros(dt.timestamp_micros()))
+        }
+        _ => value,
+    }
+}
+
+/// Parses a string as the date and time in UTC.
+fn parse_timestamp(s: &str) -> Option<NaiveDateTime> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
+        Some(dt.naive_utc())
+    } else {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f")
+            .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f"))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_record;
+    use apache_avro::{types::Value, Schema};
+
+    #[test]
+    fn it_resolves_records_with_schema_evolution() {
+        let schema = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "user",
+                "fields": [
+                    { "name": "id", "type": "long" },
+                    { "name": "name", "type": "string" },
+                    { "name": "score", "type": "double" },
+                    { "name": "status", "type": "string", "default": "active" },
+                    { "name": "created_at", "type": { "type": "long", "logicalType": "timestamp-micros" } },
+                    { "name": "tags", "type": ["null", { "type": "array", "items": "string" }] }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let record = vec![
+            ("id".to_owned(), Value::Int(1)),
+            ("name".to_owned(), Value::String("alice".to_owned())),
+            ("score".to_owned(), Value::Float(0.5)),
+            (
+                "created_at".to_owned(),
+                Value::String("2023-03-01T00:00:00Z".to_owned()),
+            ),
+            ("extra".to_owned(), Value::Boolean(true)),
+        ];
+        let record = resolve_record(record, &schema).unwrap();
+        assert_eq!(record.len(), 6);
+        assert_eq!(record[0], ("id".to_owned(), Value::Long(1)));
+        assert_eq!(record[2], ("score".to_owned(), Value::Double(0.5)));
+        assert_eq!(
+            record[3],
+            ("status".to_owned(), Value::String("active".to_owned()))
+        );
+        assert_eq!(
+            record[4],
+            (
+                "created_at".to_owned(),
+                Value::TimestampMicros(1_677_628_800_000_000)
+            )
+        );
+        assert_eq!(
+            record[5],
+            ("tags".to_owned(), Value::Union(0, Box::new(Value::Null)))
+        );
+    }
+}
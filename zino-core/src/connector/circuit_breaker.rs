@@ -0,0 +1,242 @@
+use crate::{datetime::DateTime, error::Error, extend::JsonObjectExt, Map};
+use parking_lot::Mutex;
+use regex::Regex;
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+/// States of the circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are allowed to pass through.
+    Closed,
+    /// Requests fail fast without touching the data source.
+    Open,
+    /// A trial request is allowed to check whether the data source has recovered.
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Returns the state as `&str`.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half-open",
+        }
+    }
+}
+
+/// A circuit breaker for the data source.
+#[derive(Debug)]
+pub(super) struct CircuitBreaker {
+    /// Number of consecutive failures to open the circuit.
+    failure_threshold: u32,
+    /// Duration to wait before switching from open to half-open.
+    reset_timeout: Duration,
+    /// Health status.
+    status: Mutex<HealthStatus>,
+}
+
+/// Health status recorded by the circuit breaker.
+#[derive(Debug, Default)]
+struct HealthStatus {
+    /// Number of consecutive failures.
+    consecutive_failures: u32,
+    /// Time when the circuit was opened.
+    opened_at: Option<Instant>,
+    /// Start time of the trial request in the half-open state.
+    trial_started_at: Option<Instant>,
+    /// Time of the last health check.
+    checked_at: Option<DateTime>,
+    /// Latency of the last health check.
+    latency: Option<Duration>,
+    /// Last error message.
+    last_error: Option<String>,
+}
+
+impl CircuitBreaker {
+    /// Creates a new instance.
+    #[inline]
+    pub(super) fn new(failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            reset_timeout,
+            status: Mutex::new(HealthStatus::default()),
+        }
+    }
+
+    /// Returns the current state.
+    pub(super) fn state(&self) -> CircuitState {
+        let status = self.status.lock();
+        match status.opened_at {
+            Some(opened_at) if opened_at.elapsed() < self.reset_timeout => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+            None => CircuitState::Closed,
+        }
+    }
+
+    /// Checks whether a request is allowed to pass through,
+    /// returning an error if the circuit is open.
+    /// Only a single trial request is allowed in the half-open state,
+    /// and another one is admitted if it has not finished within the reset timeout.
+    pub(super) fn check(&self, name: &str) -> Result<(), Error> {
+        let mut status = self.status.lock();
+        if let Some(opened_at) = status.opened_at {
+            let reset_timeout = self.reset_timeout;
+            let is_trial_allowed = opened_at.elapsed() >= reset_timeout
+                && status
+                    .trial_started_at
+                    .map_or(true, |started_at| started_at.elapsed() >= reset_timeout);
+            if is_trial_allowed {
+                status.trial_started_at = Some(Instant::now());
+            } else {
+                let message = format!("data source `{name}` is unavailable: the circuit is open");
+                if let Some(last_error) = status.last_error.clone() {
+                    return Err(Error::with_source(message, Error::new(last_error)));
+                }
+                return Err(Error::new(message));
+            }
+        }
+        Ok(())
+    }
+
+    /// Records a successful request and closes the circuit.
+    pub(super) fn record_success(&self) {
+        let mut status = self.status.lock();
+        status.consecutive_failures = 0;
+        status.opened_at = None;
+        status.trial_started_at = None;
+    }
+
+    /// Records a failed request, and opens the circuit if
+    /// the number of consecutive failures reaches the threshold.
+    pub(super) fn record_failure(&self, err: &Error) {
+        let mut status = self.status.lock();
+        status.consecutive_failures += 1;
+        status.last_error = Some(err.message().to_owned());
+        status.trial_started_at = None;
+        if status.opened_at.is_some() || status.consecutive_failures >= self.failure_threshold {
+            status.opened_at = Some(Instant::now());
+        }
+    }
+
+    /// Records the result of a health check.
+    pub(super) fn record_health_check(&self, result: Result<Duration, &Error>) {
+        match result {
+            Ok(latency) => {
+                self.record_success();
+                let mut status = self.status.lock();
+                status.latency = Some(latency);
+                status.checked_at = Some(DateTime::now());
+            }
+            Err(err) => {
+                self.record_failure(err);
+                let mut status = self.status.lock();
+                status.latency = None;
+                status.checked_at = Some(DateTime::now());
+            }
+        }
+    }
+
+    /// Returns the health status as a JSON object.
+    pub(super) fn status(&self) -> Map {
+        let state = self.state();
+        let status = self.status.lock();
+        let mut map = Map::new();
+        map.upsert("available", state != CircuitState::Open);
+        map.upsert("circuit_state", state.as_str());
+        map.upsert("consecutive_failures", status.consecutive_failures);
+        if let Some(checked_at) = status.checked_at {
+            map.upsert("checked_at", checked_at);
+        }
+        if let Some(latency) = status.latency {
+            map.upsert("latency_millis", latency.as_secs_f64() * 1000.0);
+        }
+        if let Some(last_error) = status.last_error.as_deref() {
+            map.upsert("last_error", sanitize_error(last_error));
+        }
+        map
+    }
+}
+
+impl Default for CircuitBreaker {
+    #[inline]
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(30))
+    }
+}
+
+/// Sanitizes the error message for the status by redacting the credentials
+/// in the connection URLs and the secret parameters, and truncating long messages.
+pub(super) fn sanitize_error(message: &str) -> String {
+    let message = URL_CREDENTIALS_PATTERN.replace_all(message, "${1}***@");
+    let message = SECRET_PARAMETER_PATTERN.replace_all(&message, "${1}${2}***");
+    match message.char_indices().nth(MAX_ERROR_LENGTH) {
+        Some((index, _)) => format!("{}...", &message[..index]),
+        None => message.into_owned(),
+    }
+}
+
+/// Max number of characters of the error message in the status.
+const MAX_ERROR_LENGTH: usize = 256;
+
+/// Pattern for the credentials in URLs.
+static URL_CREDENTIALS_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(\b[a-zA-Z][\w+.-]*://)[^\s/@]+@")
+        .expect("fail to create the URL credentials pattern")
+});
+
+/// Pattern for the secret parameters.
+static SECRET_PARAMETER_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)\b(password|passwd|pwd|secret|token|api[_-]?key|access[_-]?key)(\s*[=:]\s*)("[^"]*"|'[^']*'|[^\s;,&]+)"#,
+    )
+    .expect("fail to create the secret parameter pattern")
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{sanitize_error, CircuitBreaker, CircuitState};
+    use crate::error::Error;
+    use std::time::Duration;
+
+    #[test]
+    fn it_admits_a_single_trial_request() {
+        let breaker = CircuitBreaker::new(2, Duration::ZERO);
+        let err = Error::new("connection refused");
+        breaker.record_failure(&err);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure(&err);
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(60));
+        breaker.record_failure(&err);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(breaker.check("test").is_err());
+
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(10));
+        breaker.record_failure(&err);
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(breaker.check("test").is_ok());
+        assert!(breaker.check("test").is_err());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.check("test").is_ok());
+    }
+
+    #[test]
+    fn it_sanitizes_error_messages() {
+        assert_eq!(
+            sanitize_error("fail to connect `postgres://alice:s3cret@db:5432/app`"),
+            "fail to connect `postgres://***@db:5432/app`"
+        );
+        assert_eq!(
+            sanitize_error("invalid config: host=db password=s3cret; token: 'abc'"),
+            "invalid config: host=db password=***; token: ***"
+        );
+        assert_eq!(sanitize_error(&"x".repeat(300)).len(), 259);
+    }
+}
@@ -1,7 +1,23 @@
 use super::ArrowArrayExt;
 use crate::{error::Error, Map, Record};
-use datafusion::{arrow::util, dataframe::DataFrame};
+use apache_avro::{types::Value, Schema as AvroSchema, Writer};
+use datafusion::{
+    arrow::{
+        csv,
+        datatypes::{DataType, Schema, TimeUnit},
+        json, util,
+    },
+    dataframe::DataFrame,
+    parquet::arrow::ArrowWriter,
+};
 use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::{fs, path::Path, sync::Arc};
+
+#[cfg(feature = "accessor")]
+use crate::accessor::GlobalAccessor;
+#[cfg(feature = "orm")]
+use crate::database::Schema as ModelSchema;
 
 /// Executor trait for [`DataFrame`](datafusion::dataframe::DataFrame).
 pub trait DataFrameExecutor {
@@ -22,6 +38,25 @@ pub trait DataFrameExecutor {
 
     /// Executes the `DataFrame` and creates a visual representation of record batches.
     async fn output(self) -> Result<String, Error>;
+
+    /// Executes the `DataFrame` and encodes the results in the format.
+    /// Supported formats: `avro`, `csv`, `ndjson` and `parquet`.
+    async fn encode(self, format: &str) -> Result<Vec<u8>, Error>;
+
+    /// Executes the `DataFrame` and writes the results to a local file.
+    /// The format is determined by the file extension.
+    async fn write_file(self, path: &str) -> Result<(), Error>;
+
+    /// Executes the `DataFrame` and writes the results to a storage service
+    /// via the [`GlobalAccessor`](crate::accessor::GlobalAccessor).
+    /// The format is determined by the file extension.
+    #[cfg(feature = "accessor")]
+    async fn write_accessor(self, accessor: &str, path: &str) -> Result<(), Error>;
+
+    /// Executes the `DataFrame` and inserts the results into the table for the model `M`,
+    /// returning the total number of rows affected.
+    #[cfg(feature = "orm")]
+    async fn insert_into<M: ModelSchema>(self) -> Result<u64, Error>;
 }
 
 impl DataFrameExecutor for DataFrame {
@@ -113,4 +148,202 @@ impl DataFrameExecutor for DataFrame {
         let data = util::pretty::pretty_format_batches(&batches)?;
         Ok(data.to_string())
     }
+
+    async fn encode(self, format: &str) -> Result<Vec<u8>, Error> {
+        let schema = Arc::new(Schema::from(self.schema().clone()));
+        let mut buffer = Vec::new();
+        match format {
+            "avro" => {
+                let avro_schema = parse_avro_schema(&schema)?;
+                let mut writer = Writer::new(&avro_schema, &mut buffer);
+                for record in self.query().await? {
+                    let record = record
+                        .into_iter()
+                        .map(|(field, value)| {
+                            let value = if value == Value::Null {
+                                Value::Union(0, Box::new(value))
+                            } else {
+                                Value::Union(1, Box::new(value))
+                            };
+                            (field, value)
+                        })
+                        .collect();
+                    writer.append(Value::Record(record))?;
+                }
+                writer.flush()?;
+            }
+            "csv" => {
+                let batches = self.collect().await?;
+                let mut writer = csv::Writer::new(&mut buffer);
+                for batch in &batches {
+                    writer.write(batch)?;
+                }
+            }
+            "ndjson" => {
+                let batches = self.collect().await?;
+                let mut writer = json::LineDelimitedWriter::new(&mut buffer);
+                writer.write_batches(&batches)?;
+                writer.finish()?;
+            }
+            "parquet" => {
+                let batches = self.collect().await?;
+                let mut writer = ArrowWriter::try_new(&mut buffer, schema, None)?;
+                for batch in &batches {
+                    writer.write(batch)?;
+                }
+                writer.close()?;
+            }
+            _ => {
+                let message = format!("data format `{format}` is unsupported");
+                return Err(Error::new(message));
+            }
+        }
+        Ok(buffer)
+    }
+
+    async fn write_file(self, path: &str) -> Result<(), Error> {
+        let format = parse_file_format(path)?;
+        let bytes = self.encode(format).await?;
+        fs::write(path, bytes).map_err(Error::from)
+    }
+
+    #[cfg(feature = "accessor")]
+    async fn write_accessor(self, accessor: &str, path: &str) -> Result<(), Error> {
+        let operator = GlobalAccessor::get(accessor)
+            .ok_or_else(|| Error::new(format!("the accessor `{accessor}` does not exist")))?;
+        let format = parse_file_format(path)?;
+        let bytes = self.encode(format).await?;
+        operator.write(path, bytes).await.map_err(Error::from)
+    }
+
+    #[cfg(feature = "orm")]
+    async fn insert_into<M: ModelSchema>(self) -> Result<u64, Error> {
+        let rows = self.query_as::<Map>().await?;
+        M::insert_rows(rows).await.map_err(Error::from)
+    }
+}
+
+/// Parses the file format from the file extension.
+fn parse_file_format(path: &str) -> Result<&'static str, Error> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let format = match extension {
+        "avro" => "avro",
+        "csv" => "csv",
+        "json" | "ndjson" => "ndjson",
+        "parquet" => "parquet",
+        _ => {
+            let message = format!("fail to determine the data format for the file `{path}`");
+            return Err(Error::new(message));
+        }
+    };
+    Ok(format)
+}
+
+/// Parses an Avro schema from the Arrow schema.
+/// All the fields are encoded as a union with `null`.
+fn parse_avro_schema(schema: &Schema) -> Result<AvroSchema, Error> {
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    for field in schema.fields() {
+        let field_name = field.name();
+        let field_type = match field.data_type() {
+            DataType::Boolean => json!("boolean"),
+            DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32 => json!("int"),
+            DataType::Int64 | DataType::UInt64 => json!("long"),
+            DataType::Float32 => json!("float"),
+            DataType::Float64 => json!("double"),
+            DataType::Null | DataType::Utf8 | DataType::LargeUtf8 => json!("string"),
+            DataType::Binary | DataType::LargeBinary => json!("bytes"),
+            DataType::Date32 => json!({ "type": "int", "logicalType": "date" }),
+            DataType::Date64 => json!({ "type": "long", "logicalType": "timestamp-millis" }),
+            DataType::Time32(_) => json!({ "type": "int", "logicalType": "time-millis" }),
+            DataType::Time64(_) => json!({ "type": "long", "logicalType": "time-micros" }),
+            DataType::Timestamp(TimeUnit::Second | TimeUnit::Millisecond, None) => {
+                json!({ "type": "long", "logicalType": "timestamp-millis" })
+            }
+            DataType::Timestamp(TimeUnit::Microsecond | TimeUnit::Nanosecond, None) => {
+                json!({ "type": "long", "logicalType": "timestamp-micros" })
+            }
+            data_type => {
+                let message = format!(
+                    "fail to encode the field `{field_name}` of type `{data_type}` as Avro"
+                );
+                return Err(Error::new(message));
+            }
+        };
+        fields.push(json!({
+            "name": field_name,
+            "type": ["null", field_type],
+            "default": null,
+        }));
+    }
+
+    let schema = json!({
+        "type": "record",
+        "name": "record",
+        "fields": fields,
+    });
+    AvroSchema::parse(&schema).map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataFrameExecutor;
+    use apache_avro::{types::Value, Reader};
+    use datafusion::{
+        arrow::{
+            array::{Int64Array, StringArray},
+            datatypes::{DataType, Field, Schema},
+            record_batch::RecordBatch,
+        },
+        execution::context::SessionContext,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn it_encodes_avro_records() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let schema = Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("name", DataType::Utf8, true),
+            ]));
+            let ids = Int64Array::from(vec![1, 2]);
+            let names = StringArray::from(vec![Some("alice"), None]);
+            let batch = RecordBatch::try_new(schema, vec![Arc::new(ids), Arc::new(names)]).unwrap();
+            let df = SessionContext::new().read_batch(batch).unwrap();
+
+            let bytes = df.encode("avro").await.unwrap();
+            let records = Reader::new(bytes.as_slice())
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let union = |index, value| Value::Union(index, Box::new(value));
+            assert_eq!(
+                records,
+                vec![
+                    Value::Record(vec![
+                        ("id".to_owned(), union(1, Value::Long(1))),
+                        (
+                            "name".to_owned(),
+                            union(1, Value::String("alice".to_owned()))
+                        ),
+                    ]),
+                    Value::Record(vec![
+                        ("id".to_owned(), union(1, Value::Long(2))),
+                        ("name".to_owned(), union(0, Value::Null)),
+                    ]),
+                ]
+            );
+        });
+    }
 }
@@ -0,0 +1,172 @@
+use super::ArrowSchemaExt;
+use crate::{
+    connector::{Connector, GlobalConnector},
+    error::Error,
+    extend::JsonObjectExt,
+    Map,
+};
+use async_trait::async_trait;
+use datafusion::{
+    arrow::{
+        datatypes::{Schema, SchemaRef},
+        record_batch::RecordBatch,
+    },
+    datasource::TableProvider,
+    error::DataFusionError,
+    execution::context::SessionState,
+    logical_expr::{Expr, TableType},
+    physical_plan::{memory::MemoryExec, ExecutionPlan},
+};
+use std::{any::Any, borrow::Cow, sync::Arc};
+
+/// A table provider which executes the query on another data source for each scan.
+pub(super) struct DataSourceProvider {
+    /// Name of the data source.
+    data_source: String,
+    /// Query executed on the data source.
+    query: String,
+    /// Optional parameters of the query.
+    params: Option<Map>,
+    /// Schema of the records.
+    schema: SchemaRef,
+}
+
+impl DataSourceProvider {
+    /// Attempts to create a new instance. If the schema is absent,
+    /// it will be obtained by describing the table in the data source.
+    pub(super) async fn try_new(
+        data_source: &str,
+        query: &str,
+        params: Option<Map>,
+        schema: Option<Schema>,
+        table: Option<&str>,
+    ) -> Result<Self, Error> {
+        let schema = match (schema, table) {
+            (Some(schema), _) => schema,
+            (None, Some(table)) => GlobalConnector::try_get(data_source)?
+                .describe_table(table)
+                .await?
+                .to_arrow_schema(),
+            (None, None) => {
+                let message = format!(
+                    "the `schema` or `table` should be specified for the data source `{data_source}`"
+                );
+                return Err(Error::new(message));
+            }
+        };
+        Ok(Self {
+            data_source: data_source.to_owned(),
+            query: query.to_owned(),
+            params,
+            schema: Arc::new(schema),
+        })
+    }
+}
+
+#[async_trait]
+impl TableProvider for DataSourceProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let data_source = GlobalConnector::try_get(&self.data_source)
+            .map_err(|err| DataFusionError::Execution(err.to_string()))?;
+        let (query, params) = if let Some(limit) = limit {
+            limit_query(
+                data_source.protocol(),
+                &self.query,
+                self.params.as_ref(),
+                limit,
+            )
+        } else {
+            (Cow::Borrowed(self.query.as_str()), self.params.clone())
+        };
+        let mut records = data_source
+            .query(&query, params.as_ref())
+            .await
+            .map_err(|err| DataFusionError::Execution(err.to_string()))?;
+        if let Some(limit) = limit {
+            records.truncate(limit);
+        }
+
+        let columns = self.schema.collect_columns_from_avro_records(&records);
+        let batch = RecordBatch::try_new(self.schema(), columns)?;
+        let exec = MemoryExec::try_new(&[vec![batch]], self.schema(), projection.cloned())?;
+        Ok(Arc::new(exec))
+    }
+}
+
+/// Pushes down the limit into the query. The limit is provided as the `limit` param
+/// if the query contains `${limit}`, otherwise the SQL query is wrapped as a subquery.
+fn limit_query<'a>(
+    protocol: &str,
+    query: &'a str,
+    params: Option<&Map>,
+    limit: usize,
+) -> (Cow<'a, str>, Option<Map>) {
+    let mut params = params.cloned();
+    if query.contains("${limit}") {
+        params.get_or_insert_with(Map::new).upsert("limit", limit);
+        return (Cow::Borrowed(query), params);
+    }
+
+    let query_trimmed = query.trim().trim_end_matches(';');
+    let query = match protocol {
+        "clickhouse" | "duckdb" | "mysql" | "postgres" | "sqlite" => Cow::Owned(format!(
+            "SELECT * FROM ({query_trimmed}) AS limited_records LIMIT {limit};"
+        )),
+        "mssql" => Cow::Owned(format!(
+            "SELECT TOP {limit} * FROM ({query_trimmed}) AS limited_records;"
+        )),
+        _ => Cow::Borrowed(query),
+    };
+    (query, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::limit_query;
+    use crate::{extend::JsonObjectExt, Map};
+
+    #[test]
+    fn it_pushes_down_limits() {
+        let query = "SELECT id, name FROM accounts;";
+        let (limited_query, params) = limit_query("postgres", query, None, 10);
+        assert_eq!(
+            limited_query,
+            "SELECT * FROM (SELECT id, name FROM accounts) AS limited_records LIMIT 10;"
+        );
+        assert!(params.is_none());
+
+        let (limited_query, _) = limit_query("mssql", query, None, 10);
+        assert_eq!(
+            limited_query,
+            "SELECT TOP 10 * FROM (SELECT id, name FROM accounts) AS limited_records;"
+        );
+
+        let (limited_query, _) = limit_query("http", "/accounts", None, 10);
+        assert_eq!(limited_query, "/accounts");
+
+        let mut params = Map::new();
+        params.upsert("status", "active");
+        let query = "/accounts?status=${status}&limit=${limit}";
+        let (limited_query, params) = limit_query("http", query, Some(&params), 10);
+        assert_eq!(limited_query, query);
+        assert_eq!(params.and_then(|params| params.get_u64("limit")), Some(10));
+    }
+}
@@ -0,0 +1,308 @@
+use crate::error::Error;
+use datafusion::{
+    datasource::TableProvider,
+    execution::context::SessionContext,
+    logical_expr::{AggregateUDF, ScalarUDF},
+    scalar::ScalarValue,
+};
+use parking_lot::RwLock;
+use regex::{Captures, Regex};
+use std::{
+    borrow::Cow,
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, LazyLock},
+};
+
+/// A table function which creates a table provider with the arguments.
+pub trait TableFunction: Send + Sync {
+    /// Calls the function with the arguments and returns a table provider.
+    fn call(&self, args: &[ScalarValue]) -> Result<Arc<dyn TableProvider>, Error>;
+}
+
+impl<F> TableFunction for F
+where
+    F: Fn(&[ScalarValue]) -> Result<Arc<dyn TableProvider>, Error> + Send + Sync,
+{
+    #[inline]
+    fn call(&self, args: &[ScalarValue]) -> Result<Arc<dyn TableProvider>, Error> {
+        self(args)
+    }
+}
+
+/// A registry for user-defined functions.
+#[derive(Default)]
+pub(super) struct FunctionRegistry {
+    /// Scalar UDFs.
+    udfs: HashMap<String, ScalarUDF>,
+    /// Aggregate UDAFs.
+    udafs: HashMap<String, AggregateUDF>,
+    /// Table functions.
+    table_functions: HashMap<String, Arc<dyn TableFunction>>,
+    /// Session contexts with the enabled function names.
+    contexts: Vec<(SessionContext, Option<Vec<String>>)>,
+}
+
+impl FunctionRegistry {
+    /// Registers a scalar UDF, which is also registered in the existing session contexts.
+    pub(super) fn register_udf(udf: ScalarUDF) {
+        let mut registry = FUNCTION_REGISTRY.write();
+        for (ctx, names) in registry.contexts.iter() {
+            if is_enabled(names.as_deref(), &udf.name) {
+                ctx.register_udf(udf.clone());
+            }
+        }
+        registry.udfs.insert(udf.name.clone(), udf);
+    }
+
+    /// Registers an aggregate UDAF, which is also registered in the existing session contexts.
+    pub(super) fn register_udaf(udaf: AggregateUDF) {
+        let mut registry = FUNCTION_REGISTRY.write();
+        for (ctx, names) in registry.contexts.iter() {
+            if is_enabled(names.as_deref(), &udaf.name) {
+                ctx.register_udaf(udaf.clone());
+            }
+        }
+        registry.udafs.insert(udaf.name.clone(), udaf);
+    }
+
+    /// Registers a table function.
+    #[inline]
+    pub(super) fn register_table_function(name: &str, function: Arc<dyn TableFunction>) {
+        let mut registry = FUNCTION_REGISTRY.write();
+        registry.table_functions.insert(name.to_owned(), function);
+    }
+
+    /// Gets the table function with the name.
+    #[inline]
+    pub(super) fn get_table_function(name: &str) -> Option<Arc<dyn TableFunction>> {
+        let registry = FUNCTION_REGISTRY.read();
+        registry.table_functions.get(name).cloned()
+    }
+
+    /// Registers the UDFs and UDAFs in the session context, and keeps track of it
+    /// so that the functions registered afterwards are also available.
+    /// If the function names are specified, only those functions will be registered.
+    pub(super) fn register_functions(ctx: &SessionContext, names: Option<&[String]>) {
+        let mut registry = FUNCTION_REGISTRY.write();
+        for (name, udf) in registry.udfs.iter() {
+            if is_enabled(names, name) {
+                ctx.register_udf(udf.clone());
+            }
+        }
+        for (name, udaf) in registry.udafs.iter() {
+            if is_enabled(names, name) {
+                ctx.register_udaf(udaf.clone());
+            }
+        }
+        registry
+            .contexts
+            .push((ctx.clone(), names.map(|names| names.to_vec())));
+    }
+
+    /// Resolves the table function calls such as `FROM series(1, 10)` in the SQL query.
+    /// Each call is replaced with a table registered in the session context,
+    /// whose provider is created by calling the function with the literal arguments.
+    pub(super) fn resolve_table_functions<'a>(
+        ctx: &SessionContext,
+        sql: &'a str,
+        names: Option<&[String]>,
+    ) -> Result<Cow<'a, str>, Error> {
+        if !sql.contains('(') {
+            return Ok(Cow::Borrowed(sql));
+        }
+
+        let mut error = None;
+        let sql = TABLE_FUNCTION_PATTERN.replace_all(sql, |captures: &Captures| {
+            let name = &captures[2];
+            let function = is_enabled(names, name)
+                .then(|| Self::get_table_function(name))
+                .flatten();
+            let Some(function) = function else {
+                return captures[0].to_owned();
+            };
+            let result = parse_args(&captures[3]).and_then(|args| {
+                let table_name = format!("{name}_{:016x}", hash_args(&captures[3]));
+                ctx.register_table(table_name.as_str(), function.call(&args)?)?;
+                Ok(table_name)
+            });
+            match result {
+                Ok(table_name) => format!("{} {table_name}", &captures[1]),
+                Err(err) => {
+                    error.get_or_insert(err);
+                    captures[0].to_owned()
+                }
+            }
+        });
+        match error {
+            Some(err) => Err(err),
+            None => Ok(sql),
+        }
+    }
+}
+
+/// Returns `true` if the function is enabled for the names.
+fn is_enabled(names: Option<&[String]>, name: &str) -> bool {
+    names.map_or(true, |names| names.iter().any(|s| s == name))
+}
+
+/// Returns a hash of the arguments.
+fn hash_args(args: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    args.trim().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Parses the comma-separated SQL literals as the function arguments.
+fn parse_args(args: &str) -> Result<Vec<ScalarValue>, Error> {
+    let mut values = Vec::new();
+    let mut chars = args.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&c) = chars.peek() else {
+            break;
+        };
+        if c == '\'' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some('\'') if chars.next_if_eq(&'\'').is_some() => value.push('\''),
+                    Some('\'') => break,
+                    Some(c) => value.push(c),
+                    None => return Err(Error::new("unterminated string literal")),
+                }
+            }
+            values.push(ScalarValue::Utf8(Some(value)));
+        } else {
+            let mut literal = String::new();
+            while let Some(c) = chars.next_if(|&c| c != ',') {
+                literal.push(c);
+            }
+            let literal = literal.trim();
+            let value = if literal.eq_ignore_ascii_case("null") {
+                ScalarValue::Null
+            } else if literal.eq_ignore_ascii_case("true") {
+                ScalarValue::Boolean(Some(true))
+            } else if literal.eq_ignore_ascii_case("false") {
+                ScalarValue::Boolean(Some(false))
+            } else if let Ok(i) = literal.parse::<i64>() {
+                ScalarValue::Int64(Some(i))
+            } else if let Ok(f) = literal.parse::<f64>() {
+                ScalarValue::Float64(Some(f))
+            } else {
+                let message = format!("invalid argument `{literal}` of the table function");
+                return Err(Error::new(message));
+            };
+            values.push(value);
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some(',') | None => (),
+            Some(c) => {
+                let message = format!("unexpected character `{c}` in the arguments");
+                return Err(Error::new(message));
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Pattern for the table function calls in the `FROM` and `JOIN` clauses.
+static TABLE_FUNCTION_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(FROM|JOIN)\s+([a-zA-Z_]\w*)\s*\(((?:[^()']|'(?:[^']|'')*')*)\)")
+        .expect("fail to create the table function pattern")
+});
+
+/// Global function registry.
+static FUNCTION_REGISTRY: LazyLock<RwLock<FunctionRegistry>> =
+    LazyLock::new(|| RwLock::new(FunctionRegistry::default()));
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, FunctionRegistry};
+    use crate::error::Error;
+    use datafusion::{
+        arrow::{
+            array::Int64Array,
+            datatypes::{DataType, Field, Schema},
+            record_batch::RecordBatch,
+        },
+        datasource::{MemTable, TableProvider},
+        execution::context::SessionContext,
+        logical_expr::{create_udf, ColumnarValue, Volatility},
+        scalar::ScalarValue,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn it_parses_args() {
+        let args = parse_args(" 'it''s', 10, -1.5, true, NULL ").unwrap();
+        assert_eq!(
+            args,
+            vec![
+                ScalarValue::Utf8(Some("it's".to_owned())),
+                ScalarValue::Int64(Some(10)),
+                ScalarValue::Float64(Some(-1.5)),
+                ScalarValue::Boolean(Some(true)),
+                ScalarValue::Null,
+            ]
+        );
+        assert!(parse_args("").unwrap().is_empty());
+        assert!(parse_args("'unterminated").is_err());
+        assert!(parse_args("column_name").is_err());
+    }
+
+    #[test]
+    fn it_registers_functions_in_existing_contexts() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let ctx = SessionContext::new();
+            FunctionRegistry::register_functions(&ctx, None);
+
+            let udf = create_udf(
+                "test_identity",
+                vec![DataType::Int64],
+                Arc::new(DataType::Int64),
+                Volatility::Immutable,
+                Arc::new(|args: &[ColumnarValue]| Ok(args[0].clone())),
+            );
+            FunctionRegistry::register_udf(udf);
+            FunctionRegistry::register_table_function(
+                "test_series",
+                Arc::new(|args: &[ScalarValue]| {
+                    let (start, end) = match args {
+                        [ScalarValue::Int64(Some(start)), ScalarValue::Int64(Some(end))] => {
+                            (*start, *end)
+                        }
+                        _ => return Err(Error::new("invalid arguments")),
+                    };
+                    let schema = Arc::new(Schema::new(vec![Field::new(
+                        "value",
+                        DataType::Int64,
+                        false,
+                    )]));
+                    let array = Int64Array::from_iter_values(start..=end);
+                    let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(array)])?;
+                    let table = MemTable::try_new(schema, vec![vec![batch]])?;
+                    Ok(Arc::new(table) as Arc<dyn TableProvider>)
+                }),
+            );
+
+            let sql = "SELECT test_identity(value) AS value FROM test_series(1, 5) AS s";
+            let sql = FunctionRegistry::resolve_table_functions(&ctx, sql, None).unwrap();
+            assert!(!sql.contains("test_series(1, 5)"));
+
+            let batches = ctx.sql(&sql).await.unwrap().collect().await.unwrap();
+            let num_rows = batches.iter().map(|batch| batch.num_rows()).sum::<usize>();
+            assert_eq!(num_rows, 5);
+
+            let names = ["test_identity".to_owned()];
+            let sql = "SELECT * FROM test_series(1, 5)";
+            let sql = FunctionRegistry::resolve_table_functions(&ctx, sql, Some(&names)).unwrap();
+            assert_eq!(sql, "SELECT * FROM test_series(1, 5)");
+        });
+    }
+}
@@ -1,50 +1,160 @@
 //! Utilities for DataFusion.
 
-use super::{Connector, DataSource, DataSourceConnector::Arrow};
-use crate::{application::http_client, error::Error, extend::TomlTableExt, format, Map, Record};
+use super::{ColumnInfo, Connector, DataSource, DataSourceConnector::Arrow, TableInfo};
+use crate::{
+    application::http_client, channel::CloudEvent, error::Error, extend::TomlTableExt, format, Map,
+    Record,
+};
 use datafusion::{
-    arrow::{datatypes::Schema, record_batch::RecordBatch},
+    arrow::{
+        datatypes::{DataType, Schema},
+        record_batch::RecordBatch,
+    },
     dataframe::DataFrame,
-    datasource::file_format::file_type::FileCompressionType,
+    datasource::{file_format::file_type::FileCompressionType, MemTable, TableProvider},
     execution::{
         context::{SessionConfig, SessionContext, SessionState},
         options::{AvroReadOptions, CsvReadOptions, NdJsonReadOptions, ParquetReadOptions},
         runtime_env::RuntimeEnv,
     },
+    logical_expr::{AggregateUDF, Expr, ScalarUDF},
+    scalar::ScalarValue,
     variable::VarType,
 };
+use parking_lot::RwLock;
 use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
 use std::{
+    collections::HashMap,
     fs::File,
     io::Write,
     path::PathBuf,
-    sync::{Arc, LazyLock, OnceLock},
+    sync::{Arc, LazyLock},
+    time::Duration,
 };
+use tokio::sync::OnceCell;
 use toml::value::{Array, Table};
 
+#[cfg(feature = "accessor")]
+use crate::accessor::GlobalAccessor;
+#[cfg(feature = "accessor")]
+use url::Url;
+
 mod arrow_array;
 mod arrow_field;
 mod arrow_schema;
 mod data_frame;
+mod data_source_provider;
+mod function_registry;
+#[cfg(feature = "accessor")]
+mod opendal_store;
 mod scalar_provider;
 mod scalar_value;
+mod stream_table;
 
 pub use data_frame::DataFrameExecutor;
+pub use function_registry::TableFunction;
+pub use stream_table::StreamTable;
 
 use arrow_array::ArrowArrayExt;
 use arrow_field::ArrowFieldExt;
 use arrow_schema::ArrowSchemaExt;
+use data_source_provider::DataSourceProvider;
+use function_registry::FunctionRegistry;
+#[cfg(feature = "accessor")]
+use opendal_store::OpendalStore;
 use scalar_provider::ScalarValueProvider;
 use scalar_value::ScalarValueExt;
 
 /// A connector for Apache Arrow.
+///
+/// Besides files, other data sources can be registered as tables
+/// with the `data-source` type, so that one SQL statement can query across data sources:
+///
+/// ```toml
+/// [[connector.tables]]
+/// type = "data-source"
+/// name = "accounts"
+/// data-source = "postgres"
+/// query = "SELECT id, name, status FROM accounts;"
+/// table = "accounts"
+/// ```
+///
+/// The query is executed on the data source each time the table is scanned,
+/// and the limit is pushed down into the query. The schema is taken from the `schema` field,
+/// or obtained by describing the `table` in the data source.
+///
+/// Tables can also be read from the storage services of `GlobalAccessor` without local copies.
+/// The `path` can be a file, a directory or a glob pattern, and directories with
+/// Hive-style partitions such as `year=2023/month=03/` are supported via `partition-cols`:
+///
+/// ```toml
+/// [[connector.tables]]
+/// type = "parquet"
+/// name = "events"
+/// accessor = "s3"
+/// path = "events/"
+/// partition-cols = ["year", "month"]
+/// ```
+///
+/// User-defined functions registered by [`ArrowConnector::register_udf`],
+/// [`ArrowConnector::register_udaf`] and [`ArrowConnector::register_table_function`]
+/// are available in all the session contexts, including the ones created before
+/// the registration. The `functions` field can be used to enable only a subset of them
+/// for the connector, and a table function can be called in the `FROM` or `JOIN` clause
+/// with the literal arguments:
+///
+/// ```rust,ignore
+/// ArrowConnector::register_table_function("series", |args: &[ScalarValue]| {
+///     // Creates a table provider with the arguments.
+/// });
+/// let records = connector
+///     .query("SELECT * FROM series(1, 10) WHERE value > 5;", None)
+///     .await?;
+/// ```
+///
+/// It can also be registered as a table with the `function` type:
+///
+/// ```toml
+/// [[connector]]
+/// type = "arrow"
+/// name = "analytics"
+/// functions = ["series"]
+///
+/// [[connector.tables]]
+/// type = "function"
+/// name = "numbers"
+/// function = "series"
+/// args = [1, 10]
+/// ```
+///
+/// A table with the `stream` type is an in-memory append-only table. Records can be pushed
+/// to it continuously via [`ArrowConnector::append_records`] or
+/// [`ArrowConnector::append_cloud_event`], and SQL queries always see the latest data.
+/// The memory is bounded by `max-rows` (defaults to `100000`) and an optional `retention`:
+///
+/// ```toml
+/// [[connector.tables]]
+/// type = "stream"
+/// name = "metrics"
+/// max-rows = 500000
+/// retention = "1h"
+///
+/// [connector.tables.schema]
+/// host = "string"
+/// cpu_usage = "double"
+/// ```
 pub struct ArrowConnector {
     /// Session context.
-    context: OnceLock<SessionContext>,
+    context: OnceCell<SessionContext>,
     /// Root dir.
     root: PathBuf,
     /// Tables.
     tables: Option<Array>,
+    /// Enabled user-defined functions.
+    functions: Option<Vec<String>>,
+    /// Stream tables.
+    stream_tables: RwLock<HashMap<String, Arc<StreamTable>>>,
     /// System variables.
     system_variables: ScalarValueProvider,
     /// User-defined variables.
@@ -56,9 +166,11 @@ impl ArrowConnector {
     #[inline]
     pub fn new() -> Self {
         Self {
-            context: OnceLock::new(),
+            context: OnceCell::new(),
             root: PathBuf::from("./data/"),
             tables: None,
+            functions: None,
+            stream_tables: RwLock::new(HashMap::new()),
             system_variables: ScalarValueProvider::default(),
             user_defined_variables: ScalarValueProvider::default(),
         }
@@ -72,14 +184,53 @@ impl ArrowConnector {
             system_variables.read_toml_table(variables);
         }
         Self {
-            context: OnceLock::new(),
+            context: OnceCell::new(),
             root: PathBuf::from(root),
             tables: config.get_array("tables").cloned(),
+            functions: config.get_array("functions").map(|functions| {
+                functions
+                    .iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_owned()))
+                    .collect()
+            }),
+            stream_tables: RwLock::new(HashMap::new()),
             system_variables,
             user_defined_variables: ScalarValueProvider::default(),
         }
     }
 
+    /// Registers a scalar UDF for all the session contexts.
+    #[inline]
+    pub fn register_udf(udf: ScalarUDF) {
+        FunctionRegistry::register_udf(udf);
+    }
+
+    /// Registers an aggregate UDAF for all the session contexts.
+    #[inline]
+    pub fn register_udaf(udaf: AggregateUDF) {
+        FunctionRegistry::register_udaf(udaf);
+    }
+
+    /// Registers a table function with the name.
+    #[inline]
+    pub fn register_table_function(name: &str, function: impl TableFunction + 'static) {
+        FunctionRegistry::register_table_function(name, Arc::new(function));
+    }
+
+    /// Attempts to create a [`DataFrame`](datafusion::dataframe::DataFrame)
+    /// from calling the table function with the arguments.
+    pub async fn read_table_function(
+        &self,
+        name: &str,
+        args: &[ScalarValue],
+    ) -> Result<DataFrame, Error> {
+        let ctx = self.try_get_session_context().await?;
+        let function = FunctionRegistry::get_table_function(name)
+            .ok_or_else(|| Error::new(format!("table function `{name}` does not exist")))?;
+        let provider = function.call(args)?;
+        ctx.read_table(provider).map_err(Error::from)
+    }
+
     /// Binds system variables.
     pub async fn bind_system_variables(&mut self, variables: &Table) {
         self.system_variables.read_toml_table(variables);
@@ -99,13 +250,19 @@ impl ArrowConnector {
         }
     }
 
-    /// Attempts to get the session context.
+    /// Attempts to get the session context. It is initialized only once
+    /// even if there are concurrent calls.
     pub async fn try_get_session_context(&self) -> Result<&SessionContext, Error> {
-        if let Some(ctx) = self.context.get() {
-            return Ok(ctx);
-        };
+        self.context
+            .get_or_try_init(|| self.init_session_context())
+            .await
+    }
 
+    /// Initializes the session context and the stream tables.
+    async fn init_session_context(&self) -> Result<SessionContext, Error> {
         let ctx = SessionContext::with_state(SHARED_SESSION_STATE.clone());
+        let mut stream_tables = HashMap::new();
+        FunctionRegistry::register_functions(&ctx, self.functions.as_deref());
         if let Some(tables) = self.tables.as_deref() {
             let root = &self.root;
             for table in tables.iter().filter_map(|v| v.as_table()) {
@@ -115,7 +272,95 @@ impl ArrowConnector {
                 let table_name = table
                     .get_str("name")
                     .ok_or_else(|| Error::new("the `name` field should be a str"))?;
-                let table_path = if let Some(url) = table.get_str("url") {
+                let table_schema = if let Some(schema) = table.get_table("schema") {
+                    Some(Schema::try_from_toml_table(schema)?)
+                } else {
+                    None
+                };
+                if data_type == "function" {
+                    let name = table
+                        .get_str("function")
+                        .ok_or_else(|| Error::new("the `function` field should be a str"))?;
+                    let function = FunctionRegistry::get_table_function(name).ok_or_else(|| {
+                        Error::new(format!("table function `{name}` does not exist"))
+                    })?;
+                    let args = table
+                        .get_array("args")
+                        .map(|args| {
+                            args.iter()
+                                .map(|arg| ScalarValue::from_toml_value(arg.to_owned()))
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default();
+                    ctx.register_table(table_name, function.call(&args)?)?;
+                    continue;
+                }
+                if data_type == "stream" {
+                    let schema = table_schema.ok_or_else(|| {
+                        Error::new(format!("the schema for the table `{table_name}` is absent"))
+                    })?;
+                    let max_rows = table.get_usize("max-rows").unwrap_or(100_000);
+                    let retention = table.get_duration("retention");
+                    let stream_table = Arc::new(StreamTable::new(schema, max_rows, retention));
+                    ctx.register_table(table_name, stream_table.clone())?;
+                    stream_tables.insert(table_name.to_owned(), stream_table);
+                    continue;
+                }
+                if data_type == "data-source" {
+                    let data_source = table.get_str("data-source").ok_or_else(|| {
+                        Error::new("the `data-source` field should be a str")
+                    })?;
+                    let query = table
+                        .get_str("query")
+                        .ok_or_else(|| Error::new("the `query` field should be a str"))?;
+                    let params = match table.get("params").map(serde_json::to_value) {
+                        Some(Ok(JsonValue::Object(map))) => Some(map),
+                        _ => None,
+                    };
+                    let source_table = table.get_str("table");
+                    let provider = DataSourceProvider::try_new(
+                        data_source,
+                        query,
+                        params,
+                        table_schema,
+                        source_table,
+                    )
+                    .await?;
+                    ctx.register_table(table_name, Arc::new(provider))?;
+                    continue;
+                }
+
+                let table_partition_cols = table
+                    .get_array("partition-cols")
+                    .map(|cols| {
+                        cols.iter()
+                            .filter_map(|col| col.as_str())
+                            .map(|col| (col.to_owned(), DataType::Utf8))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let table_path = if let Some(accessor) = table.get_str("accessor") {
+                    #[cfg(feature = "accessor")]
+                    {
+                        let operator = GlobalAccessor::get(accessor).ok_or_else(|| {
+                            Error::new(format!("the accessor `{accessor}` does not exist"))
+                        })?;
+                        let store = OpendalStore::new(accessor, operator.clone());
+                        let store_url = Url::parse(&format!("opendal://{accessor}"))?;
+                        ctx.runtime_env().register_object_store(&store_url, Arc::new(store));
+
+                        let path = table.get_str("path").unwrap_or_default();
+                        format!("{store_url}/{}", path.trim_start_matches('/'))
+                    }
+                    #[cfg(not(feature = "accessor"))]
+                    {
+                        let message = format!(
+                            "the `accessor` feature is required to read the table `{table_name}` \
+                                via the accessor `{accessor}`"
+                        );
+                        return Err(Error::new(message));
+                    }
+                } else if let Some(url) = table.get_str("url") {
                     let table_file_path = root.join(format!("{table_name}.{data_type}"));
                     let mut table_file = File::create(&table_file_path)?;
                     let mut res = http_client::request_builder(url, None)?.send().await?;
@@ -131,17 +376,13 @@ impl ArrowConnector {
                             Error::new(format!("the path for the table `{table_name}` is absent"))
                         })?
                 };
-                let table_schema = if let Some(schema) = table.get_table("schema") {
-                    Some(Schema::try_from_toml_table(schema)?)
-                } else {
-                    None
-                };
                 match data_type {
                     "avro" => {
                         let mut options = AvroReadOptions::default();
                         if table_schema.is_some() {
                             options.schema = table_schema.as_ref();
                         }
+                        options.table_partition_cols = table_partition_cols;
                         if let Some(infinite) = table.get_bool("infinite") {
                             options.infinite = infinite;
                         }
@@ -152,6 +393,7 @@ impl ArrowConnector {
                         if table_schema.is_some() {
                             options.schema = table_schema.as_ref();
                         }
+                        options.table_partition_cols = table_partition_cols;
                         if let Some(max_records) = table.get_usize("max-records") {
                             options.schema_infer_max_records = max_records;
                         }
@@ -173,6 +415,7 @@ impl ArrowConnector {
                         if table_schema.is_some() {
                             options.schema = table_schema.as_ref();
                         }
+                        options.table_partition_cols = table_partition_cols;
                         if let Some(max_records) = table.get_usize("max-records") {
                             options.schema_infer_max_records = max_records;
                         }
@@ -191,6 +434,7 @@ impl ArrowConnector {
                     }
                     "parquet" => {
                         let mut options = ParquetReadOptions::default();
+                        options.table_partition_cols = table_partition_cols;
                         if let Some(parquet_pruning) = table.get_bool("parquet-pruning") {
                             options.parquet_pruning = Some(parquet_pruning);
                         }
@@ -209,7 +453,8 @@ impl ArrowConnector {
         }
         ctx.register_variable(VarType::System, Arc::new(self.system_variables.clone()));
         ctx.refresh_catalogs().await?;
-        self.context.get_or_try_init(|| Ok(ctx))
+        *self.stream_tables.write() = stream_tables;
+        Ok(ctx)
     }
 
     /// Attempts to create a [`DateFrame`](datafusion::dataframe::DataFrame)
@@ -226,6 +471,60 @@ impl ArrowConnector {
         let batch = RecordBatch::try_new(Arc::new(schema), columns)?;
         ctx.read_batch(batch).map_err(Error::from)
     }
+
+    /// Attempts to get the stream table with the name.
+    pub async fn try_get_stream_table(&self, name: &str) -> Result<Arc<StreamTable>, Error> {
+        self.try_get_session_context().await?;
+        self.stream_tables
+            .read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::new(format!("stream table `{name}` does not exist")))
+    }
+
+    /// Appends the Avro records to the stream table and returns the number of rows in the table.
+    pub async fn append_records(&self, table: &str, records: &[Record]) -> Result<usize, Error> {
+        self.try_get_stream_table(table).await?.append(records)
+    }
+
+    /// Appends the data of a cloud event to the stream table
+    /// and returns the number of rows in the table.
+    pub async fn append_cloud_event(
+        &self,
+        table: &str,
+        event: &CloudEvent,
+    ) -> Result<usize, Error> {
+        self.try_get_stream_table(table)
+            .await?
+            .append_cloud_event(event)
+    }
+
+    /// Attempts to create a [`DateFrame`](datafusion::dataframe::DataFrame)
+    /// from the records appended to the stream table within the window.
+    pub async fn read_stream_window(
+        &self,
+        table: &str,
+        window: Duration,
+    ) -> Result<DataFrame, Error> {
+        let ctx = self.try_get_session_context().await?;
+        let stream_table = self.try_get_stream_table(table).await?;
+        let batches = stream_table.snapshot(Some(window));
+        let mem_table = MemTable::try_new(stream_table.schema(), vec![batches])?;
+        ctx.read_table(Arc::new(mem_table)).map_err(Error::from)
+    }
+
+    /// Attempts to create a [`DateFrame`](datafusion::dataframe::DataFrame)
+    /// from aggregating the records appended to the stream table within the window.
+    pub async fn aggregate_stream_window(
+        &self,
+        table: &str,
+        window: Duration,
+        group_expr: Vec<Expr>,
+        aggr_expr: Vec<Expr>,
+    ) -> Result<DataFrame, Error> {
+        let df = self.read_stream_window(table, window).await?;
+        df.aggregate(group_expr, aggr_expr).map_err(Error::from)
+    }
 }
 
 impl Default for ArrowConnector {
@@ -244,9 +543,15 @@ impl Connector for ArrowConnector {
         Ok(data_source)
     }
 
+    async fn check_availability(&self) -> Result<(), Error> {
+        self.try_get_session_context().await?;
+        Ok(())
+    }
+
     async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
         let ctx = self.try_get_session_context().await?;
         let sql = format::format_query(query, params);
+        let sql = FunctionRegistry::resolve_table_functions(ctx, &sql, self.functions.as_deref())?;
         let df = ctx.sql(&sql).await?;
         df.execute().await
     }
@@ -254,6 +559,7 @@ impl Connector for ArrowConnector {
     async fn query(&self, query: &str, params: Option<&Map>) -> Result<Vec<Record>, Error> {
         let ctx = self.try_get_session_context().await?;
         let sql = format::format_query(query, params);
+        let sql = FunctionRegistry::resolve_table_functions(ctx, &sql, self.functions.as_deref())?;
         let df = ctx.sql(&sql).await?;
         df.query().await
     }
@@ -265,6 +571,7 @@ impl Connector for ArrowConnector {
     ) -> Result<Vec<T>, Error> {
         let ctx = self.try_get_session_context().await?;
         let sql = format::format_query(query, params);
+        let sql = FunctionRegistry::resolve_table_functions(ctx, &sql, self.functions.as_deref())?;
         let df = ctx.sql(&sql).await?;
         df.query_as().await
     }
@@ -272,6 +579,7 @@ impl Connector for ArrowConnector {
     async fn query_one(&self, query: &str, params: Option<&Map>) -> Result<Option<Record>, Error> {
         let ctx = self.try_get_session_context().await?;
         let sql = format::format_query(query, params);
+        let sql = FunctionRegistry::resolve_table_functions(ctx, &sql, self.functions.as_deref())?;
         let df = ctx.sql(&sql).await?;
         df.query_one().await
     }
@@ -283,9 +591,41 @@ impl Connector for ArrowConnector {
     ) -> Result<Option<T>, Error> {
         let ctx = self.try_get_session_context().await?;
         let sql = format::format_query(query, params);
+        let sql = FunctionRegistry::resolve_table_functions(ctx, &sql, self.functions.as_deref())?;
         let df = ctx.sql(&sql).await?;
         df.query_one_as().await
     }
+
+    async fn list_tables(&self) -> Result<Vec<String>, Error> {
+        let ctx = self.try_get_session_context().await?;
+        let mut tables = Vec::new();
+        for catalog_name in ctx.catalog_names() {
+            if let Some(catalog) = ctx.catalog(&catalog_name) {
+                for schema_name in catalog.schema_names() {
+                    if let Some(schema) = catalog.schema(&schema_name) {
+                        tables.extend(schema.table_names());
+                    }
+                }
+            }
+        }
+        tables.sort();
+        Ok(tables)
+    }
+
+    async fn describe_table(&self, table: &str) -> Result<TableInfo, Error> {
+        let ctx = self.try_get_session_context().await?;
+        let provider = ctx.table_provider(table).await?;
+        let columns = provider
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| {
+                let type_name = field.data_type().to_string();
+                ColumnInfo::new(field.name(), type_name, field.is_nullable(), false)
+            })
+            .collect();
+        Ok(TableInfo::new(table, columns))
+    }
 }
 
 /// Shared session state for Arrow.
@@ -0,0 +1,243 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use futures::{stream::BoxStream, AsyncReadExt, StreamExt, TryStreamExt};
+use object_store::{
+    path::Path, GetResult, ListResult, MultipartId, ObjectMeta, ObjectStore, Result,
+};
+use opendal::{ErrorKind, Metadata, Operator};
+use std::{collections::BTreeSet, fmt, io, ops::Range};
+use tokio::io::AsyncWrite;
+
+/// Size of the buffer for reading the objects as streams.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// An object store backed by the [`Operator`](opendal::Operator) of `GlobalAccessor`.
+#[derive(Debug, Clone)]
+pub(super) struct OpendalStore {
+    /// Name of the accessor.
+    name: String,
+    /// Operator.
+    operator: Operator,
+}
+
+impl OpendalStore {
+    /// Creates a new instance.
+    #[inline]
+    pub(super) fn new(name: impl Into<String>, operator: Operator) -> Self {
+        Self {
+            name: name.into(),
+            operator,
+        }
+    }
+
+    /// Fetches the object metadata for the location.
+    async fn stat(&self, location: &str) -> Result<ObjectMeta> {
+        let metadata = self
+            .operator
+            .stat(location)
+            .await
+            .map_err(|err| convert_error(location, err))?;
+        Ok(parse_object_meta(location, &metadata))
+    }
+}
+
+impl fmt::Display for OpendalStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OpendalStore({})", self.name)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for OpendalStore {
+    async fn put(&self, location: &Path, bytes: Bytes) -> Result<()> {
+        let path = location.as_ref();
+        self.operator
+            .write(path, bytes)
+            .await
+            .map_err(|err| convert_error(path, err))
+    }
+
+    async fn put_multipart(
+        &self,
+        _location: &Path,
+    ) -> Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn abort_multipart(&self, _location: &Path, _multipart_id: &MultipartId) -> Result<()> {
+        Err(object_store::Error::NotImplemented)
+    }
+
+    async fn get(&self, location: &Path) -> Result<GetResult> {
+        let path = location.as_ref();
+        let reader = self
+            .operator
+            .reader(path)
+            .await
+            .map_err(|err| convert_error(path, err))?;
+        let stream = futures::stream::try_unfold(reader, |mut reader| async move {
+            let mut buffer = vec![0; READ_BUFFER_SIZE];
+            let num_bytes = reader.read(&mut buffer).await.map_err(convert_io_error)?;
+            if num_bytes == 0 {
+                Ok(None)
+            } else {
+                buffer.truncate(num_bytes);
+                Ok(Some((Bytes::from(buffer), reader)))
+            }
+        });
+        Ok(GetResult::Stream(stream.boxed()))
+    }
+
+    async fn get_range(&self, location: &Path, range: Range<usize>) -> Result<Bytes> {
+        let path = location.as_ref();
+        let bytes = self
+            .operator
+            .range_read(path, range.start as u64..range.end as u64)
+            .await
+            .map_err(|err| convert_error(path, err))?;
+        Ok(Bytes::from(bytes))
+    }
+
+    async fn head(&self, location: &Path) -> Result<ObjectMeta> {
+        self.stat(location.as_ref()).await
+    }
+
+    async fn delete(&self, location: &Path) -> Result<()> {
+        let path = location.as_ref();
+        self.operator
+            .delete(path)
+            .await
+            .map_err(|err| convert_error(path, err))
+    }
+
+    async fn list(&self, prefix: Option<&Path>) -> Result<BoxStream<'_, Result<ObjectMeta>>> {
+        let path = prefix.map(|prefix| format!("{prefix}/")).unwrap_or_default();
+        let lister = self
+            .operator
+            .scan(&path)
+            .await
+            .map_err(|err| convert_error(&path, err))?;
+        let stream = lister
+            .map_err(|err| convert_error("", err))
+            .try_filter(|entry| futures::future::ready(!entry.path().ends_with('/')))
+            .and_then(move |entry| async move { self.stat(entry.path()).await });
+        Ok(stream.boxed())
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        let path = prefix.map(|prefix| format!("{prefix}/")).unwrap_or_default();
+        let mut lister = self
+            .operator
+            .list(&path)
+            .await
+            .map_err(|err| convert_error(&path, err))?;
+        let mut common_prefixes = BTreeSet::new();
+        let mut objects = Vec::new();
+        while let Some(entry) = lister
+            .try_next()
+            .await
+            .map_err(|err| convert_error(&path, err))?
+        {
+            let entry_path = entry.path();
+            if let Some(dir) = entry_path.strip_suffix('/') {
+                common_prefixes.insert(Path::from(dir));
+            } else {
+                objects.push(self.stat(entry_path).await?);
+            }
+        }
+        Ok(ListResult {
+            common_prefixes: common_prefixes.into_iter().collect(),
+            objects,
+        })
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<()> {
+        let bytes = self.get_range(from, 0..self.head(from).await?.size).await?;
+        self.put(to, bytes).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> Result<()> {
+        match self.head(to).await {
+            Ok(_) => Err(object_store::Error::AlreadyExists {
+                path: to.to_string(),
+                source: "the destination already exists".into(),
+            }),
+            Err(object_store::Error::NotFound { .. }) => self.copy(from, to).await,
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Parses the object metadata.
+fn parse_object_meta(path: &str, metadata: &Metadata) -> ObjectMeta {
+    let last_modified = metadata
+        .last_modified()
+        .and_then(|dt| Utc.timestamp_opt(dt.unix_timestamp(), 0).single())
+        .unwrap_or_default();
+    ObjectMeta {
+        location: Path::from(path),
+        last_modified,
+        size: metadata.content_length().try_into().unwrap_or_default(),
+    }
+}
+
+/// Converts an OpenDAL error into an object store error.
+fn convert_error(path: &str, err: opendal::Error) -> object_store::Error {
+    if err.kind() == ErrorKind::NotFound {
+        object_store::Error::NotFound {
+            path: path.to_owned(),
+            source: Box::new(err),
+        }
+    } else {
+        object_store::Error::Generic {
+            store: "opendal",
+            source: Box::new(err),
+        }
+    }
+}
+
+/// Converts an IO error into an object store error.
+fn convert_io_error(err: io::Error) -> object_store::Error {
+    object_store::Error::Generic {
+        store: "opendal",
+        source: Box::new(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OpendalStore, READ_BUFFER_SIZE};
+    use futures::TryStreamExt;
+    use object_store::{path::Path, GetResult, ObjectStore};
+    use opendal::{services::Memory, Operator};
+
+    #[test]
+    fn it_gets_objects_as_streams() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let operator = Operator::new(Memory::default()).unwrap().finish();
+            let store = OpendalStore::new("memory", operator);
+            let location = Path::from("data/large.bin");
+            let data = (0..READ_BUFFER_SIZE * 2 + 10)
+                .map(|i| i as u8)
+                .collect::<Vec<_>>();
+            store.put(&location, data.clone().into()).await.unwrap();
+
+            let GetResult::Stream(stream) = store.get(&location).await.unwrap() else {
+                panic!("the object should be read as a stream");
+            };
+            let chunks = stream.try_collect::<Vec<_>>().await.unwrap();
+            assert!(chunks.len() > 1);
+            assert_eq!(chunks.concat(), data);
+
+            let location = Path::from("data/missing.bin");
+            assert!(matches!(
+                store.get(&location).await,
+                Err(object_store::Error::NotFound { .. })
+            ));
+        });
+    }
+}
@@ -0,0 +1,268 @@
+use super::ArrowSchemaExt;
+use crate::{channel::CloudEvent, error::Error, extend::JsonObjectExt, Record};
+use async_trait::async_trait;
+use datafusion::{
+    arrow::{
+        datatypes::{Schema, SchemaRef},
+        record_batch::RecordBatch,
+    },
+    datasource::TableProvider,
+    error::DataFusionError,
+    execution::context::SessionState,
+    logical_expr::{Expr, TableType},
+    physical_plan::{memory::MemoryExec, ExecutionPlan},
+};
+use parking_lot::RwLock;
+use serde_json::Value;
+use std::{
+    any::Any,
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// An in-memory append-only table which records can be pushed to continuously.
+///
+/// The memory is bounded by the maximum number of rows and an optional retention period.
+/// When the limits are exceeded, the oldest rows will be evicted.
+pub struct StreamTable {
+    /// Schema of the records.
+    schema: SchemaRef,
+    /// Maximum number of rows.
+    max_rows: usize,
+    /// Retention period of the rows.
+    retention: Option<Duration>,
+    /// Buffered record batches.
+    buffer: RwLock<StreamBuffer>,
+}
+
+/// Record batches with the time when they were appended.
+#[derive(Default)]
+struct StreamBuffer {
+    /// Record batches.
+    batches: VecDeque<(Instant, RecordBatch)>,
+    /// Total number of rows.
+    num_rows: usize,
+}
+
+impl StreamTable {
+    /// Creates a new instance.
+    #[inline]
+    pub(super) fn new(schema: Schema, max_rows: usize, retention: Option<Duration>) -> Self {
+        Self {
+            schema: Arc::new(schema),
+            max_rows: max_rows.max(1),
+            retention,
+            buffer: RwLock::new(StreamBuffer::default()),
+        }
+    }
+
+    /// Appends the Avro records and returns the number of rows in the table.
+    /// Fields absent from the schema are ignored.
+    pub fn append(&self, records: &[Record]) -> Result<usize, Error> {
+        if records.is_empty() {
+            return Ok(self.num_rows());
+        }
+
+        let columns = self.schema.collect_columns_from_avro_records(records);
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+        let mut buffer = self.buffer.write();
+        buffer.num_rows += batch.num_rows();
+        buffer.batches.push_back((Instant::now(), batch));
+        self.evict(&mut buffer);
+        Ok(buffer.num_rows)
+    }
+
+    /// Appends the data of a cloud event, which should be an object or an array of objects,
+    /// and returns the number of rows in the table.
+    pub fn append_cloud_event(&self, event: &CloudEvent) -> Result<usize, Error> {
+        let records = match event.data() {
+            Value::Object(data) => vec![data.clone().into_avro_record()],
+            Value::Array(data) => data
+                .iter()
+                .filter_map(|v| v.as_object())
+                .map(|data| data.clone().into_avro_record())
+                .collect(),
+            _ => {
+                let message = format!("data of the cloud event `{}` is invalid", event.id());
+                return Err(Error::new(message));
+            }
+        };
+        self.append(&records)
+    }
+
+    /// Returns the number of rows in the table.
+    #[inline]
+    pub fn num_rows(&self) -> usize {
+        self.buffer.read().num_rows
+    }
+
+    /// Removes all the rows in the table.
+    #[inline]
+    pub fn clear(&self) {
+        let mut buffer = self.buffer.write();
+        buffer.batches.clear();
+        buffer.num_rows = 0;
+    }
+
+    /// Returns the record batches appended within the window.
+    /// If the window is absent, all the record batches will be returned.
+    pub fn snapshot(&self, window: Option<Duration>) -> Vec<RecordBatch> {
+        let mut buffer = self.buffer.write();
+        self.evict(&mut buffer);
+        buffer
+            .batches
+            .iter()
+            .filter(|(appended_at, _)| window.map_or(true, |w| appended_at.elapsed() <= w))
+            .map(|(_, batch)| batch.clone())
+            .collect()
+    }
+
+    /// Evicts expired rows and the oldest rows exceeding the limit.
+    fn evict(&self, buffer: &mut StreamBuffer) {
+        if let Some(retention) = self.retention {
+            while let Some((appended_at, _)) = buffer.batches.front()
+                && appended_at.elapsed() > retention
+            {
+                if let Some((_, batch)) = buffer.batches.pop_front() {
+                    buffer.num_rows -= batch.num_rows();
+                }
+            }
+        }
+        while buffer.num_rows > self.max_rows {
+            let Some((appended_at, batch)) = buffer.batches.pop_front() else {
+                break;
+            };
+            let excess = buffer.num_rows - self.max_rows;
+            let batch_rows = batch.num_rows();
+            if batch_rows > excess {
+                let batch = batch.slice(excess, batch_rows - excess);
+                buffer.batches.push_front((appended_at, batch));
+                buffer.num_rows -= excess;
+            } else {
+                buffer.num_rows -= batch_rows;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TableProvider for StreamTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &SessionState,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let batches = self.snapshot(None);
+        let exec = MemoryExec::try_new(&[batches], self.schema(), projection.cloned())?;
+        Ok(Arc::new(exec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamTable;
+    use crate::{channel::CloudEvent, Uuid};
+    use datafusion::arrow::{
+        array::{Int64Array, StringArray},
+        datatypes::{DataType, Field, Schema},
+        record_batch::RecordBatch,
+    };
+    use serde_json::json;
+    use std::{thread, time::Duration};
+
+    fn new_stream_table(max_rows: usize, retention: Option<Duration>) -> StreamTable {
+        let schema = Schema::new(vec![
+            Field::new("host", DataType::Utf8, true),
+            Field::new("value", DataType::Int64, true),
+        ]);
+        StreamTable::new(schema, max_rows, retention)
+    }
+
+    fn new_event(data: serde_json::Value) -> CloudEvent {
+        let id = Uuid::new_v4().to_string();
+        CloudEvent::new(id, "test".to_owned(), "metrics".to_owned(), data)
+    }
+
+    fn collect_values(batches: &[RecordBatch]) -> Vec<Option<i64>> {
+        batches
+            .iter()
+            .flat_map(|batch| {
+                let column = batch.column(1).as_any().downcast_ref::<Int64Array>();
+                column.unwrap().iter().collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_maps_cloud_events_to_rows() {
+        let stream_table = new_stream_table(10, None);
+        let event = new_event(json!({ "host": "a", "value": 1, "unknown": true }));
+        assert_eq!(stream_table.append_cloud_event(&event).unwrap(), 1);
+
+        let event = new_event(json!([{ "host": "b", "value": 2 }, 3, { "host": "c" }]));
+        assert_eq!(stream_table.append_cloud_event(&event).unwrap(), 3);
+        let event = new_event(json!(4));
+        assert!(stream_table.append_cloud_event(&event).is_err());
+
+        let batches = stream_table.snapshot(None);
+        assert_eq!(collect_values(&batches), vec![Some(1), Some(2), None]);
+
+        let hosts = batches[1].column(0).as_any().downcast_ref::<StringArray>();
+        let hosts = hosts.unwrap().iter().collect::<Vec<_>>();
+        assert_eq!(hosts, vec![Some("b"), Some("c")]);
+    }
+
+    #[test]
+    fn it_bounds_the_capacity() {
+        let stream_table = new_stream_table(3, None);
+        let event = new_event(json!([{ "value": 1 }, { "value": 2 }]));
+        assert_eq!(stream_table.append_cloud_event(&event).unwrap(), 2);
+
+        let event = new_event(json!([{ "value": 3 }, { "value": 4 }]));
+        assert_eq!(stream_table.append_cloud_event(&event).unwrap(), 3);
+
+        let batches = stream_table.snapshot(None);
+        assert_eq!(collect_values(&batches), vec![Some(2), Some(3), Some(4)]);
+
+        let event = new_event(json!([{ "value": 5 }, { "value": 6 }, { "value": 7 }]));
+        assert_eq!(stream_table.append_cloud_event(&event).unwrap(), 3);
+
+        let batches = stream_table.snapshot(None);
+        assert_eq!(collect_values(&batches), vec![Some(5), Some(6), Some(7)]);
+    }
+
+    #[test]
+    fn it_expires_rows_out_of_the_window() {
+        let stream_table = new_stream_table(10, Some(Duration::from_millis(200)));
+        stream_table
+            .append_cloud_event(&new_event(json!({ "value": 1 })))
+            .unwrap();
+        thread::sleep(Duration::from_millis(120));
+        stream_table
+            .append_cloud_event(&new_event(json!({ "value": 2 })))
+            .unwrap();
+
+        let batches = stream_table.snapshot(Some(Duration::from_millis(60)));
+        assert_eq!(collect_values(&batches), vec![Some(2)]);
+
+        thread::sleep(Duration::from_millis(120));
+        let batches = stream_table.snapshot(None);
+        assert_eq!(collect_values(&batches), vec![Some(2)]);
+        assert_eq!(stream_table.num_rows(), 1);
+    }
+}
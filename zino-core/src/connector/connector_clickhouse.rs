@@ -0,0 +1,396 @@
+use super::{Connector, DataSource, DataSourceConnector::ClickHouse};
+use crate::{
+    application::http_client, error::Error, extend::TomlTableExt, format, state::State, Map,
+    Record, Uuid,
+};
+use apache_avro::types::Value as AvroValue;
+use chrono::{DateTime, NaiveDate};
+use reqwest::Response;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use toml::Table;
+use url::Url;
+
+/// A connector to ClickHouse via the HTTP interface.
+pub struct ClickHouseConnector {
+    /// Base URL.
+    base_url: Url,
+    /// Database.
+    database: String,
+    /// Username.
+    username: Option<String>,
+    /// Password.
+    password: Option<String>,
+}
+
+/// Response in the `JSON` format.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct JsonResponse {
+    /// Names and types of the columns.
+    meta: Vec<ColumnMeta>,
+    /// Rows.
+    data: Vec<Map>,
+}
+
+/// Name and type of a column.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ColumnMeta {
+    /// Column name.
+    name: String,
+    /// Column type.
+    #[serde(rename = "type")]
+    column_type: String,
+}
+
+impl ClickHouseConnector {
+    /// Constructs a new instance, returning an error if it fails.
+    pub fn try_new(base_url: &str, database: &str) -> Result<Self, Error> {
+        Ok(Self {
+            base_url: base_url.parse()?,
+            database: database.to_owned(),
+            username: None,
+            password: None,
+        })
+    }
+
+    /// Returns the database.
+    #[inline]
+    pub fn database(&self) -> &str {
+        self.database.as_str()
+    }
+
+    /// Sends the query to the server with the given params,
+    /// which are bound as the native query parameters.
+    pub async fn fetch(&self, query: &str, params: Option<&Map>) -> Result<Response, Error> {
+        let (query, values) = format::prepare_clickhouse_query(query, params);
+        let mut url = self.base_url.clone();
+        url.query_pairs_mut()
+            .append_pair("database", &self.database)
+            .append_pair("default_format", "JSON")
+            .append_pair("date_time_output_format", "iso")
+            .append_pair("output_format_json_quote_64bit_integers", "0")
+            .extend_pairs(
+                values
+                    .iter()
+                    .map(|(name, value)| (format!("param_{name}"), value)),
+            );
+
+        let mut options = Map::new();
+        options.upsert("method", "POST");
+        options.upsert("body", query);
+        let mut request_builder = http_client::request_builder(url.as_str(), Some(&options))?;
+        if let Some(username) = self.username.as_deref() {
+            request_builder = request_builder.header("x-clickhouse-user", username);
+        }
+        if let Some(password) = self.password.as_deref() {
+            request_builder = request_builder.header("x-clickhouse-key", password);
+        }
+
+        let response = request_builder.send().await?;
+        let status = response.status();
+        if status.is_client_error() || status.is_server_error() {
+            let text = response.text().await?;
+            let message = format!("ClickHouse error `{status}`: {}", text.trim());
+            return Err(Error::new(message));
+        }
+        Ok(response)
+    }
+
+    /// Sends the query to the server and parses the response in the `JSON` format.
+    async fn fetch_json(&self, query: &str, params: Option<&Map>) -> Result<JsonResponse, Error> {
+        let text = self.fetch(query, params).await?.text().await?;
+        if text.trim().is_empty() {
+            Ok(JsonResponse::default())
+        } else {
+            serde_json::from_str(&text).map_err(Error::from)
+        }
+    }
+}
+
+impl Connector for ClickHouseConnector {
+    fn try_new_data_source(config: &Table) -> Result<DataSource, Error> {
+        let name = config.get_str("name").unwrap_or("clickhouse");
+        let database = config.get_str("database").unwrap_or("default");
+        let scheme = if config.get_bool("secure").unwrap_or(false) {
+            "https"
+        } else {
+            "http"
+        };
+        let host = config.get_str("host").unwrap_or("localhost");
+        let port = config.get_u16("port").unwrap_or(8123);
+        let base_url = format!("{scheme}://{host}:{port}/");
+
+        let mut connector = ClickHouseConnector::try_new(&base_url, database)?;
+        connector.username = config.get_str("username").map(|s| s.to_owned());
+        connector.password = State::decrypt_password(config).map(|s| s.into_owned());
+        let data_source =
+            DataSource::new("clickhouse", None, name, database, ClickHouse(connector));
+        Ok(data_source)
+    }
+
+    async fn check_availability(&self) -> Result<(), Error> {
+        let url = self.base_url.join("ping")?;
+        http_client::request_builder(url.as_str(), None)?
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
+        let response = self.fetch(query, params).await?;
+        let rows_affected = response
+            .headers()
+            .get("x-clickhouse-summary")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| serde_json::from_str::<Map>(s).ok())
+            .and_then(|summary| match summary.get("written_rows") {
+                Some(Value::String(s)) => s.parse().ok(),
+                Some(Value::Number(n)) => n.as_u64(),
+                _ => None,
+            });
+        Ok(rows_affected)
+    }
+
+    async fn query(&self, query: &str, params: Option<&Map>) -> Result<Vec<Record>, Error> {
+        let response = self.fetch_json(query, params).await?;
+        Ok(decode_records(response))
+    }
+
+    async fn query_as<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+    ) -> Result<Vec<T>, Error> {
+        let response = self.fetch_json(query, params).await?;
+        serde_json::from_value(response.data.into()).map_err(Error::from)
+    }
+
+    async fn query_one(&self, query: &str, params: Option<&Map>) -> Result<Option<Record>, Error> {
+        let response = self.fetch_json(query, params).await?;
+        Ok(decode_records(response).into_iter().next())
+    }
+
+    async fn query_one_as<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+    ) -> Result<Option<T>, Error> {
+        let response = self.fetch_json(query, params).await?;
+        if let Some(row) = response.data.into_iter().next() {
+            serde_json::from_value(row.into()).map_err(Error::from)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Decodes the rows in the response as Avro records according to the column types.
+fn decode_records(response: JsonResponse) -> Vec<Record> {
+    let meta = response.meta;
+    response
+        .data
+        .into_iter()
+        .map(|mut row| {
+            meta.iter()
+                .map(|column| {
+                    let value = row.remove(&column.name).unwrap_or_default();
+                    let value = decode_value(&column.column_type, value);
+                    (column.name.clone(), value)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Decodes a JSON value as an Avro value according to the ClickHouse type.
+fn decode_value(column_type: &str, value: Value) -> AvroValue {
+    if value.is_null() {
+        return AvroValue::Null;
+    }
+    if let Some(column_type) =
+        unwrap_type(column_type, "Nullable").or_else(|| unwrap_type(column_type, "LowCardinality"))
+    {
+        return decode_value(column_type, value);
+    }
+    if let Some(item_type) = unwrap_type(column_type, "Array") {
+        return match value {
+            Value::Array(vec) => AvroValue::Array(
+                vec.into_iter()
+                    .map(|value| decode_value(item_type, value))
+                    .collect(),
+            ),
+            _ => value.into(),
+        };
+    }
+    if let Some(entry_types) = unwrap_type(column_type, "Map") {
+        let value_type = split_top_level(entry_types)
+            .get(1)
+            .copied()
+            .unwrap_or("String");
+        return match value {
+            Value::Object(map) => AvroValue::Map(
+                map.into_iter()
+                    .map(|(key, value)| (key, decode_value(value_type, value)))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            _ => value.into(),
+        };
+    }
+
+    let base_type = column_type
+        .split_once('(')
+        .map_or(column_type, |(base_type, _)| base_type);
+    match base_type {
+        "Bool" => value.as_bool().map_or(AvroValue::Null, AvroValue::Boolean),
+        "Int8" | "Int16" | "Int32" | "UInt8" | "UInt16" => parse_i64(&value)
+            .and_then(|i| i32::try_from(i).ok())
+            .map_or_else(|| value.into(), AvroValue::Int),
+        "Int64" | "UInt32" | "UInt64" | "Int128" | "UInt128" | "Int256" | "UInt256" => {
+            parse_i64(&value).map_or_else(|| value.into(), AvroValue::Long)
+        }
+        "Float32" => parse_f64(&value).map_or(AvroValue::Null, |f| AvroValue::Float(f as f32)),
+        "Float64" | "Decimal" | "Decimal32" | "Decimal64" | "Decimal128" | "Decimal256" => {
+            parse_f64(&value).map_or(AvroValue::Null, AvroValue::Double)
+        }
+        "UUID" => value
+            .as_str()
+            .and_then(|s| s.parse::<Uuid>().ok())
+            .map_or_else(|| value.into(), AvroValue::Uuid),
+        "Date" | "Date32" => value
+            .as_str()
+            .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+            .and_then(|date| NaiveDate::from_ymd_opt(1970, 1, 1).map(|epoch| date - epoch))
+            .and_then(|duration| i32::try_from(duration.num_days()).ok())
+            .map_or_else(|| value.into(), AvroValue::Date),
+        "DateTime" => value
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map_or_else(
+                || value.into(),
+                |dt| AvroValue::TimestampMillis(dt.timestamp_millis()),
+            ),
+        "DateTime64" => value
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map_or_else(
+                || value.into(),
+                |dt| AvroValue::TimestampMicros(dt.timestamp_micros()),
+            ),
+        _ => match value {
+            Value::String(s) => AvroValue::String(s),
+            value => value.into(),
+        },
+    }
+}
+
+/// Unwraps the type parameters of a parameterized type such as `Nullable(T)`.
+fn unwrap_type<'a>(column_type: &'a str, name: &str) -> Option<&'a str> {
+    column_type
+        .strip_prefix(name)
+        .and_then(|s| s.strip_prefix('('))
+        .and_then(|s| s.strip_suffix(')'))
+}
+
+/// Splits the type parameters at top-level commas.
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, ch) in s.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(s[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim());
+    parts
+}
+
+/// Parses a JSON number or numeric string as `i64`.
+fn parse_i64(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Parses a JSON number or numeric string as `f64`.
+fn parse_f64(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_decodes_json_response() {
+        let text = r#"{
+            "meta": [
+                {"name": "id", "type": "UInt64"},
+                {"name": "name", "type": "LowCardinality(String)"},
+                {"name": "score", "type": "Nullable(Float64)"},
+                {"name": "tags", "type": "Array(String)"},
+                {"name": "uuid", "type": "UUID"},
+                {"name": "created_at", "type": "DateTime('UTC')"},
+                {"name": "birthday", "type": "Date"}
+            ],
+            "data": [
+                {
+                    "id": 1,
+                    "name": "alice",
+                    "score": null,
+                    "tags": ["admin"],
+                    "uuid": "3a1f8e9a-7d43-4c39-8e5e-0f0e2a6d5c11",
+                    "created_at": "2023-03-01T08:00:00Z",
+                    "birthday": "1970-01-11"
+                }
+            ],
+            "rows": 1
+        }"#;
+        let response = serde_json::from_str::<JsonResponse>(text).unwrap();
+        let records = decode_records(response);
+        assert_eq!(records.len(), 1);
+
+        let record = &records[0];
+        let uuid = "3a1f8e9a-7d43-4c39-8e5e-0f0e2a6d5c11"
+            .parse::<Uuid>()
+            .unwrap();
+        assert_eq!(record[0], ("id".to_owned(), AvroValue::Long(1)));
+        assert_eq!(
+            record[1],
+            ("name".to_owned(), AvroValue::String("alice".to_owned()))
+        );
+        assert_eq!(record[2], ("score".to_owned(), AvroValue::Null));
+        assert_eq!(
+            record[3],
+            (
+                "tags".to_owned(),
+                AvroValue::Array(vec![AvroValue::String("admin".to_owned())])
+            )
+        );
+        assert_eq!(record[4], ("uuid".to_owned(), AvroValue::Uuid(uuid)));
+        assert_eq!(
+            record[5],
+            (
+                "created_at".to_owned(),
+                AvroValue::TimestampMillis(1_677_657_600_000)
+            )
+        );
+        assert_eq!(record[6], ("birthday".to_owned(), AvroValue::Date(10)));
+    }
+}
@@ -0,0 +1,196 @@
+use super::{Connector, DataSource, DataSourceConnector::DuckDB};
+use crate::{error::Error, extend::TomlTableExt, format, Map, Record};
+use apache_avro::types::Value as AvroValue;
+use duckdb::{
+    params_from_iter,
+    types::{TimeUnit, Value as DuckValue},
+    AccessMode, Config, Connection, Row,
+};
+use parking_lot::Mutex;
+use serde_json::Value;
+use toml::Table;
+
+/// A connector to DuckDB databases.
+///
+/// Queries are executed on a cloned connection of the database in a blocking thread
+/// of the tokio runtime, so it is suitable for local analytics on the embedded database files.
+pub struct DuckDBConnector {
+    /// Database path.
+    database: String,
+    /// Connection to the database.
+    connection: Mutex<Connection>,
+}
+
+impl DuckDBConnector {
+    /// Opens the database, returning an error if it fails.
+    /// The in-memory database will be used if the path is `:memory:`.
+    pub fn try_new(database: &str, read_only: bool) -> Result<Self, Error> {
+        let connection = if database == ":memory:" {
+            Connection::open_in_memory()?
+        } else if read_only {
+            let config = Config::default().access_mode(AccessMode::ReadOnly)?;
+            Connection::open_with_flags(database, config)?
+        } else {
+            Connection::open(database)?
+        };
+        Ok(Self {
+            database: database.to_owned(),
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Returns the database path.
+    #[inline]
+    pub fn database(&self) -> &str {
+        self.database.as_str()
+    }
+
+    /// Creates a new connection to the same database.
+    #[inline]
+    pub fn try_clone_connection(&self) -> Result<Connection, Error> {
+        self.connection.lock().try_clone().map_err(Error::from)
+    }
+
+    /// Runs the blocking operation with a cloned connection
+    /// so that the async runtime is not blocked.
+    async fn run_blocking<T, F>(&self, operation: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(Connection) -> Result<T, Error> + Send + 'static,
+    {
+        let connection = self.try_clone_connection()?;
+        tokio::task::spawn_blocking(move || operation(connection)).await?
+    }
+}
+
+impl Connector for DuckDBConnector {
+    fn try_new_data_source(config: &Table) -> Result<DataSource, Error> {
+        let name = config.get_str("name").unwrap_or("duckdb");
+        let database = config.get_str("database").unwrap_or(":memory:");
+        let read_only = config.get_bool("read-only").unwrap_or(false);
+
+        let connector = DuckDBConnector::try_new(database, read_only)?;
+        let data_source = DataSource::new("duckdb", None, name, database, DuckDB(connector));
+        Ok(data_source)
+    }
+
+    async fn check_availability(&self) -> Result<(), Error> {
+        self.run_blocking(|connection| connection.execute_batch("SELECT 1;").map_err(Error::from))
+            .await
+    }
+
+    async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
+        let (sql, values) = format::prepare_sql_query(query, params, '?');
+        let sql = sql.into_owned();
+        let params = values.into_iter().map(encode_value).collect::<Vec<_>>();
+        self.run_blocking(move |connection| {
+            let rows_affected = connection.execute(&sql, params_from_iter(params))?;
+            Ok(rows_affected.try_into().ok())
+        })
+        .await
+    }
+
+    async fn query(&self, query: &str, params: Option<&Map>) -> Result<Vec<Record>, Error> {
+        let (sql, values) = format::prepare_sql_query(query, params, '?');
+        let sql = sql.into_owned();
+        let params = values.into_iter().map(encode_value).collect::<Vec<_>>();
+        self.run_blocking(move |connection| {
+            let mut stmt = connection.prepare(&sql)?;
+            let mut rows = stmt.query(params_from_iter(params))?;
+            let mut records = Vec::new();
+            while let Some(row) = rows.next()? {
+                records.push(decode_row(row)?);
+            }
+            Ok(records)
+        })
+        .await
+    }
+
+    async fn query_one(&self, query: &str, params: Option<&Map>) -> Result<Option<Record>, Error> {
+        let (sql, values) = format::prepare_sql_query(query, params, '?');
+        let sql = sql.into_owned();
+        let params = values.into_iter().map(encode_value).collect::<Vec<_>>();
+        self.run_blocking(move |connection| {
+            let mut stmt = connection.prepare(&sql)?;
+            let mut rows = stmt.query(params_from_iter(params))?;
+            if let Some(row) = rows.next()? {
+                decode_row(row).map(Some)
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+    }
+}
+
+/// Encodes a JSON value as a DuckDB value for binding.
+fn encode_value(value: &Value) -> DuckValue {
+    match value {
+        Value::Null => DuckValue::Null,
+        Value::Bool(b) => DuckValue::Boolean(*b),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                DuckValue::BigInt(i)
+            } else {
+                DuckValue::Double(n.as_f64().unwrap_or_default())
+            }
+        }
+        Value::String(s) => DuckValue::Text(s.to_owned()),
+        value => DuckValue::Text(value.to_string()),
+    }
+}
+
+/// Decodes a row as an Avro record.
+fn decode_row(row: &Row<'_>) -> Result<Record, Error> {
+    let stmt = row.as_ref();
+    let column_count = stmt.column_count();
+    let mut record = Record::with_capacity(column_count);
+    for index in 0..column_count {
+        let name = stmt.column_name(index)?.to_owned();
+        let value = decode_value(row.get::<_, DuckValue>(index)?);
+        record.push((name, value));
+    }
+    Ok(record)
+}
+
+/// Decodes a DuckDB value as an Avro value.
+fn decode_value(value: DuckValue) -> AvroValue {
+    match value {
+        DuckValue::Null => AvroValue::Null,
+        DuckValue::Boolean(b) => AvroValue::Boolean(b),
+        DuckValue::TinyInt(i) => AvroValue::Int(i.into()),
+        DuckValue::SmallInt(i) => AvroValue::Int(i.into()),
+        DuckValue::Int(i) => AvroValue::Int(i),
+        DuckValue::BigInt(i) => AvroValue::Long(i),
+        DuckValue::HugeInt(i) => {
+            i64::try_from(i).map_or_else(|_| AvroValue::String(i.to_string()), AvroValue::Long)
+        }
+        DuckValue::UTinyInt(u) => AvroValue::Int(u.into()),
+        DuckValue::USmallInt(u) => AvroValue::Int(u.into()),
+        DuckValue::UInt(u) => AvroValue::Long(u.into()),
+        DuckValue::UBigInt(u) => {
+            i64::try_from(u).map_or_else(|_| AvroValue::String(u.to_string()), AvroValue::Long)
+        }
+        DuckValue::Float(f) => AvroValue::Float(f),
+        DuckValue::Double(f) => AvroValue::Double(f),
+        DuckValue::Decimal(d) => d
+            .to_string()
+            .parse()
+            .map_or_else(|_| AvroValue::String(d.to_string()), AvroValue::Double),
+        DuckValue::Timestamp(unit, t) => AvroValue::TimestampMicros(convert_to_micros(unit, t)),
+        DuckValue::Text(s) => AvroValue::String(s),
+        DuckValue::Blob(bytes) => AvroValue::Bytes(bytes),
+        DuckValue::Date32(days) => AvroValue::Date(days),
+        DuckValue::Time64(unit, t) => AvroValue::TimeMicros(convert_to_micros(unit, t)),
+    }
+}
+
+/// Converts the value in the time unit to microseconds.
+fn convert_to_micros(unit: TimeUnit, value: i64) -> i64 {
+    match unit {
+        TimeUnit::Second => value * 1_000_000,
+        TimeUnit::Millisecond => value * 1_000,
+        TimeUnit::Microsecond => value,
+        TimeUnit::Nanosecond => value / 1_000,
+    }
+}
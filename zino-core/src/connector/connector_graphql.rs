@@ -0,0 +1,398 @@
+use super::{Connector, DataSource, DataSourceConnector::GraphQL};
+use crate::{
+    application::http_client,
+    error::Error,
+    extend::{JsonObjectExt, TomlTableExt},
+    format,
+    trace::TraceContext,
+    Map, Record,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use toml::Table;
+use url::Url;
+
+/// A connector to GraphQL APIs.
+///
+/// The query is a GraphQL document which is sent verbatim,
+/// and the params are sent as the `variables` of the request.
+/// Default variables can be specified by the `variables` table of the configuration,
+/// in which `${param}`s are resolved with the params. A string value which is exactly
+/// a `${param}` is replaced with the param value as is, otherwise the params are interpolated.
+/// The operation name can be specified by the `operationName` param,
+/// which defaults to the `operation-name` field of the configuration.
+///
+/// If the query declares the `$after` variable and the root field is a connection type
+/// with `pageInfo { hasNextPage endCursor }`, the pages will be fetched automatically
+/// and the nodes in `edges` or `nodes` will be collected as records.
+pub struct GraphQLConnector {
+    /// Endpoint.
+    endpoint: Url,
+    /// HTTP request headers.
+    headers: Map,
+    /// Default variables.
+    variables: Map,
+    /// Default operation name.
+    operation_name: Option<String>,
+    /// Maximum number of pages to fetch.
+    max_pages: usize,
+}
+
+impl GraphQLConnector {
+    /// Constructs a new instance, returning an error if it fails.
+    pub fn try_new(endpoint: &str) -> Result<Self, Error> {
+        Ok(Self {
+            endpoint: endpoint.parse()?,
+            headers: Map::new(),
+            variables: Map::new(),
+            operation_name: None,
+            max_pages: 100,
+        })
+    }
+
+    /// Returns the endpoint.
+    #[inline]
+    pub fn endpoint(&self) -> &str {
+        self.endpoint.as_str()
+    }
+
+    /// Sends a GraphQL request with the given query and params,
+    /// and returns the `data` payload of the response.
+    pub async fn fetch_data(&self, query: &str, params: Option<&Map>) -> Result<Value, Error> {
+        let mut variables = self
+            .variables
+            .iter()
+            .map(|(key, value)| (key.to_owned(), resolve_variable(value, params)))
+            .collect::<Map>();
+        if let Some(params) = params {
+            variables.extend(params.clone());
+        }
+        let operation_name = match variables.remove("operationName") {
+            Some(Value::String(operation_name)) => Some(operation_name),
+            _ => self.operation_name.clone(),
+        };
+        let mut body = Map::new();
+        body.upsert("query", query);
+        body.upsert("variables", variables);
+        if let Some(operation_name) = operation_name {
+            body.upsert("operationName", operation_name);
+        }
+
+        let mut trace_context = TraceContext::new();
+        let span_id = trace_context.span_id();
+        trace_context
+            .trace_state_mut()
+            .push("zino", format!("{span_id:x}"));
+
+        let mut options = Map::new();
+        options.upsert("method", "POST");
+        options.upsert("body", body);
+        options.upsert("data_type", "json");
+        options.upsert("headers", self.headers.clone());
+        let mut response: Map = http_client::request_builder(self.endpoint(), Some(&options))?
+            .header("traceparent", trace_context.traceparent())
+            .header("tracestate", trace_context.tracestate())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(Value::Array(errors)) = response.remove("errors") && !errors.is_empty() {
+            return Err(parse_errors(&errors));
+        }
+        response
+            .remove("data")
+            .ok_or_else(|| Error::new("the `data` field is absent in the GraphQL response"))
+    }
+
+    /// Sends GraphQL requests with the given query and params,
+    /// and collects the nodes of the root field. If the root field is a connection type,
+    /// the pages will be fetched automatically.
+    pub async fn fetch_nodes(&self, query: &str, params: Option<&Map>) -> Result<Vec<Map>, Error> {
+        let paginated = query.contains("$after");
+        let mut params = params.cloned().unwrap_or_default();
+        let mut nodes = Vec::new();
+        for _ in 0..self.max_pages {
+            let data = self.fetch_data(query, Some(&params)).await?;
+            let Some(value) = unwrap_root_field(data) else {
+                break;
+            };
+            match collect_nodes(value, &mut nodes) {
+                Some(end_cursor) if paginated => params.upsert("after", end_cursor),
+                _ => break,
+            }
+        }
+        Ok(nodes)
+    }
+}
+
+impl Connector for GraphQLConnector {
+    fn try_new_data_source(config: &Table) -> Result<DataSource, Error> {
+        let name = config.get_str("name").unwrap_or("graphql");
+        let catalog = config.get_str("catalog").unwrap_or(name);
+
+        let endpoint = config
+            .get_str("endpoint")
+            .or_else(|| config.get_str("base-url"))
+            .unwrap_or_default();
+        let mut connector = GraphQLConnector::try_new(endpoint)?;
+        if let Some(headers) = config.get_table("headers") {
+            if let Value::Object(headers) = serde_json::to_value(headers)? {
+                connector.headers = headers;
+            }
+        }
+        if let Some(variables) = config.get_table("variables") {
+            if let Value::Object(variables) = serde_json::to_value(variables)? {
+                connector.variables = variables;
+            }
+        }
+        if let Some(operation_name) = config.get_str("operation-name") {
+            connector.operation_name = Some(operation_name.to_owned());
+        }
+        if let Some(max_pages) = config.get_usize("max-pages") {
+            connector.max_pages = max_pages.max(1);
+        }
+
+        let data_source = DataSource::new("graphql", None, name, catalog, GraphQL(connector));
+        Ok(data_source)
+    }
+
+    async fn check_availability(&self) -> Result<(), Error> {
+        self.fetch_data("{ __typename }", None).await?;
+        Ok(())
+    }
+
+    async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
+        let data = self.fetch_data(query, params).await?;
+        if let Some(Value::Object(map)) = unwrap_root_field(data)
+            && let Some(rows_affected) = map
+                .get_u64("affected_rows")
+                .or_else(|| map.get_u64("affectedRows"))
+        {
+            Ok(Some(rows_affected))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn query(&self, query: &str, params: Option<&Map>) -> Result<Vec<Record>, Error> {
+        let nodes = self.fetch_nodes(query, params).await?;
+        let records = nodes
+            .into_iter()
+            .map(|node| node.into_avro_record())
+            .collect();
+        Ok(records)
+    }
+
+    async fn query_as<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+    ) -> Result<Vec<T>, Error> {
+        let nodes = self.fetch_nodes(query, params).await?;
+        serde_json::from_value(nodes.into()).map_err(Error::from)
+    }
+
+    async fn query_one(&self, query: &str, params: Option<&Map>) -> Result<Option<Record>, Error> {
+        let data = self.fetch_data(query, params).await?;
+        let record = match unwrap_root_field(data) {
+            Some(Value::Object(map)) => Some(map.into_avro_record()),
+            Some(Value::Null) | None => None,
+            Some(value) => {
+                let mut record = Record::new();
+                record.upsert("data", value);
+                Some(record)
+            }
+        };
+        Ok(record)
+    }
+
+    async fn query_one_as<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+    ) -> Result<Option<T>, Error> {
+        let data = self.fetch_data(query, params).await?;
+        match unwrap_root_field(data) {
+            Some(Value::Null) | None => Ok(None),
+            Some(value) => serde_json::from_value(value).map_err(Error::from),
+        }
+    }
+}
+
+/// Unwraps the value of the root field if the `data` payload has only one field.
+fn unwrap_root_field(data: Value) -> Option<Value> {
+    match data {
+        Value::Object(mut map) if map.len() == 1 => map.values_mut().next().map(|v| v.take()),
+        Value::Null => None,
+        data => Some(data),
+    }
+}
+
+/// Collects the nodes of the root field value, and returns the cursor of the next page
+/// if the value is a connection type which has the next page.
+fn collect_nodes(value: Value, nodes: &mut Vec<Map>) -> Option<String> {
+    match value {
+        Value::Array(vec) => {
+            nodes.extend(vec.into_iter().filter_map(into_object));
+            None
+        }
+        Value::Object(mut map) => {
+            let page_info = map.remove("pageInfo");
+            if let Some(Value::Array(edges)) = map.remove("edges") {
+                nodes.extend(edges.into_iter().filter_map(|mut edge| {
+                    edge.get_mut("node").map(Value::take).and_then(into_object)
+                }));
+            } else if let Some(Value::Array(vec)) = map.remove("nodes") {
+                nodes.extend(vec.into_iter().filter_map(into_object));
+            } else {
+                nodes.push(map);
+                return None;
+            }
+            if let Some(Value::Object(page_info)) = page_info
+                && page_info.get("hasNextPage").and_then(|v| v.as_bool()) == Some(true)
+            {
+                page_info.get_str("endCursor").map(|s| s.to_owned())
+            } else {
+                None
+            }
+        }
+        _ => {
+            let mut map = Map::new();
+            map.upsert("data", value);
+            nodes.push(map);
+            None
+        }
+    }
+}
+
+/// Parses the `errors` array of a GraphQL response as an error.
+fn parse_errors(errors: &[Value]) -> Error {
+    let message = errors
+        .iter()
+        .map(|error| {
+            let message = error
+                .get("message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown error");
+            if let Some(Value::Array(path)) = error.get("path") {
+                let path = path
+                    .iter()
+                    .map(|v| v.as_str().map_or_else(|| v.to_string(), |s| s.to_owned()))
+                    .collect::<Vec<_>>();
+                format!("{message} (at `{}`)", path.join("."))
+            } else {
+                message.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    Error::new(message)
+}
+
+/// Resolves the `${param}`s in the variable value with the params.
+fn resolve_variable(value: &Value, params: Option<&Map>) -> Value {
+    match value {
+        Value::String(s) => {
+            let key = s
+                .strip_prefix("${")
+                .and_then(|s| s.strip_suffix('}'))
+                .map(|key| key.trim());
+            if let Some(key) = key
+                && let Some(value) = params.and_then(|params| params.get(key))
+            {
+                value.clone()
+            } else {
+                format::format_query(s, params).into_owned().into()
+            }
+        }
+        Value::Array(vec) => vec
+            .iter()
+            .map(|value| resolve_variable(value, params))
+            .collect::<Vec<_>>()
+            .into(),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| (key.to_owned(), resolve_variable(value, params)))
+            .collect::<Map>()
+            .into(),
+        _ => value.clone(),
+    }
+}
+
+/// Converts the value into a JSON object if possible.
+fn into_object(value: Value) -> Option<Map> {
+    if let Value::Object(map) = value {
+        Some(map)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_nodes, parse_errors, resolve_variable, unwrap_root_field};
+    use crate::{extend::JsonObjectExt, Map};
+    use serde_json::json;
+
+    #[test]
+    fn it_unwraps_root_field() {
+        let data = json!({ "user": { "id": 1 } });
+        assert_eq!(unwrap_root_field(data), Some(json!({ "id": 1 })));
+
+        let data = json!({ "user": { "id": 1 }, "viewer": { "id": 2 } });
+        assert_eq!(unwrap_root_field(data.clone()), Some(data));
+        assert_eq!(unwrap_root_field(json!(null)), None);
+    }
+
+    #[test]
+    fn it_parses_errors() {
+        let errors = [
+            json!({ "message": "not found", "path": ["users", 0, "name"] }),
+            json!({ "message": "unauthorized" }),
+            json!({}),
+        ];
+        assert_eq!(
+            parse_errors(&errors).message(),
+            "not found (at `users.0.name`); unauthorized; unknown error"
+        );
+    }
+
+    #[test]
+    fn it_collects_nodes() {
+        let mut nodes = Vec::new();
+        let value = json!({
+            "edges": [{ "node": { "id": 1 } }, { "node": { "id": 2 } }],
+            "pageInfo": { "hasNextPage": true, "endCursor": "c2" },
+        });
+        assert_eq!(collect_nodes(value, &mut nodes).as_deref(), Some("c2"));
+
+        let value = json!({
+            "nodes": [{ "id": 3 }],
+            "pageInfo": { "hasNextPage": false, "endCursor": "c3" },
+        });
+        assert_eq!(collect_nodes(value, &mut nodes), None);
+        assert_eq!(collect_nodes(json!([{ "id": 4 }]), &mut nodes), None);
+        assert_eq!(collect_nodes(json!({ "id": 5 }), &mut nodes), None);
+        assert_eq!(collect_nodes(json!(6), &mut nodes), None);
+
+        let ids = nodes
+            .iter()
+            .map(|node| node.get("id").or_else(|| node.get("data")).cloned())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [1, 2, 3, 4, 5, 6].map(|id| Some(json!(id))).to_vec());
+    }
+
+    #[test]
+    fn it_resolves_variables() {
+        let mut params = Map::new();
+        params.upsert("id", 1);
+        params.upsert("name", "zino");
+
+        let value = json!({ "id": "${id}", "filter": ["name:${name}", "${missing}"] });
+        assert_eq!(
+            resolve_variable(&value, Some(&params)),
+            json!({ "id": 1, "filter": ["name:zino", "${missing}"] })
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    authentication::{AccessKeyId, Authentication, SecretAccessKey},
+    datetime::DateTime,
+    error::Error,
+    extend::TomlTableExt,
+};
+use hmac::Hmac;
+use http::Method;
+use reqwest_middleware::RequestBuilder;
+use sha2::Sha256;
+use toml::Table;
+use url::Url;
+
+/// Authentication schemes for HTTP requests.
+pub(super) enum HttpAuth {
+    /// Bearer token.
+    Bearer(String),
+    /// Basic authentication.
+    Basic {
+        /// Username.
+        username: String,
+        /// Optional password.
+        password: Option<String>,
+    },
+    /// HTTP signature using HMAC.
+    Hmac {
+        /// Service name.
+        service_name: String,
+        /// Access key ID.
+        access_key_id: AccessKeyId,
+        /// Secret access key.
+        secret_access_key: SecretAccessKey,
+    },
+}
+
+impl HttpAuth {
+    /// Attempts to create a new instance from the TOML table configuration.
+    pub(super) fn try_from_toml_table(config: &Table) -> Result<Self, Error> {
+        let auth_type = config.get_str("type").unwrap_or("bearer");
+        let auth = match auth_type {
+            "bearer" => {
+                let token = config
+                    .get_str("token")
+                    .ok_or_else(|| Error::new("the `token` field should be a str"))?;
+                HttpAuth::Bearer(token.to_owned())
+            }
+            "basic" => {
+                let username = config
+                    .get_str("username")
+                    .ok_or_else(|| Error::new("the `username` field should be a str"))?;
+                HttpAuth::Basic {
+                    username: username.to_owned(),
+                    password: config.get_str("password").map(|s| s.to_owned()),
+                }
+            }
+            "hmac" => {
+                let service_name = config
+                    .get_str("service-name")
+                    .filter(|s| !s.is_empty())
+                    .ok_or_else(|| Error::new("the `service-name` field should be a str"))?;
+                let access_key_id = config
+                    .get_str("access-key-id")
+                    .ok_or_else(|| Error::new("the `access-key-id` field should be a str"))?;
+                let secret_access_key = if let Some(secret_access_key) =
+                    config.get_str("secret-access-key")
+                {
+                    SecretAccessKey::from(secret_access_key)
+                } else if let Some(secret_key) = config.get_str("secret-key") {
+                    SecretAccessKey::new::<Hmac<Sha256>>(secret_key, access_key_id)
+                } else {
+                    let message = "the `secret-access-key` or `secret-key` field should be a str";
+                    return Err(Error::new(message));
+                };
+                HttpAuth::Hmac {
+                    service_name: service_name.to_owned(),
+                    access_key_id: access_key_id.into(),
+                    secret_access_key,
+                }
+            }
+            _ => {
+                let message = format!("authentication scheme `{auth_type}` is unsupported");
+                return Err(Error::new(message));
+            }
+        };
+        Ok(auth)
+    }
+
+    /// Applies the authentication to the request builder.
+    pub(super) fn apply(
+        &self,
+        request_builder: RequestBuilder,
+        method: &Method,
+        url: &Url,
+        content_type: Option<&str>,
+    ) -> RequestBuilder {
+        match self {
+            HttpAuth::Bearer(token) => request_builder.bearer_auth(token),
+            HttpAuth::Basic { username, password } => {
+                request_builder.basic_auth(username, password.as_ref())
+            }
+            HttpAuth::Hmac {
+                service_name,
+                access_key_id,
+                secret_access_key,
+            } => {
+                let date = DateTime::now();
+                let mut authentication = Authentication::new(method.as_str());
+                authentication.set_service_name(service_name);
+                authentication.set_access_key_id(access_key_id.clone());
+                authentication.set_content_type(content_type.map(|s| s.to_owned()));
+                authentication.set_date_header("date", date);
+                authentication.set_resource(url.path().to_owned(), None);
+
+                let signature = authentication.sign_with::<Hmac<Sha256>>(secret_access_key.clone());
+                authentication.set_signature(signature);
+                request_builder
+                    .header("date", date.to_utc_string())
+                    .header("authorization", authentication.authorization())
+            }
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use super::{Connector, DataSource, DataSourceConnector::Http};
+use crate::{
+    application::http_client,
+    error::Error,
+    extend::{HeaderMapExt, JsonObjectExt, TomlTableExt},
+    format,
+    trace::TraceContext,
+    Map, Record,
+};
+use http::{
+    header::{self, HeaderMap, HeaderName},
+    Method,
+};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use serde_json::{value::RawValue, Value};
+use toml::Table;
+use url::Url;
+
+mod http_auth;
+mod pagination;
+
+use http_auth::HttpAuth;
+use pagination::{PageState, Pagination};
+
+/// A connector to HTTP services.
+///
+/// Third-party REST APIs can be used as data sources with the configuration
+/// for pagination, response mapping and authentication:
+///
+/// ```toml
+/// [[connector]]
+/// type = "rest"
+/// name = "github"
+/// method = "GET"
+/// base-url = "https://api.github.com/"
+/// records-pointer = "/items"
+/// headers = { accept = "application/vnd.github+json" }
+///
+/// [connector.pagination]
+/// style = "page"
+/// page-param = "page"
+/// size-param = "per_page"
+/// page-size = 100
+/// max-pages = 10
+///
+/// [connector.fields]
+/// id = "id"
+/// name = "full_name"
+/// owner = "/owner/login"
+///
+/// [connector.auth]
+/// type = "bearer"
+/// token = "ghp_xxxxxxxx"
+/// ```
+///
+/// The supported pagination styles are `page`, `offset`, `cursor` and `link`.
+/// The `records-pointer` is a JSON pointer to locate the records in the response,
+/// and the `fields` table maps each output field to a field or a JSON pointer in a record,
+/// with unlisted fields being dropped. The supported authentication schemes are
+/// `bearer`, `basic` and `hmac` (the HTTP signature of [`Authentication`]),
+/// and the `service-name` is required for `hmac`.
+///
+/// [`Authentication`]: crate::authentication::Authentication
+pub struct HttpConnector {
+    /// HTTP request method (VERB).
+    method: Method,
+    /// Base URL.
+    base_url: Url,
+    /// HTTP request headers.
+    headers: Map,
+    /// Optional request body.
+    body: Option<Box<RawValue>>,
+    /// JSON pointer to locate the records in the response.
+    records_pointer: Option<String>,
+    /// Mappings from the output fields to the fields or JSON pointers in a record.
+    fields: Option<Vec<(String, String)>>,
+    /// Pagination style.
+    pagination: Option<Pagination>,
+    /// Maximum number of pages to fetch.
+    max_pages: usize,
+    /// Authentication scheme.
+    auth: Option<HttpAuth>,
+}
+
+impl HttpConnector {
+    /// Constructs a new instance, returning an error if it fails.
+    pub fn try_new(method: &str, base_url: &str) -> Result<Self, Error> {
+        Ok(Self {
+            method: method.parse()?,
+            base_url: base_url.parse()?,
+            headers: Map::new(),
+            body: None,
+            records_pointer: None,
+            fields: None,
+            pagination: None,
+            max_pages: 100,
+            auth: None,
+        })
+    }
+
+    /// Returns the request method.
+    #[inline]
+    pub fn method(&self) -> &str {
+        self.method.as_str()
+    }
+
+    /// Returns the optional body.
+    #[inline]
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref().map(|raw_value| raw_value.get())
+    }
+
+    /// Makes an HTTP request with the given query and params.
+    pub async fn fetch(&self, query: &str, params: Option<&Map>) -> Result<Response, Error> {
+        let url = self.base_url.join(query)?;
+        let resource = format::format_query(url.as_str(), params);
+        self.fetch_url(resource.parse()?, params).await
+    }
+
+    /// Makes an HTTP request to the URL with the given params.
+    async fn fetch_url(&self, url: Url, params: Option<&Map>) -> Result<Response, Error> {
+        let mut options = Map::new();
+        options.upsert("method", self.method());
+        if let Some(body) = self.body() {
+            options.upsert("body", format::format_query(body, params));
+        }
+
+        let mut headers = HeaderMap::new();
+        for (key, value) in self.headers.iter() {
+            if let Ok(header_name) = HeaderName::try_from(key) {
+                if let Some(header_value) = value
+                    .as_str()
+                    .and_then(|s| format::format_query(s, params).parse().ok())
+                {
+                    headers.insert(header_name, header_value);
+                }
+            }
+        }
+
+        let mut trace_context = TraceContext::new();
+        let span_id = trace_context.span_id();
+        trace_context
+            .trace_state_mut()
+            .push("zino", format!("{span_id:x}"));
+
+        let mut request_builder = http_client::request_builder(url.as_str(), Some(&options))?;
+        if let Some(auth) = self.auth.as_ref() {
+            let content_type = headers
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .or_else(|| self.body.is_some().then_some("text/plain"));
+            request_builder = auth.apply(request_builder, &self.method, &url, content_type);
+        }
+        request_builder
+            .headers(headers)
+            .header("traceparent", trace_context.traceparent())
+            .header("tracestate", trace_context.tracestate())
+            .send()
+            .await
+            .map_err(Error::from)
+    }
+
+    /// Makes an HTTP request with the given query and params,
+    /// and deserializes the response body as JSON.
+    pub async fn fetch_json<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+    ) -> Result<T, Error> {
+        let response = self.fetch(query, params).await?.error_for_status()?;
+        parse_json_response(response).await
+    }
+
+    /// Makes HTTP requests with the given query and params, and collects the records
+    /// in the responses. If the pagination is configured, the pages will be fetched
+    /// until there are no more records or the maximum number of pages is reached.
+    pub async fn fetch_records(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+    ) -> Result<Vec<Map>, Error> {
+        let Some(pagination) = self.pagination.as_ref() else {
+            let data = self.fetch_json(query, params).await?;
+            return self.extract_records(data);
+        };
+
+        let url = self.base_url.join(query)?;
+        let url = format::format_query(url.as_str(), params).parse::<Url>()?;
+        let mut state = PageState::default();
+        let mut records = Vec::new();
+        while state.pages < self.max_pages
+            && let Some(page_url) = pagination.next_url(&url, &mut state)
+        {
+            let response = self
+                .fetch_url(page_url.clone(), params)
+                .await?
+                .error_for_status()?;
+            let headers = response.headers().clone();
+            let data = parse_json_response::<Value>(response).await?;
+            pagination.read_next(&mut state, &page_url, &self.base_url, &headers, &data)?;
+
+            let page_records = self.extract_records(data)?;
+            let has_next = pagination.update_state(&mut state, page_records.len());
+            records.extend(page_records);
+            if !has_next {
+                break;
+            }
+        }
+        Ok(records)
+    }
+
+    /// Extracts the records from the response data.
+    fn extract_records(&self, mut data: Value) -> Result<Vec<Map>, Error> {
+        let records = if let Some(pointer) = self.records_pointer.as_deref() {
+            match data.pointer_mut(pointer).map(Value::take) {
+                Some(Value::Array(vec)) => collect_objects(vec),
+                Some(Value::Object(map)) => vec![map],
+                _ => Vec::new(),
+            }
+        } else {
+            match data {
+                Value::Array(vec) => collect_objects(vec),
+                Value::Object(mut map) => {
+                    if let Some(value) = map.remove("data").or_else(|| map.remove("result")) {
+                        if let Value::Array(vec) = value {
+                            collect_objects(vec)
+                        } else {
+                            let mut map = Map::new();
+                            map.upsert("data", value);
+                            vec![map]
+                        }
+                    } else {
+                        vec![map]
+                    }
+                }
+                _ => return Err(Error::new("invalid data format")),
+            }
+        };
+        Ok(records
+            .into_iter()
+            .map(|record| self.map_fields(record))
+            .collect())
+    }
+
+    /// Extracts a single record from the response data.
+    fn extract_record(&self, data: Value) -> Result<Option<Map>, Error> {
+        if self.records_pointer.is_some() {
+            return self
+                .extract_records(data)
+                .map(|records| records.into_iter().next());
+        }
+
+        let record = match data {
+            Value::Object(mut map) => {
+                if let Some(value) = map.remove("data").or_else(|| map.remove("result")) {
+                    if let Value::Object(data) = value {
+                        data
+                    } else {
+                        let mut map = Map::new();
+                        map.upsert("data", value);
+                        map
+                    }
+                } else {
+                    map
+                }
+            }
+            _ => return Err(Error::new("invalid data format")),
+        };
+        Ok(Some(self.map_fields(record)))
+    }
+
+    /// Renames and projects the fields of a record.
+    fn map_fields(&self, record: Map) -> Map {
+        let Some(fields) = self.fields.as_deref() else {
+            return record;
+        };
+
+        let record = Value::Object(record);
+        let mut map = Map::new();
+        for (field, source) in fields {
+            let value = if source.starts_with('/') {
+                record.pointer(source)
+            } else {
+                record.get(source)
+            };
+            map.upsert(field, value.cloned().unwrap_or_default());
+        }
+        map
+    }
+}
+
+impl Connector for HttpConnector {
+    fn try_new_data_source(config: &Table) -> Result<DataSource, Error> {
+        let name = config.get_str("name").unwrap_or("http");
+        let catalog = config.get_str("catalog").unwrap_or(name);
+
+        let method = config.get_str("method").unwrap_or_default();
+        let base_url = config.get_str("base-url").unwrap_or_default();
+        let mut connector = HttpConnector::try_new(method, base_url)?;
+        if let Some(headers) = config.get_table("headers") {
+            if let Value::Object(headers) = serde_json::to_value(headers)? {
+                connector.headers = headers;
+            }
+        }
+        if let Some(records_pointer) = config.get_str("records-pointer") {
+            connector.records_pointer = Some(records_pointer.to_owned());
+        }
+        if let Some(fields) = config.get_table("fields") {
+            let fields = fields
+                .iter()
+                .filter_map(|(key, value)| value.as_str().map(|s| (key.to_owned(), s.to_owned())))
+                .collect();
+            connector.fields = Some(fields);
+        }
+        if let Some(pagination) = config.get_table("pagination") {
+            connector.pagination = Some(Pagination::try_from_toml_table(pagination)?);
+            if let Some(max_pages) = pagination.get_usize("max-pages") {
+                connector.max_pages = max_pages.max(1);
+            }
+        }
+        if let Some(auth) = config.get_table("auth") {
+            connector.auth = Some(HttpAuth::try_from_toml_table(auth)?);
+        }
+        let data_source = DataSource::new("http", None, name, catalog, Http(connector));
+        Ok(data_source)
+    }
+
+    async fn check_availability(&self) -> Result<(), Error> {
+        let response = http_client::request_builder(self.base_url.as_str(), None)?
+            .send()
+            .await?;
+        let status = response.status();
+        if status.is_server_error() {
+            let message = format!("server error `{status}` from `{}`", self.base_url);
+            return Err(Error::new(message));
+        }
+        Ok(())
+    }
+
+    async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
+        if let Value::Object(map) = self.fetch_json(query, params).await? &&
+            let Some(rows_affected) = map
+                .get_u64("rows_affected")
+                .or_else(|| map.get_u64("total_rows"))
+        {
+            Ok(Some(rows_affected))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn query(&self, query: &str, params: Option<&Map>) -> Result<Vec<Record>, Error> {
+        let records = self
+            .fetch_records(query, params)
+            .await?
+            .into_iter()
+            .map(|record| record.into_avro_record())
+            .collect();
+        Ok(records)
+    }
+
+    async fn query_as<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+    ) -> Result<Vec<T>, Error> {
+        let data = self.fetch_records(query, params).await?;
+        serde_json::from_value(data.into()).map_err(Error::from)
+    }
+
+    async fn query_one(&self, query: &str, params: Option<&Map>) -> Result<Option<Record>, Error> {
+        let data = self.fetch_json(query, params).await?;
+        let record = self.extract_record(data)?;
+        Ok(record.map(|record| record.into_avro_record()))
+    }
+
+    async fn query_one_as<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+    ) -> Result<Option<T>, Error> {
+        let data = self.fetch_json(query, params).await?;
+        if let Some(record) = self.extract_record(data)? {
+            serde_json::from_value(record.into()).map_err(Error::from)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Deserializes the response body as JSON.
+async fn parse_json_response<T: DeserializeOwned>(response: Response) -> Result<T, Error> {
+    let data = if response.headers().has_json_content_type() {
+        response.json().await?
+    } else {
+        let text = response.text().await?;
+        serde_json::from_str(&text)?
+    };
+    Ok(data)
+}
+
+/// Collects the JSON objects in the array and drops the other values.
+fn collect_objects(vec: Vec<Value>) -> Vec<Map> {
+    vec.into_iter()
+        .filter_map(|value| {
+            if let Value::Object(map) = value {
+                Some(map)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,294 @@
+use crate::{error::Error, extend::TomlTableExt};
+use http::header::{HeaderMap, LINK};
+use serde_json::Value;
+use toml::Table;
+use url::Url;
+
+/// Pagination styles for HTTP services.
+pub(super) enum Pagination {
+    /// Page number based pagination.
+    Page {
+        /// Query parameter for the page number.
+        page_param: String,
+        /// Query parameter for the page size.
+        size_param: Option<String>,
+        /// Page size.
+        page_size: Option<usize>,
+        /// Number of the first page.
+        start_page: usize,
+    },
+    /// Offset based pagination.
+    Offset {
+        /// Query parameter for the offset.
+        offset_param: String,
+        /// Query parameter for the limit.
+        limit_param: String,
+        /// Number of records in a page.
+        limit: usize,
+    },
+    /// Cursor based pagination.
+    Cursor {
+        /// Query parameter for the cursor.
+        cursor_param: String,
+        /// JSON pointer to the next cursor in the response.
+        cursor_pointer: String,
+    },
+    /// Pagination via the `Link` header.
+    Link,
+}
+
+/// State of the pagination.
+#[derive(Default)]
+pub(super) struct PageState {
+    /// Number of pages fetched.
+    pub(super) pages: usize,
+    /// Number of records fetched.
+    pub(super) records: usize,
+    /// Next cursor.
+    pub(super) cursor: Option<String>,
+    /// Next URL.
+    pub(super) next_url: Option<Url>,
+}
+
+impl Pagination {
+    /// Attempts to create a new instance from the TOML table configuration.
+    pub(super) fn try_from_toml_table(config: &Table) -> Result<Self, Error> {
+        let style = config.get_str("style").unwrap_or("page");
+        let pagination = match style {
+            "page" => Pagination::Page {
+                page_param: config.get_str("page-param").unwrap_or("page").to_owned(),
+                size_param: config.get_str("size-param").map(|s| s.to_owned()),
+                page_size: config.get_usize("page-size"),
+                start_page: config.get_usize("start-page").unwrap_or(1),
+            },
+            "offset" => Pagination::Offset {
+                offset_param: config
+                    .get_str("offset-param")
+                    .unwrap_or("offset")
+                    .to_owned(),
+                limit_param: config.get_str("limit-param").unwrap_or("limit").to_owned(),
+                limit: config.get_usize("limit").unwrap_or(100),
+            },
+            "cursor" => Pagination::Cursor {
+                cursor_param: config
+                    .get_str("cursor-param")
+                    .unwrap_or("cursor")
+                    .to_owned(),
+                cursor_pointer: config
+                    .get_str("cursor-pointer")
+                    .ok_or_else(|| Error::new("the `cursor-pointer` field should be a str"))?
+                    .to_owned(),
+            },
+            "link" => Pagination::Link,
+            _ => {
+                let message = format!("pagination style `{style}` is unsupported");
+                return Err(Error::new(message));
+            }
+        };
+        Ok(pagination)
+    }
+
+    /// Returns the URL for the next page, or `None` if there are no more pages.
+    pub(super) fn next_url(&self, url: &Url, state: &mut PageState) -> Option<Url> {
+        let mut url = url.clone();
+        match self {
+            Pagination::Page {
+                page_param,
+                size_param,
+                page_size,
+                start_page,
+            } => {
+                let page = start_page + state.pages;
+                let mut params = vec![(page_param.as_str(), page.to_string())];
+                if let Some(size_param) = size_param
+                    && let Some(page_size) = page_size
+                {
+                    params.push((size_param.as_str(), page_size.to_string()));
+                }
+                set_query_params(&mut url, &params);
+            }
+            Pagination::Offset {
+                offset_param,
+                limit_param,
+                limit,
+            } => {
+                let params = [
+                    (offset_param.as_str(), state.records.to_string()),
+                    (limit_param.as_str(), limit.to_string()),
+                ];
+                set_query_params(&mut url, &params);
+            }
+            Pagination::Cursor { cursor_param, .. } => {
+                if let Some(cursor) = state.cursor.take() {
+                    set_query_params(&mut url, &[(cursor_param.as_str(), cursor)]);
+                } else if state.pages > 0 {
+                    return None;
+                }
+            }
+            Pagination::Link => {
+                if state.pages > 0 {
+                    return state.next_url.take();
+                }
+            }
+        }
+        Some(url)
+    }
+
+    /// Reads the next cursor or the next link of the page fetched from the URL.
+    /// The next link is resolved against the URL, and it should have the same origin
+    /// as the base URL since the credentials are attached to the request.
+    pub(super) fn read_next(
+        &self,
+        state: &mut PageState,
+        url: &Url,
+        base_url: &Url,
+        headers: &HeaderMap,
+        data: &Value,
+    ) -> Result<(), Error> {
+        match self {
+            Pagination::Cursor { cursor_pointer, .. } => {
+                state.cursor = match data.pointer(cursor_pointer) {
+                    Some(Value::String(cursor)) if !cursor.is_empty() => Some(cursor.to_owned()),
+                    Some(Value::Number(cursor)) => Some(cursor.to_string()),
+                    _ => None,
+                };
+            }
+            Pagination::Link => {
+                let link = headers
+                    .get_all(LINK)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .find_map(parse_next_link);
+                state.next_url = if let Some(link) = link {
+                    let next_url = url.join(link)?;
+                    if next_url.origin() != base_url.origin() {
+                        let message = format!("the next link `{link}` has a different origin");
+                        return Err(Error::new(message));
+                    }
+                    Some(next_url)
+                } else {
+                    None
+                };
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Updates the state after a page has been fetched.
+    /// Returns `true` if there may be more pages.
+    pub(super) fn update_state(&self, state: &mut PageState, num_records: usize) -> bool {
+        state.pages += 1;
+        state.records += num_records;
+        match self {
+            Pagination::Page { page_size, .. } => {
+                num_records > 0 && page_size.map_or(true, |page_size| num_records >= page_size)
+            }
+            Pagination::Offset { limit, .. } => num_records >= *limit,
+            Pagination::Cursor { .. } => state.cursor.is_some(),
+            Pagination::Link => state.next_url.is_some(),
+        }
+    }
+}
+
+/// Sets the query params of the URL, replacing the existing values.
+fn set_query_params(url: &mut Url, params: &[(&str, String)]) {
+    let pairs = url
+        .query_pairs()
+        .filter(|(key, _)| params.iter().all(|(name, _)| key != name))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .extend_pairs(params);
+}
+
+/// Parses the URL with `rel="next"` in the `Link` header value.
+fn parse_next_link(header_value: &str) -> Option<&str> {
+    header_value.split(',').find_map(|link| {
+        let (url, params) = link.trim().split_once(';')?;
+        let is_next = params.split(';').any(|param| {
+            matches!(
+                param.trim().split_once('='),
+                Some(("rel", rel)) if rel.trim_matches('"').split_whitespace().any(|s| s == "next")
+            )
+        });
+        is_next.then(|| url.trim().trim_start_matches('<').trim_end_matches('>'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_next_link, PageState, Pagination};
+    use http::header::{HeaderMap, HeaderValue, LINK};
+    use serde_json::Value;
+    use url::Url;
+
+    #[test]
+    fn it_parses_next_link() {
+        let header_value = r#"<https://api.github.com/user/repos?page=3>; rel="next", <https://api.github.com/user/repos?page=50>; rel="last""#;
+        assert_eq!(
+            parse_next_link(header_value),
+            Some("https://api.github.com/user/repos?page=3")
+        );
+
+        let header_value = r#"<https://api.github.com/user/repos?page=1>; rel="prev""#;
+        assert_eq!(parse_next_link(header_value), None);
+    }
+
+    #[test]
+    fn it_sets_page_params() {
+        let pagination = Pagination::Offset {
+            offset_param: "offset".to_owned(),
+            limit_param: "limit".to_owned(),
+            limit: 10,
+        };
+        let url = Url::parse("https://example.com/items?offset=5&limit=20&q=zino").unwrap();
+        let mut state = PageState {
+            records: 30,
+            ..PageState::default()
+        };
+
+        let next_url = pagination.next_url(&url, &mut state).unwrap();
+        assert_eq!(next_url.query(), Some("q=zino&offset=30&limit=10"));
+    }
+
+    #[test]
+    fn it_follows_next_links() {
+        let pagination = Pagination::Link;
+        let base_url = Url::parse("https://example.com/api/").unwrap();
+        let url = base_url.join("items?page=1").unwrap();
+        let mut state = PageState::default();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            HeaderValue::from_static(r#"</api/items?page=2>; rel="next""#),
+        );
+        assert!(pagination
+            .read_next(&mut state, &url, &base_url, &headers, &Value::Null)
+            .is_ok());
+        assert!(pagination.update_state(&mut state, 10));
+        assert_eq!(
+            pagination.next_url(&url, &mut state).map(String::from),
+            Some("https://example.com/api/items?page=2".to_owned())
+        );
+
+        headers.insert(
+            LINK,
+            HeaderValue::from_static(r#"<https://evil.example.com/items?page=3>; rel="next""#),
+        );
+        assert!(pagination
+            .read_next(&mut state, &url, &base_url, &headers, &Value::Null)
+            .is_err());
+
+        headers.insert(
+            LINK,
+            HeaderValue::from_static(r#"<http://example.com/api/items?page=3>; rel="next""#),
+        );
+        assert!(pagination
+            .read_next(&mut state, &url, &base_url, &headers, &Value::Null)
+            .is_err());
+    }
+}
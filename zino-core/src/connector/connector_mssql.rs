@@ -1,5 +1,10 @@
-use super::{Connector, DataSource, DataSourceConnector::Mssql};
+use super::{
+    sqlx_common::{QueryBinder, SchemaQuery, SqlxQuery},
+    Connector, DataSource,
+    DataSourceConnector::Mssql,
+};
 use crate::{error::Error, extend::TomlTableExt, state::State};
+use serde_json::Value;
 use sqlx::mssql::{MssqlPool, MssqlPoolOptions};
 use std::time::Duration;
 use toml::Table;
@@ -33,5 +38,43 @@ impl Connector for MssqlPool {
         Ok(data_source)
     }
 
-    super::sqlx_common::impl_sqlx_connector!(MssqlPool);
+    super::sqlx_common::impl_sqlx_connector!(sqlx::Mssql);
+}
+
+impl QueryBinder for sqlx::Mssql {
+    const PLACEHOLDER: char = '@';
+
+    fn bind_value<'q>(query: SqlxQuery<'q, Self>, value: &'q Value) -> SqlxQuery<'q, Self> {
+        match value {
+            Value::Null => query.bind(Option::<String>::None),
+            Value::Bool(b) => query.bind(*b),
+            Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    query.bind(i)
+                } else {
+                    query.bind(n.as_f64())
+                }
+            }
+            Value::String(s) => query.bind(s.as_str()),
+            Value::Array(_) | Value::Object(_) => query.bind(value.to_string()),
+        }
+    }
+}
+
+impl SchemaQuery for sqlx::Mssql {
+    const LIST_TABLES: &'static str = "SELECT TABLE_NAME AS table_name \
+        FROM INFORMATION_SCHEMA.TABLES \
+        WHERE TABLE_SCHEMA = SCHEMA_NAME() ORDER BY TABLE_NAME;";
+
+    const DESCRIBE_TABLE: &'static str = "SELECT c.COLUMN_NAME AS column_name, \
+        c.DATA_TYPE AS data_type, c.IS_NULLABLE AS is_nullable, \
+        CAST(CASE WHEN EXISTS (SELECT 1 FROM INFORMATION_SCHEMA.TABLE_CONSTRAINTS tc \
+            JOIN INFORMATION_SCHEMA.KEY_COLUMN_USAGE kcu \
+            ON tc.CONSTRAINT_NAME = kcu.CONSTRAINT_NAME AND tc.TABLE_SCHEMA = kcu.TABLE_SCHEMA \
+            WHERE tc.CONSTRAINT_TYPE = 'PRIMARY KEY' AND tc.TABLE_SCHEMA = c.TABLE_SCHEMA \
+            AND tc.TABLE_NAME = c.TABLE_NAME AND kcu.COLUMN_NAME = c.COLUMN_NAME \
+        ) THEN 1 ELSE 0 END AS BIGINT) AS is_primary_key \
+        FROM INFORMATION_SCHEMA.COLUMNS c \
+        WHERE c.TABLE_SCHEMA = COALESCE(${schema}, SCHEMA_NAME()) \
+        AND c.TABLE_NAME = ${table} ORDER BY c.ORDINAL_POSITION;";
 }
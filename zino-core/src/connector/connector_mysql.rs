@@ -1,5 +1,10 @@
-use super::{Connector, DataSource, DataSourceConnector::MySql};
+use super::{
+    sqlx_common::{QueryBinder, SchemaQuery, SqlxQuery},
+    Connector, DataSource,
+    DataSourceConnector::MySql,
+};
 use crate::{error::Error, extend::TomlTableExt, state::State};
+use serde_json::Value;
 use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
 use std::time::Duration;
 use toml::Table;
@@ -33,5 +38,38 @@ impl Connector for MySqlPool {
         Ok(data_source)
     }
 
-    super::sqlx_common::impl_sqlx_connector!(MySqlPool);
+    super::sqlx_common::impl_sqlx_connector!(sqlx::MySql);
+}
+
+impl QueryBinder for sqlx::MySql {
+    const PLACEHOLDER: char = '?';
+
+    fn bind_value<'q>(query: SqlxQuery<'q, Self>, value: &'q Value) -> SqlxQuery<'q, Self> {
+        match value {
+            Value::Null => query.bind(Option::<String>::None),
+            Value::Bool(b) => query.bind(*b),
+            Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    query.bind(i)
+                } else {
+                    query.bind(n.as_f64())
+                }
+            }
+            Value::String(s) => query.bind(s.as_str()),
+            Value::Array(_) | Value::Object(_) => query.bind(value.to_string()),
+        }
+    }
+}
+
+impl SchemaQuery for sqlx::MySql {
+    const LIST_TABLES: &'static str = "SELECT table_name AS table_name \
+        FROM information_schema.tables \
+        WHERE table_schema = DATABASE() ORDER BY table_name;";
+
+    const DESCRIBE_TABLE: &'static str = "SELECT column_name AS column_name, \
+        column_type AS data_type, is_nullable AS is_nullable, \
+        column_key = 'PRI' AS is_primary_key \
+        FROM information_schema.columns \
+        WHERE table_schema = COALESCE(${schema}, DATABASE()) \
+        AND table_name = ${table} ORDER BY ordinal_position;";
 }
@@ -1,6 +1,14 @@
-use super::{Connector, DataSource, DataSourceConnector::Postgres};
+use super::{
+    sqlx_common::{QueryBinder, SchemaQuery, SqlxQuery},
+    Connector, DataSource,
+    DataSourceConnector::Postgres,
+};
 use crate::{error::Error, extend::TomlTableExt, state::State};
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use serde_json::Value;
+use sqlx::{
+    postgres::{PgPool, PgPoolOptions},
+    types::Json,
+};
 use std::time::Duration;
 use toml::Table;
 
@@ -33,5 +41,57 @@ impl Connector for PgPool {
         Ok(data_source)
     }
 
-    super::sqlx_common::impl_sqlx_connector!(PgPool);
+    super::sqlx_common::impl_sqlx_connector!(sqlx::Postgres);
+}
+
+impl QueryBinder for sqlx::Postgres {
+    const PLACEHOLDER: char = '$';
+
+    fn bind_value<'q>(query: SqlxQuery<'q, Self>, value: &'q Value) -> SqlxQuery<'q, Self> {
+        match value {
+            Value::Null => query.bind(Option::<String>::None),
+            Value::Bool(b) => query.bind(*b),
+            Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    query.bind(i)
+                } else {
+                    query.bind(n.as_f64())
+                }
+            }
+            Value::String(s) => query.bind(s.as_str()),
+            Value::Array(vec) => {
+                if vec.iter().all(|v| v.is_i64()) {
+                    query.bind(vec.iter().filter_map(|v| v.as_i64()).collect::<Vec<_>>())
+                } else if vec.iter().all(|v| v.is_number()) {
+                    query.bind(vec.iter().filter_map(|v| v.as_f64()).collect::<Vec<_>>())
+                } else if vec.iter().all(|v| v.is_boolean()) {
+                    query.bind(vec.iter().filter_map(|v| v.as_bool()).collect::<Vec<_>>())
+                } else if vec.iter().all(|v| v.is_string()) {
+                    let values = vec.iter().filter_map(|v| v.as_str().map(|s| s.to_owned()));
+                    query.bind(values.collect::<Vec<_>>())
+                } else {
+                    query.bind(Json(value))
+                }
+            }
+            Value::Object(_) => query.bind(Json(value)),
+        }
+    }
+}
+
+impl SchemaQuery for sqlx::Postgres {
+    const LIST_TABLES: &'static str = "SELECT table_name::text AS table_name \
+        FROM information_schema.tables \
+        WHERE table_schema = current_schema() ORDER BY table_name;";
+
+    const DESCRIBE_TABLE: &'static str = "SELECT c.column_name::text AS column_name, \
+        c.data_type::text AS data_type, c.is_nullable::text AS is_nullable, \
+        EXISTS (SELECT 1 FROM information_schema.table_constraints tc \
+            JOIN information_schema.key_column_usage kcu \
+            ON tc.constraint_name = kcu.constraint_name AND tc.table_schema = kcu.table_schema \
+            WHERE tc.constraint_type = 'PRIMARY KEY' AND tc.table_schema = c.table_schema \
+            AND tc.table_name = c.table_name AND kcu.column_name = c.column_name \
+        ) AS is_primary_key \
+        FROM information_schema.columns c \
+        WHERE c.table_schema = COALESCE(${schema}, current_schema()) \
+        AND c.table_name = ${table} ORDER BY c.ordinal_position;";
 }
@@ -1,5 +1,10 @@
-use super::{Connector, DataSource, DataSourceConnector::Sqlite};
+use super::{
+    sqlx_common::{QueryBinder, SchemaQuery, SqlxQuery},
+    Connector, DataSource,
+    DataSourceConnector::Sqlite,
+};
 use crate::{error::Error, extend::TomlTableExt};
+use serde_json::Value;
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use std::time::Duration;
 use toml::Table;
@@ -32,5 +37,34 @@ impl Connector for SqlitePool {
         Ok(data_source)
     }
 
-    super::sqlx_common::impl_sqlx_connector!(SqlitePool);
+    super::sqlx_common::impl_sqlx_connector!(sqlx::Sqlite);
+}
+
+impl QueryBinder for sqlx::Sqlite {
+    const PLACEHOLDER: char = '?';
+
+    fn bind_value<'q>(query: SqlxQuery<'q, Self>, value: &'q Value) -> SqlxQuery<'q, Self> {
+        match value {
+            Value::Null => query.bind(Option::<String>::None),
+            Value::Bool(b) => query.bind(*b),
+            Value::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    query.bind(i)
+                } else {
+                    query.bind(n.as_f64())
+                }
+            }
+            Value::String(s) => query.bind(s.as_str()),
+            Value::Array(_) | Value::Object(_) => query.bind(value.to_string()),
+        }
+    }
+}
+
+impl SchemaQuery for sqlx::Sqlite {
+    const LIST_TABLES: &'static str = "SELECT name AS table_name FROM sqlite_master \
+        WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name;";
+
+    const DESCRIBE_TABLE: &'static str = "SELECT name AS column_name, type AS data_type, \
+        \"notnull\" = 0 AS is_nullable, pk > 0 AS is_primary_key \
+        FROM pragma_table_info(${table}) ORDER BY cid;";
 }
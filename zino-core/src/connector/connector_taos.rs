@@ -1,10 +1,18 @@
-use super::{Connector, DataSource, DataSourceConnector::Taos};
-use crate::{error::Error, extend::TomlTableExt, format, state::State, Map, Record};
+use super::{ColumnInfo, Connector, DataSource, DataSourceConnector::Taos, TableInfo};
+use crate::{
+    error::Error,
+    extend::{AvroRecordExt, TomlTableExt},
+    format,
+    state::State,
+    Map, Record,
+};
 use futures::TryStreamExt;
 use serde::de::DeserializeOwned;
 use taos::{AsyncFetchable, AsyncQueryable, PoolBuilder, TBuilder, TaosBuilder, TaosPool};
 use toml::Table;
 
+// The `${param}` parameters are encoded as escaped literals, since the statement
+// interface of TDengine only supports the insertions.
 impl Connector for TaosPool {
     fn try_new_data_source(config: &Table) -> Result<DataSource, Error> {
         let name = config.get_str("name").unwrap_or("taos");
@@ -23,16 +31,22 @@ impl Connector for TaosPool {
         Ok(data_source)
     }
 
+    async fn check_availability(&self) -> Result<(), Error> {
+        let taos = self.get()?;
+        taos.exec("SELECT SERVER_STATUS();").await?;
+        Ok(())
+    }
+
     async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
         let taos = self.get()?;
-        let sql = format::format_query(query, params);
+        let sql = format::format_sql_query(query, params);
         let affected_rows = taos.exec(sql).await?;
         Ok(affected_rows.try_into().ok())
     }
 
     async fn query(&self, query: &str, params: Option<&Map>) -> Result<Vec<Record>, Error> {
         let taos = self.get()?;
-        let sql = format::format_query(query, params);
+        let sql = format::format_sql_query(query, params);
         let mut result = taos.query(sql).await?;
         let mut rows = result.rows();
         let mut records = Vec::new();
@@ -52,7 +66,7 @@ impl Connector for TaosPool {
         params: Option<&Map>,
     ) -> Result<Vec<T>, Error> {
         let taos = self.get()?;
-        let sql = format::format_query(query, params);
+        let sql = format::format_sql_query(query, params);
         let mut result = taos.query(sql).await?;
         let mut rows = result.rows();
         let mut data = Vec::new();
@@ -68,7 +82,7 @@ impl Connector for TaosPool {
 
     async fn query_one(&self, query: &str, params: Option<&Map>) -> Result<Option<Record>, Error> {
         let taos = self.get()?;
-        let sql = format::format_query(query, params);
+        let sql = format::format_sql_query(query, params);
         let mut result = taos.query(sql).await?;
         let data = result.rows().try_next().await?.map(|row| {
             let mut record = Record::new();
@@ -86,7 +100,7 @@ impl Connector for TaosPool {
         params: Option<&Map>,
     ) -> Result<Option<T>, Error> {
         let taos = self.get()?;
-        let sql = format::format_query(query, params);
+        let sql = format::format_sql_query(query, params);
         let mut result = taos.query(sql).await?;
         if let Some(row) = result.rows().try_next().await? {
             let mut map = Map::new();
@@ -98,4 +112,52 @@ impl Connector for TaosPool {
             Ok(None)
         }
     }
+
+    async fn list_tables(&self) -> Result<Vec<String>, Error> {
+        let mut tables = Vec::new();
+        for (query, field) in [
+            ("SHOW STABLES;", "stable_name"),
+            ("SHOW TABLES;", "table_name"),
+        ] {
+            let records = self.query(query, None).await?;
+            for record in records {
+                if let Some(table) = record.get_str(field) {
+                    tables.push(table.to_owned());
+                }
+            }
+        }
+        Ok(tables)
+    }
+
+    async fn describe_table(&self, table: &str) -> Result<TableInfo, Error> {
+        let is_valid = table
+            .split('.')
+            .all(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        if !is_valid {
+            return Err(Error::new(format!("table name `{table}` is invalid")));
+        }
+
+        let query = format!("DESCRIBE {table};");
+        let records = self.query(&query, None).await?;
+        if records.is_empty() {
+            return Err(Error::new(format!("table `{table}` does not exist")));
+        }
+
+        // The first column is always the timestamp as the primary key.
+        let mut columns = Vec::with_capacity(records.len());
+        for (index, record) in records.iter().enumerate() {
+            let column_name = record
+                .get_str("field")
+                .ok_or_else(|| Error::new("the `field` field should be a str"))?;
+            let type_name = record.get_str("type").unwrap_or("VARCHAR");
+            let primary_key = index == 0;
+            columns.push(ColumnInfo::new(
+                column_name,
+                type_name,
+                !primary_key,
+                primary_key,
+            ));
+        }
+        Ok(TableInfo::new(table, columns))
+    }
 }
@@ -1,12 +1,24 @@
 use self::DataSourceConnector::*;
-use super::Connector;
-use crate::{error::Error, extend::TomlTableExt, Map, Record};
+use super::{circuit_breaker::CircuitBreaker, CircuitState, Connector, TableInfo};
+use crate::{
+    error::Error,
+    extend::{JsonObjectExt, TomlTableExt},
+    Map, Record,
+};
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
 use toml::Table;
 
 #[cfg(feature = "connector-arrow")]
 use super::ArrowConnector;
+#[cfg(feature = "connector-clickhouse")]
+use super::ClickHouseConnector;
+#[cfg(feature = "connector-duckdb")]
+use super::DuckDBConnector;
 #[cfg(feature = "connector-http")]
-use super::HttpConnector;
+use super::{GraphQLConnector, HttpConnector};
 #[cfg(feature = "connector-mssql")]
 use sqlx::mssql::MssqlPool;
 #[cfg(feature = "connector-mysql")]
@@ -24,6 +36,15 @@ pub(super) enum DataSourceConnector {
     /// Apache Arrow
     #[cfg(feature = "connector-arrow")]
     Arrow(ArrowConnector),
+    /// ClickHouse
+    #[cfg(feature = "connector-clickhouse")]
+    ClickHouse(ClickHouseConnector),
+    /// DuckDB
+    #[cfg(feature = "connector-duckdb")]
+    DuckDB(DuckDBConnector),
+    /// GraphQL
+    #[cfg(feature = "connector-http")]
+    GraphQL(GraphQLConnector),
     /// HTTP
     #[cfg(feature = "connector-http")]
     Http(HttpConnector),
@@ -56,6 +77,8 @@ pub struct DataSource {
     catalog: String,
     /// Connector
     connector: DataSourceConnector,
+    /// Circuit breaker
+    breaker: CircuitBreaker,
 }
 
 impl DataSource {
@@ -74,6 +97,7 @@ impl DataSource {
             name: name.into(),
             catalog: catalog.into(),
             connector,
+            breaker: CircuitBreaker::default(),
         }
     }
 
@@ -83,6 +107,9 @@ impl DataSource {
     /// Currently, we have built-in support for the following protocols:
     ///
     /// - `arrow`
+    /// - `clickhouse`
+    /// - `duckdb`
+    /// - `graphql`
     /// - `http`
     /// - `mssql`
     /// - `mysql`
@@ -93,6 +120,12 @@ impl DataSource {
         let mut data_source = match protocol {
             #[cfg(feature = "connector-arrow")]
             "arrow" => ArrowConnector::try_new_data_source(config)?,
+            #[cfg(feature = "connector-clickhouse")]
+            "clickhouse" => ClickHouseConnector::try_new_data_source(config)?,
+            #[cfg(feature = "connector-duckdb")]
+            "duckdb" => DuckDBConnector::try_new_data_source(config)?,
+            #[cfg(feature = "connector-http")]
+            "graphql" => GraphQLConnector::try_new_data_source(config)?,
             #[cfg(feature = "connector-http")]
             "http" => HttpConnector::try_new_data_source(config)?,
             #[cfg(feature = "connector-mssql")]
@@ -111,7 +144,12 @@ impl DataSource {
             }
         };
         let source_type = config.get_str("type").unwrap_or(protocol);
+        let failure_threshold = config.get_u32("failure-threshold").unwrap_or(5);
+        let reset_timeout = config
+            .get_duration("reset-timeout")
+            .unwrap_or_else(|| Duration::from_secs(30));
         data_source.source_type = source_type.to_owned();
+        data_source.breaker = CircuitBreaker::new(failure_threshold, reset_timeout);
         Ok(data_source)
     }
 
@@ -137,6 +175,47 @@ impl DataSource {
         self.catalog.as_str()
     }
 
+    /// Returns the state of the circuit breaker.
+    #[inline]
+    pub fn circuit_state(&self) -> CircuitState {
+        self.breaker.state()
+    }
+
+    /// Returns `true` if the data source is available for requests.
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        self.breaker.state() != CircuitState::Open
+    }
+
+    /// Returns the status of the data source as a JSON object.
+    pub fn status(&self) -> Map {
+        let mut map = self.breaker.status();
+        map.upsert("name", self.name());
+        map.upsert("protocol", self.protocol());
+        map.upsert("type", self.source_type());
+        map.upsert("catalog", self.catalog());
+        map
+    }
+
+    /// Runs the future if the circuit is not open,
+    /// and records the result in the circuit breaker.
+    ///
+    /// A failed request is counted as a failure only if the subsequent availability check
+    /// also fails, so that the errors of the queries themselves, such as syntax errors,
+    /// constraint violations or unsupported operations, do not open the circuit.
+    async fn guard<T>(&self, future: impl Future<Output = Result<T, Error>>) -> Result<T, Error> {
+        self.breaker.check(self.name())?;
+        let result = future.await;
+        match &result {
+            Ok(_) => self.breaker.record_success(),
+            Err(_) => {
+                // The result is recorded in the circuit breaker by the health check.
+                self.check_availability().await.ok();
+            }
+        }
+        result
+    }
+
     /// Returns a reference to the inner connector if it is of type `ArrowConnector`,
     /// or `None` if it isn’t.
     #[cfg(feature = "connector-arrow")]
@@ -149,6 +228,18 @@ impl DataSource {
         }
     }
 
+    /// Returns a reference to the inner connector if it is of type `GraphQLConnector`,
+    /// or `None` if it isn’t.
+    #[cfg(feature = "connector-http")]
+    #[inline]
+    pub fn get_graphql_connector(&self) -> Option<&GraphQLConnector> {
+        if let GraphQL(connector) = &self.connector {
+            Some(connector)
+        } else {
+            None
+        }
+    }
+
     /// Returns a reference to the inner connector if it is of type `HttpConnector`,
     /// or `None` if it isn’t.
     #[cfg(feature = "connector-http")]
@@ -167,7 +258,10 @@ impl Connector for DataSource {
         let source_type = config.get_str("type").unwrap_or("unkown");
         let protocol = match source_type {
             "arrow" => "arrow",
-            "http" | "rest" | "graphql" => "http",
+            "clickhouse" => "clickhouse",
+            "duckdb" => "duckdb",
+            "graphql" => "graphql",
+            "http" | "rest" => "http",
             "mssql" => "mssql",
             "mysql" | "ceresdb" | "databend" | "mariadb" | "tidb" => "mysql",
             "postgres" | "citus" | "greptimedb" | "hologres" | "opengauss" | "postgis"
@@ -186,60 +280,186 @@ impl Connector for DataSource {
         Self::try_new(protocol, config)
     }
 
-    async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
-        match &self.connector {
+    async fn check_availability(&self) -> Result<(), Error> {
+        let start_time = Instant::now();
+        let result = match &self.connector {
             #[cfg(feature = "connector-arrow")]
-            Arrow(connector) => connector.execute(query, params).await,
+            Arrow(connector) => connector.check_availability().await,
+            #[cfg(feature = "connector-clickhouse")]
+            ClickHouse(connector) => connector.check_availability().await,
+            #[cfg(feature = "connector-duckdb")]
+            DuckDB(connector) => connector.check_availability().await,
+            #[cfg(feature = "connector-http")]
+            GraphQL(connector) => connector.check_availability().await,
             #[cfg(feature = "connector-http")]
-            Http(connector) => connector.execute(query, params).await,
+            Http(connector) => connector.check_availability().await,
             #[cfg(feature = "connector-mssql")]
-            Mssql(pool) => pool.execute(query, params).await,
+            Mssql(pool) => pool.check_availability().await,
             #[cfg(feature = "connector-mysql")]
-            MySql(pool) => pool.execute(query, params).await,
+            MySql(pool) => pool.check_availability().await,
             #[cfg(feature = "connector-postgres")]
-            Postgres(pool) => pool.execute(query, params).await,
+            Postgres(pool) => pool.check_availability().await,
             #[cfg(feature = "connector-sqlite")]
-            Sqlite(pool) => pool.execute(query, params).await,
+            Sqlite(pool) => pool.check_availability().await,
             #[cfg(feature = "connector-taos")]
-            Taos(pool) => pool.execute(query, params).await,
-        }
+            Taos(pool) => pool.check_availability().await,
+        };
+        let latency = start_time.elapsed();
+
+        // Emit metrics.
+        let labels = [
+            ("name", self.name().to_owned()),
+            ("type", self.source_type().to_owned()),
+        ];
+        let available = if result.is_ok() { 1.0 } else { 0.0 };
+        metrics::gauge!("zino_data_source_available", available, &labels);
+        metrics::histogram!(
+            "zino_data_source_health_check_duration_seconds",
+            latency.as_secs_f64(),
+            &labels,
+        );
+
+        self.breaker.record_health_check(result.as_ref().map(|_| latency));
+        result
+    }
+
+    async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
+        self.guard(async {
+            match &self.connector {
+                #[cfg(feature = "connector-arrow")]
+                Arrow(connector) => connector.execute(query, params).await,
+                #[cfg(feature = "connector-clickhouse")]
+                ClickHouse(connector) => connector.execute(query, params).await,
+                #[cfg(feature = "connector-duckdb")]
+                DuckDB(connector) => connector.execute(query, params).await,
+                #[cfg(feature = "connector-http")]
+                GraphQL(connector) => connector.execute(query, params).await,
+                #[cfg(feature = "connector-http")]
+                Http(connector) => connector.execute(query, params).await,
+                #[cfg(feature = "connector-mssql")]
+                Mssql(pool) => pool.execute(query, params).await,
+                #[cfg(feature = "connector-mysql")]
+                MySql(pool) => pool.execute(query, params).await,
+                #[cfg(feature = "connector-postgres")]
+                Postgres(pool) => pool.execute(query, params).await,
+                #[cfg(feature = "connector-sqlite")]
+                Sqlite(pool) => pool.execute(query, params).await,
+                #[cfg(feature = "connector-taos")]
+                Taos(pool) => pool.execute(query, params).await,
+            }
+        })
+        .await
     }
 
     async fn query(&self, query: &str, params: Option<&Map>) -> Result<Vec<Record>, Error> {
-        match &self.connector {
-            #[cfg(feature = "connector-arrow")]
-            Arrow(connector) => connector.query(query, params).await,
-            #[cfg(feature = "connector-http")]
-            Http(connector) => connector.query(query, params).await,
-            #[cfg(feature = "connector-mssql")]
-            Mssql(pool) => pool.query(query, params).await,
-            #[cfg(feature = "connector-mysql")]
-            MySql(pool) => pool.query(query, params).await,
-            #[cfg(feature = "connector-postgres")]
-            Postgres(pool) => pool.query(query, params).await,
-            #[cfg(feature = "connector-sqlite")]
-            Sqlite(pool) => pool.query(query, params).await,
-            #[cfg(feature = "connector-taos")]
-            Taos(pool) => pool.query(query, params).await,
-        }
+        self.guard(async {
+            match &self.connector {
+                #[cfg(feature = "connector-arrow")]
+                Arrow(connector) => connector.query(query, params).await,
+                #[cfg(feature = "connector-clickhouse")]
+                ClickHouse(connector) => connector.query(query, params).await,
+                #[cfg(feature = "connector-duckdb")]
+                DuckDB(connector) => connector.query(query, params).await,
+                #[cfg(feature = "connector-http")]
+                GraphQL(connector) => connector.query(query, params).await,
+                #[cfg(feature = "connector-http")]
+                Http(connector) => connector.query(query, params).await,
+                #[cfg(feature = "connector-mssql")]
+                Mssql(pool) => pool.query(query, params).await,
+                #[cfg(feature = "connector-mysql")]
+                MySql(pool) => pool.query(query, params).await,
+                #[cfg(feature = "connector-postgres")]
+                Postgres(pool) => pool.query(query, params).await,
+                #[cfg(feature = "connector-sqlite")]
+                Sqlite(pool) => pool.query(query, params).await,
+                #[cfg(feature = "connector-taos")]
+                Taos(pool) => pool.query(query, params).await,
+            }
+        })
+        .await
     }
 
     async fn query_one(&self, query: &str, params: Option<&Map>) -> Result<Option<Record>, Error> {
-        match &self.connector {
-            #[cfg(feature = "connector-arrow")]
-            Arrow(connector) => connector.query_one(query, params).await,
-            #[cfg(feature = "connector-http")]
-            Http(connector) => connector.query_one(query, params).await,
-            #[cfg(feature = "connector-mssql")]
-            Mssql(pool) => pool.query_one(query, params).await,
-            #[cfg(feature = "connector-mysql")]
-            MySql(pool) => pool.query_one(query, params).await,
-            #[cfg(feature = "connector-postgres")]
-            Postgres(pool) => pool.query_one(query, params).await,
-            #[cfg(feature = "connector-sqlite")]
-            Sqlite(pool) => pool.query_one(query, params).await,
-            #[cfg(feature = "connector-taos")]
-            Taos(pool) => pool.query_one(query, params).await,
-        }
+        self.guard(async {
+            match &self.connector {
+                #[cfg(feature = "connector-arrow")]
+                Arrow(connector) => connector.query_one(query, params).await,
+                #[cfg(feature = "connector-clickhouse")]
+                ClickHouse(connector) => connector.query_one(query, params).await,
+                #[cfg(feature = "connector-duckdb")]
+                DuckDB(connector) => connector.query_one(query, params).await,
+                #[cfg(feature = "connector-http")]
+                GraphQL(connector) => connector.query_one(query, params).await,
+                #[cfg(feature = "connector-http")]
+                Http(connector) => connector.query_one(query, params).await,
+                #[cfg(feature = "connector-mssql")]
+                Mssql(pool) => pool.query_one(query, params).await,
+                #[cfg(feature = "connector-mysql")]
+                MySql(pool) => pool.query_one(query, params).await,
+                #[cfg(feature = "connector-postgres")]
+                Postgres(pool) => pool.query_one(query, params).await,
+                #[cfg(feature = "connector-sqlite")]
+                Sqlite(pool) => pool.query_one(query, params).await,
+                #[cfg(feature = "connector-taos")]
+                Taos(pool) => pool.query_one(query, params).await,
+            }
+        })
+        .await
+    }
+
+    async fn list_tables(&self) -> Result<Vec<String>, Error> {
+        self.guard(async {
+            match &self.connector {
+                #[cfg(feature = "connector-arrow")]
+                Arrow(connector) => connector.list_tables().await,
+                #[cfg(feature = "connector-clickhouse")]
+                ClickHouse(connector) => connector.list_tables().await,
+                #[cfg(feature = "connector-duckdb")]
+                DuckDB(connector) => connector.list_tables().await,
+                #[cfg(feature = "connector-http")]
+                GraphQL(connector) => connector.list_tables().await,
+                #[cfg(feature = "connector-http")]
+                Http(connector) => connector.list_tables().await,
+                #[cfg(feature = "connector-mssql")]
+                Mssql(pool) => pool.list_tables().await,
+                #[cfg(feature = "connector-mysql")]
+                MySql(pool) => pool.list_tables().await,
+                #[cfg(feature = "connector-postgres")]
+                Postgres(pool) => pool.list_tables().await,
+                #[cfg(feature = "connector-sqlite")]
+                Sqlite(pool) => pool.list_tables().await,
+                #[cfg(feature = "connector-taos")]
+                Taos(pool) => pool.list_tables().await,
+            }
+        })
+        .await
+    }
+
+    async fn describe_table(&self, table: &str) -> Result<TableInfo, Error> {
+        self.guard(async {
+            match &self.connector {
+                #[cfg(feature = "connector-arrow")]
+                Arrow(connector) => connector.describe_table(table).await,
+                #[cfg(feature = "connector-clickhouse")]
+                ClickHouse(connector) => connector.describe_table(table).await,
+                #[cfg(feature = "connector-duckdb")]
+                DuckDB(connector) => connector.describe_table(table).await,
+                #[cfg(feature = "connector-http")]
+                GraphQL(connector) => connector.describe_table(table).await,
+                #[cfg(feature = "connector-http")]
+                Http(connector) => connector.describe_table(table).await,
+                #[cfg(feature = "connector-mssql")]
+                Mssql(pool) => pool.describe_table(table).await,
+                #[cfg(feature = "connector-mysql")]
+                MySql(pool) => pool.describe_table(table).await,
+                #[cfg(feature = "connector-postgres")]
+                Postgres(pool) => pool.describe_table(table).await,
+                #[cfg(feature = "connector-sqlite")]
+                Sqlite(pool) => pool.describe_table(table).await,
+                #[cfg(feature = "connector-taos")]
+                Taos(pool) => pool.describe_table(table).await,
+            }
+        })
+        .await
     }
 }
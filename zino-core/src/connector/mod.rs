@@ -7,7 +7,9 @@
 //! | `arrow`          | Apache Arrow           | `connector-arrow`      |
 //! | `ceresdb`        | CeresDB                | `connector-mysql`      |
 //! | `citus`          | Citus                  | `connector-postgres`   |
+//! | `clickhouse`     | ClickHouse             | `connector-clickhouse` |
 //! | `databend`       | Databend               | `connector-mysql`      |
+//! | `duckdb`         | DuckDB                 | `connector-duckdb`     |
 //! | `graphql`        | GraphQL API            | `connector-http`       |
 //! | `greptimedb`     | GreptimeDB             | `connector-postgres`   |
 //! | `hologres`       | Aliyun Hologres        | `connector-postgres`   |
@@ -24,18 +26,78 @@
 //! | `tidb`           | TiDB                   | `connector-mysql`      |
 //! | `timescaledb`    | TimescaleDB            | `connector-postgres`   |
 //!
+//! ## Query parameters
+//!
+//! The query parameter is represented as `${param}`. For SQL data sources,
+//! it is translated into a native binding parameter for the dialect
+//! (`?` for DuckDB, MySQL and SQLite, `$N` for PostgreSQL and `@pN` for MSSQL),
+//! and the value is encoded according to its type. For ClickHouse, it is translated into
+//! a native query parameter `{pN:Type}` whose type is inferred from the value.
+//! TDengine is queried without binding parameters since its statement interface
+//! only supports the insertions, so the value is encoded as an escaped literal instead.
+//! For GraphQL, the params are sent as the variables, and the `${param}`s are resolved
+//! in the `variables` of the configuration instead of the query document.
+//! For other data sources, the parameter is interpolated into the query directly.
+//!
+//! ## Avro schemas
+//!
+//! The records can be resolved with a reader schema by [`Connector::query_with_schema`],
+//! following the Avro schema evolution rules: missing fields are filled with the defaults,
+//! values are promoted to the reader types, and fields can be matched by aliases.
+//! The schema of a model, i.e. [`Schema::schema()`](crate::database::Schema::schema),
+//! can be used as the reader schema, and [`Connector::write_avro`] writes the results
+//! as an Avro object container.
+//!
+//! ## Schema introspection
+//!
+//! The tables of a data source can be listed by [`Connector::list_tables`],
+//! and the columns of a table can be described by [`Connector::describe_table`],
+//! which returns a [`TableInfo`] convertible into an Avro or Arrow schema.
+//! It is supported for the SQL data sources via sqlx, TDengine and Apache Arrow.
+//!
+//! ## Health checks
+//!
+//! Each data source has a circuit breaker which opens after `failure-threshold`
+//! consecutive failures (5 by default), so that requests fail fast until
+//! `reset-timeout` (30s by default) has elapsed and a trial request is allowed.
+//! If a data source fails to be constructed, the reconnection is delayed
+//! with an exponential backoff up to `reconnect-max-backoff` (5m by default).
+//! The availability of data sources can be checked periodically by adding
+//! [`GlobalConnector::check_health`] to the async cron jobs.
+//!
 
-use crate::{error::Error, extend::TomlTableExt, state::State, Map, Record};
-use apache_avro::types::Value;
+use crate::{
+    datetime::DateTime,
+    error::Error,
+    extend::{JsonObjectExt, TomlTableExt},
+    state::State,
+    BoxFuture, Map, Record, Uuid,
+};
+use apache_avro::{types::Value, Schema};
+use parking_lot::Mutex;
 use serde::de::DeserializeOwned;
-use std::{collections::HashMap, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    io::Write,
+    sync::{LazyLock, OnceLock},
+    time::{Duration, Instant},
+};
 use toml::Table;
 
+mod avro_schema;
+mod circuit_breaker;
 mod data_source;
+mod table_info;
 
 /// Supported connectors.
 #[cfg(feature = "connector-arrow")]
 mod connector_arrow;
+#[cfg(feature = "connector-clickhouse")]
+mod connector_clickhouse;
+#[cfg(feature = "connector-duckdb")]
+mod connector_duckdb;
+#[cfg(feature = "connector-http")]
+mod connector_graphql;
 #[cfg(feature = "connector-http")]
 mod connector_http;
 #[cfg(feature = "connector-mssql")]
@@ -57,12 +119,22 @@ mod connector_taos;
 ))]
 mod sqlx_common;
 
+pub use circuit_breaker::CircuitState;
 pub use data_source::DataSource;
+pub use table_info::{ColumnInfo, TableInfo};
 use data_source::DataSourceConnector;
 
 #[cfg(feature = "connector-arrow")]
-pub use connector_arrow::{ArrowConnector, DataFrameExecutor};
+pub use connector_arrow::{ArrowConnector, DataFrameExecutor, StreamTable, TableFunction};
 
+#[cfg(feature = "connector-clickhouse")]
+pub use connector_clickhouse::ClickHouseConnector;
+
+#[cfg(feature = "connector-duckdb")]
+pub use connector_duckdb::DuckDBConnector;
+
+#[cfg(feature = "connector-http")]
+pub use connector_graphql::GraphQLConnector;
 #[cfg(feature = "connector-http")]
 pub use connector_http::HttpConnector;
 
@@ -72,6 +144,9 @@ pub trait Connector {
     /// returning an error if it fails.
     fn try_new_data_source(config: &Table) -> Result<DataSource, Error>;
 
+    /// Checks the availability of the data source, returning an error if it is unavailable.
+    async fn check_availability(&self) -> Result<(), Error>;
+
     /// Executes the query and returns the total number of rows affected.
     async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error>;
 
@@ -85,10 +160,7 @@ pub trait Connector {
         params: Option<&Map>,
     ) -> Result<Vec<T>, Error> {
         let data = self.query(query, params).await?;
-        let value = data
-            .into_iter()
-            .map(|record| Value::Record(record))
-            .collect::<Vec<_>>();
+        let value = data.into_iter().map(Value::Record).collect::<Vec<_>>();
         apache_avro::from_value(&Value::Array(value)).map_err(|err| err.into())
     }
 
@@ -108,6 +180,56 @@ pub trait Connector {
             Ok(None)
         }
     }
+
+    /// Executes the query and resolves the records with the reader schema
+    /// according to the Avro schema evolution rules.
+    async fn query_with_schema(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+        schema: &Schema,
+    ) -> Result<Vec<Record>, Error> {
+        let data = self.query(query, params).await?;
+        data.into_iter()
+            .map(|record| avro_schema::resolve_record(record, schema))
+            .collect()
+    }
+
+    /// Executes the query and parses it as `Vec<T>` with the reader schema.
+    async fn query_as_with_schema<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+        schema: &Schema,
+    ) -> Result<Vec<T>, Error> {
+        let data = self.query_with_schema(query, params, schema).await?;
+        let value = data.into_iter().map(Value::Record).collect::<Vec<_>>();
+        apache_avro::from_value(&Value::Array(value)).map_err(|err| err.into())
+    }
+
+    /// Executes the query and writes the records as an Avro object container
+    /// with the schema, returning the number of bytes written.
+    async fn write_avro<W: Write>(
+        &self,
+        query: &str,
+        params: Option<&Map>,
+        schema: &Schema,
+        writer: W,
+    ) -> Result<usize, Error> {
+        let data = self.query_with_schema(query, params, schema).await?;
+        avro_schema::write_records(data, schema, writer)
+    }
+
+    /// Lists the names of tables in the data source.
+    async fn list_tables(&self) -> Result<Vec<String>, Error> {
+        Err(Error::new("listing tables is unsupported for the data source"))
+    }
+
+    /// Describes the columns of a table in the data source.
+    async fn describe_table(&self, table: &str) -> Result<TableInfo, Error> {
+        let message = format!("describing the table `{table}` is unsupported for the data source");
+        Err(Error::new(message))
+    }
 }
 
 /// Global connector to data sources.
@@ -117,22 +239,181 @@ pub struct GlobalConnector;
 impl GlobalConnector {
     /// Gets the data source for the specific database service.
     #[inline]
-    pub fn get(name: &'static str) -> Option<&'static DataSource> {
-        GLOBAL_CONNECTOR.get(name)
+    pub fn get(name: &str) -> Option<&'static DataSource> {
+        Self::try_get(name).ok()
+    }
+
+    /// Attempts to get the data source for the specific database service.
+    /// If the data source has failed to be constructed, it will try to reconnect.
+    pub fn try_get(name: &str) -> Result<&'static DataSource, Error> {
+        let entry = GLOBAL_CONNECTOR
+            .get(name)
+            .ok_or_else(|| Error::new(format!("data source `{name}` does not exist")))?;
+        entry.try_get()
+    }
+
+    /// Checks the availability of all the data sources.
+    pub async fn check_availability() -> Vec<(&'static str, Result<(), Error>)> {
+        let mut results = Vec::with_capacity(GLOBAL_CONNECTOR.len());
+        for (&name, entry) in GLOBAL_CONNECTOR.iter() {
+            let result = match entry.try_get() {
+                Ok(data_source) => data_source.check_availability().await,
+                Err(err) => Err(err),
+            };
+            results.push((name, result));
+        }
+        results
+    }
+
+    /// An async cron job to check the availability of all the data sources.
+    pub fn check_health(job_id: Uuid, job_data: &mut Map, _last_tick: DateTime) -> BoxFuture {
+        Box::pin(async move {
+            for (name, result) in Self::check_availability().await {
+                if let Err(err) = result {
+                    tracing::warn!("job {job_id}: data source `{name}` is unavailable: {err}");
+                }
+            }
+            job_data.insert("data_sources".to_owned(), Self::status().into());
+        })
+    }
+
+    /// Returns the status of all the data sources.
+    pub fn status() -> Vec<Map> {
+        let mut entries = GLOBAL_CONNECTOR.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(name, _)| **name);
+        entries
+            .into_iter()
+            .map(|(name, entry)| entry.status(name))
+            .collect()
+    }
+}
+
+/// A data source which is constructed lazily.
+struct LazyDataSource {
+    /// Name.
+    name: &'static str,
+    /// Configuration.
+    config: &'static Table,
+    /// Data source.
+    data_source: OnceLock<DataSource>,
+    /// Failures of the construction.
+    failures: Mutex<ConnectFailures>,
+}
+
+/// Failures of constructing a data source.
+#[derive(Debug, Default)]
+struct ConnectFailures {
+    /// Number of consecutive failures.
+    count: u32,
+    /// Time when the next construction is allowed.
+    retry_at: Option<Instant>,
+    /// Error message of the last construction.
+    last_error: Option<String>,
+}
+
+impl LazyDataSource {
+    /// Creates a new instance.
+    fn new(name: &'static str, config: &'static Table) -> Self {
+        Self {
+            name,
+            config,
+            data_source: OnceLock::new(),
+            failures: Mutex::new(ConnectFailures::default()),
+        }
     }
+
+    /// Attempts to get the data source, constructing it if necessary.
+    /// After a failed construction, the reconnection is delayed with an exponential backoff
+    /// from `1s` up to `reconnect-max-backoff` (`5m` by default).
+    fn try_get(&self) -> Result<&DataSource, Error> {
+        if let Some(data_source) = self.data_source.get() {
+            return Ok(data_source);
+        }
+        {
+            let failures = self.failures.lock();
+            if let Some(retry_at) = failures.retry_at
+                && retry_at > Instant::now()
+            {
+                let name = self.name;
+                let message = format!("data source `{name}` is unavailable: waiting to reconnect");
+                if let Some(last_error) = failures.last_error.clone() {
+                    return Err(Error::with_source(message, Error::new(last_error)));
+                }
+                return Err(Error::new(message));
+            }
+        }
+        self.data_source
+            .get_or_try_init(|| DataSource::try_new_data_source(self.config))
+            .map_err(|err| {
+                let max_backoff = self
+                    .config
+                    .get_duration("reconnect-max-backoff")
+                    .unwrap_or(MAX_RECONNECT_BACKOFF);
+                let mut failures = self.failures.lock();
+                failures.count = failures.count.saturating_add(1);
+                failures.retry_at =
+                    Some(Instant::now() + reconnect_backoff(failures.count, max_backoff));
+                failures.last_error = Some(err.message().to_owned());
+                err
+            })
+    }
+
+    /// Returns the status of the data source.
+    fn status(&self, name: &str) -> Map {
+        if let Some(data_source) = self.data_source.get() {
+            data_source.status()
+        } else {
+            let mut map = Map::new();
+            map.upsert("name", name);
+            map.upsert("type", self.config.get_str("type").unwrap_or("unkown"));
+            map.upsert("available", false);
+            map.upsert("circuit_state", CircuitState::Open.as_str());
+            if let Some(last_error) = self.failures.lock().last_error.as_deref() {
+                map.upsert("last_error", circuit_breaker::sanitize_error(last_error));
+            }
+            map
+        }
+    }
+}
+
+/// Returns the backoff before the next reconnection after the number of failures.
+fn reconnect_backoff(failures: u32, max_backoff: Duration) -> Duration {
+    let exponent = failures.saturating_sub(1).min(16);
+    Duration::from_secs(1 << exponent).min(max_backoff)
 }
 
+/// Max backoff of the reconnection.
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
 /// Global connector.
-static GLOBAL_CONNECTOR: LazyLock<HashMap<&'static str, DataSource>> = LazyLock::new(|| {
+static GLOBAL_CONNECTOR: LazyLock<HashMap<&'static str, LazyDataSource>> = LazyLock::new(|| {
     let mut data_sources = HashMap::new();
     if let Some(connectors) = State::shared().config().get_array("connector") {
         for connector in connectors.iter().filter_map(|v| v.as_table()) {
             let data_source_type = connector.get_str("type").unwrap_or("unkown");
             let name = connector.get_str("name").unwrap_or(data_source_type);
-            let data_source = DataSource::try_new_data_source(connector)
-                .unwrap_or_else(|err| panic!("fail to connect data source `{name}`: {err}"));
+            let data_source = LazyDataSource::new(name, connector);
+            if let Err(err) = data_source.try_get() {
+                tracing::error!("fail to connect data source `{name}`: {err}");
+            }
             data_sources.insert(name, data_source);
         }
     }
     data_sources
 });
+
+#[cfg(test)]
+mod tests {
+    use super::reconnect_backoff;
+    use std::time::Duration;
+
+    #[test]
+    fn it_backs_off_exponentially() {
+        let max_backoff = Duration::from_secs(60);
+        assert_eq!(reconnect_backoff(1, max_backoff), Duration::from_secs(1));
+        assert_eq!(reconnect_backoff(2, max_backoff), Duration::from_secs(2));
+        assert_eq!(reconnect_backoff(5, max_backoff), Duration::from_secs(16));
+        assert_eq!(reconnect_backoff(7, max_backoff), max_backoff);
+        assert_eq!(reconnect_backoff(u32::MAX, max_backoff), max_backoff);
+    }
+}
@@ -1,13 +1,53 @@
-use crate::{error::Error, format, Map, Record};
+use super::{Connector, TableInfo};
+use crate::{
+    error::Error,
+    extend::{AvroRecordExt, JsonObjectExt},
+    format, Map, Record,
+};
 use apache_avro::types::Value;
 use futures::TryStreamExt;
 use serde::{
     de::DeserializeOwned,
     ser::{self, Serialize, SerializeMap, Serializer},
 };
-use sqlx::{database::HasValueRef, Column, ColumnIndex, Database, Decode, Row, TypeInfo, ValueRef};
+use serde_json::Value as JsonValue;
+use sqlx::{
+    database::{HasArguments, HasValueRef},
+    query::Query,
+    Column, ColumnIndex, Connection, Database, Decode, Row, TypeInfo, ValueRef,
+};
 use std::borrow::Cow;
 
+/// A query with the arguments for the database.
+pub(super) type SqlxQuery<'q, DB> = Query<'q, DB, <DB as HasArguments<'q>>::Arguments>;
+
+/// Binding JSON values as native query parameters for the database.
+pub(super) trait QueryBinder: Database {
+    /// Placeholder of the binding parameters.
+    const PLACEHOLDER: char;
+
+    /// Binds a JSON value to the query with the type-aware encoding.
+    fn bind_value<'q>(query: SqlxQuery<'q, Self>, value: &'q JsonValue) -> SqlxQuery<'q, Self>;
+}
+
+/// SQL statements for the schema introspection of the database.
+pub(super) trait SchemaQuery: Database {
+    /// SQL statement to list the tables, which returns the `table_name` column.
+    const LIST_TABLES: &'static str;
+
+    /// SQL statement to describe a table with the parameters `${schema}` and `${table}`,
+    /// which returns the columns `column_name`, `data_type`, `is_nullable` and `is_primary_key`.
+    const DESCRIBE_TABLE: &'static str;
+}
+
+/// Builds a query with the binding values.
+pub(super) fn bind_query<'q, DB: QueryBinder>(
+    sql: &'q str,
+    values: Vec<&'q JsonValue>,
+) -> SqlxQuery<'q, DB> {
+    values.into_iter().fold(sqlx::query(sql), DB::bind_value)
+}
+
 /// A generic struct for the row.
 pub(super) struct SerializeRow<R: Row>(pub(super) R);
 
@@ -53,17 +93,22 @@ fn map_serialize<'r, M: SerializeMap, DB: Database, T: Decode<'r, DB> + Serializ
     map.serialize_entry(key, &value)
 }
 
-pub(super) macro impl_sqlx_connector($pool:ty) {
+pub(super) macro impl_sqlx_connector($db:ty) {
+    async fn check_availability(&self) -> Result<(), Error> {
+        let mut connection = self.acquire().await?;
+        connection.ping().await.map_err(Error::from)
+    }
+
     async fn execute(&self, query: &str, params: Option<&Map>) -> Result<Option<u64>, Error> {
-        let sql = format::format_query(query, params);
-        let query = sqlx::query(sql.as_ref());
+        let (sql, values) = format::prepare_sql_query(query, params, <$db>::PLACEHOLDER);
+        let query = bind_query::<$db>(sql.as_ref(), values);
         let query_result = query.execute(self).await?;
         Ok(Some(query_result.rows_affected()))
     }
 
     async fn query(&self, query: &str, params: Option<&Map>) -> Result<Vec<Record>, Error> {
-        let sql = format::format_query(query, params);
-        let query = sqlx::query(sql.as_ref());
+        let (sql, values) = format::prepare_sql_query(query, params, <$db>::PLACEHOLDER);
+        let query = bind_query::<$db>(sql.as_ref(), values);
         let mut rows = query.fetch(self);
         let mut records = Vec::new();
         while let Some(row) = rows.try_next().await? {
@@ -80,8 +125,8 @@ pub(super) macro impl_sqlx_connector($pool:ty) {
         query: &str,
         params: Option<&Map>,
     ) -> Result<Vec<T>, Error> {
-        let sql = format::format_query(query, params);
-        let query = sqlx::query(sql.as_ref());
+        let (sql, values) = format::prepare_sql_query(query, params, <$db>::PLACEHOLDER);
+        let query = bind_query::<$db>(sql.as_ref(), values);
         let mut rows = query.fetch(self);
         let mut data = Vec::new();
         while let Some(row) = rows.try_next().await? {
@@ -93,8 +138,8 @@ pub(super) macro impl_sqlx_connector($pool:ty) {
     }
 
     async fn query_one(&self, query: &str, params: Option<&Map>) -> Result<Option<Record>, Error> {
-        let sql = format::format_query(query, params);
-        let query = sqlx::query(sql.as_ref());
+        let (sql, values) = format::prepare_sql_query(query, params, <$db>::PLACEHOLDER);
+        let query = bind_query::<$db>(sql.as_ref(), values);
         let data = if let Some(row) = query.fetch_optional(self).await? {
             let value = apache_avro::to_value(&SerializeRow(row))?;
             if let Value::Record(record) = value {
@@ -113,8 +158,8 @@ pub(super) macro impl_sqlx_connector($pool:ty) {
         query: &str,
         params: Option<&Map>,
     ) -> Result<Option<T>, Error> {
-        let sql = format::format_query(query, params);
-        let query = sqlx::query(sql.as_ref());
+        let (sql, values) = format::prepare_sql_query(query, params, <$db>::PLACEHOLDER);
+        let query = bind_query::<$db>(sql.as_ref(), values);
         if let Some(row) = query.fetch_optional(self).await? {
             let json_value = serde_json::to_value(&SerializeRow(row))?;
             serde_json::from_value(json_value).map_err(Error::from)
@@ -122,4 +167,27 @@ pub(super) macro impl_sqlx_connector($pool:ty) {
             Ok(None)
         }
     }
+
+    async fn list_tables(&self) -> Result<Vec<String>, Error> {
+        let records = Connector::query(self, <$db>::LIST_TABLES, None).await?;
+        let tables = records
+            .iter()
+            .filter_map(|record| record.get_str("table_name"))
+            .map(|table| table.to_owned())
+            .collect();
+        Ok(tables)
+    }
+
+    async fn describe_table(&self, table: &str) -> Result<TableInfo, Error> {
+        let mut params = Map::new();
+        if let Some((schema, table_name)) = table.rsplit_once('.') {
+            params.upsert("schema", schema);
+            params.upsert("table", table_name);
+        } else {
+            params.upsert("table", table);
+        }
+
+        let records = Connector::query(self, <$db>::DESCRIBE_TABLE, Some(&params)).await?;
+        TableInfo::try_from_records(table, &records)
+    }
 }
@@ -0,0 +1,360 @@
+use crate::{error::Error, extend::AvroRecordExt, Record};
+use apache_avro::{types::Value as AvroValue, Schema};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Information of a table column.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ColumnInfo {
+    /// Column name.
+    name: String,
+    /// Native type name of the column.
+    type_name: String,
+    /// A flag indicating whether the column is nullable.
+    nullable: bool,
+    /// A flag indicating whether the column is a part of the primary key.
+    primary_key: bool,
+}
+
+impl ColumnInfo {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(
+        name: impl Into<String>,
+        type_name: impl Into<String>,
+        nullable: bool,
+        primary_key: bool,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            type_name: type_name.into(),
+            nullable,
+            primary_key,
+        }
+    }
+
+    /// Returns the column name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the native type name of the column.
+    #[inline]
+    pub fn type_name(&self) -> &str {
+        self.type_name.as_str()
+    }
+
+    /// Returns `true` if the column is nullable.
+    #[inline]
+    pub fn is_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    /// Returns `true` if the column is a part of the primary key.
+    #[inline]
+    pub fn is_primary_key(&self) -> bool {
+        self.primary_key
+    }
+}
+
+/// Information of a table in the data source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableInfo {
+    /// Table name.
+    name: String,
+    /// Columns.
+    columns: Vec<ColumnInfo>,
+}
+
+impl TableInfo {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, columns: Vec<ColumnInfo>) -> Self {
+        Self {
+            name: name.into(),
+            columns,
+        }
+    }
+
+    /// Creates a new instance from the records with the fields
+    /// `column_name`, `data_type`, `is_nullable` and `is_primary_key`.
+    pub(super) fn try_from_records(name: &str, records: &[Record]) -> Result<Self, Error> {
+        if records.is_empty() {
+            return Err(Error::new(format!("table `{name}` does not exist")));
+        }
+
+        let mut columns = Vec::with_capacity(records.len());
+        for record in records {
+            let column_name = record
+                .get_str("column_name")
+                .ok_or_else(|| Error::new("the `column_name` field should be a str"))?;
+            let type_name = record.get_str("data_type").unwrap_or("text");
+            let nullable = record.find("is_nullable").map_or(true, parse_flag);
+            let primary_key = record.find("is_primary_key").map_or(false, parse_flag);
+            columns.push(ColumnInfo::new(
+                column_name,
+                type_name,
+                nullable,
+                primary_key,
+            ));
+        }
+        Ok(Self::new(name, columns))
+    }
+
+    /// Returns the table name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns the columns.
+    #[inline]
+    pub fn columns(&self) -> &[ColumnInfo] {
+        self.columns.as_slice()
+    }
+
+    /// Returns a reference to the column with the name.
+    #[inline]
+    pub fn get_column(&self, name: &str) -> Option<&ColumnInfo> {
+        self.columns.iter().find(|col| col.name == name)
+    }
+
+    /// Returns the names of the primary key columns.
+    pub fn primary_key(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .filter(|col| col.primary_key)
+            .map(|col| col.name())
+            .collect()
+    }
+
+    /// Converts `self` to an Avro record schema.
+    /// Nullable columns are represented as unions with `null`.
+    pub fn to_avro_schema(&self) -> Result<Schema, Error> {
+        let fields = self
+            .columns
+            .iter()
+            .map(|col| {
+                let avro_type = ColumnType::parse(&col.type_name).avro_type();
+                if col.nullable {
+                    json!({
+                        "name": col.name,
+                        "type": ["null", avro_type],
+                        "default": null,
+                    })
+                } else {
+                    json!({
+                        "name": col.name,
+                        "type": avro_type,
+                    })
+                }
+            })
+            .collect::<Vec<_>>();
+        let (namespace, name) = match self.name.rsplit_once('.') {
+            Some((namespace, name)) => (Some(namespace), name),
+            None => (None, self.name.as_str()),
+        };
+        let mut schema = json!({
+            "type": "record",
+            "name": format_avro_name(name),
+            "fields": fields,
+        });
+        if let Some(namespace) = namespace {
+            let namespace = namespace
+                .split('.')
+                .map(format_avro_name)
+                .collect::<Vec<_>>();
+            schema["namespace"] = namespace.join(".").into();
+        }
+        Schema::parse(&schema).map_err(Error::from)
+    }
+
+    /// Converts `self` to an Arrow schema.
+    #[cfg(feature = "connector-arrow")]
+    pub fn to_arrow_schema(&self) -> datafusion::arrow::datatypes::Schema {
+        use datafusion::arrow::datatypes::{Field, Schema};
+
+        let fields = self
+            .columns
+            .iter()
+            .map(|col| {
+                let data_type = ColumnType::parse(&col.type_name).arrow_data_type();
+                Field::new(&col.name, data_type, col.nullable)
+            })
+            .collect::<Vec<_>>();
+        Schema::new(fields)
+    }
+}
+
+/// Column types which are common to the data sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    /// Boolean.
+    Boolean,
+    /// 32-bit integer.
+    Int,
+    /// 64-bit integer.
+    Long,
+    /// 32-bit floating point number.
+    Float,
+    /// 64-bit floating point number.
+    Double,
+    /// Binary data.
+    Bytes,
+    /// Text.
+    String,
+    /// Date.
+    Date,
+    /// Time of the day.
+    Time,
+    /// Date and time.
+    Timestamp,
+    /// UUID.
+    Uuid,
+}
+
+impl ColumnType {
+    /// Parses the native type name of a column.
+    /// Integers are widened if the type is ambiguous in different dialects.
+    fn parse(type_name: &str) -> Self {
+        let mut type_name = type_name.trim().to_ascii_lowercase();
+        for wrapper in ["nullable(", "lowcardinality("] {
+            if let Some(s) = type_name.strip_prefix(wrapper)
+                && let Some(s) = s.strip_suffix(')')
+            {
+                type_name = s.to_owned();
+            }
+        }
+
+        let unsigned = type_name.ends_with(" unsigned");
+        let base_type = type_name
+            .split_once('(')
+            .map_or(type_name.as_str(), |(s, _)| s)
+            .trim_end_matches(" unsigned")
+            .trim();
+        match base_type {
+            "bool" | "boolean" | "bit" => ColumnType::Boolean,
+            "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "int2" | "int4"
+            | "int16" | "int32" | "serial" | "smallserial" | "uint8" | "uint16" => {
+                if unsigned {
+                    ColumnType::Long
+                } else {
+                    ColumnType::Int
+                }
+            }
+            "bigint" | "bigserial" | "int8" | "int64" | "uint32" | "uint64" => ColumnType::Long,
+            "real" | "float" | "float4" | "float16" | "float32" => ColumnType::Float,
+            "double" | "double precision" | "float8" | "float64" | "decimal" | "numeric"
+            | "money" => ColumnType::Double,
+            "blob" | "tinyblob" | "mediumblob" | "longblob" | "bytea" | "binary" | "varbinary"
+            | "largebinary" | "image" => ColumnType::Bytes,
+            "date" | "date32" | "date64" => ColumnType::Date,
+            "time" | "time32" | "time64" | "time with time zone" | "time without time zone" => {
+                ColumnType::Time
+            }
+            "uuid" | "uniqueidentifier" => ColumnType::Uuid,
+            _ => {
+                if base_type.starts_with("timestamp")
+                    || base_type.starts_with("datetime")
+                    || base_type == "smalldatetime"
+                {
+                    ColumnType::Timestamp
+                } else {
+                    ColumnType::String
+                }
+            }
+        }
+    }
+
+    /// Returns the Avro type as a JSON value.
+    fn avro_type(self) -> Value {
+        match self {
+            ColumnType::Boolean => "boolean".into(),
+            ColumnType::Int => "int".into(),
+            ColumnType::Long => "long".into(),
+            ColumnType::Float => "float".into(),
+            ColumnType::Double => "double".into(),
+            ColumnType::Bytes => "bytes".into(),
+            ColumnType::String => "string".into(),
+            ColumnType::Date => json!({ "type": "int", "logicalType": "date" }),
+            ColumnType::Time => json!({ "type": "long", "logicalType": "time-micros" }),
+            ColumnType::Timestamp => json!({ "type": "long", "logicalType": "timestamp-micros" }),
+            ColumnType::Uuid => json!({ "type": "string", "logicalType": "uuid" }),
+        }
+    }
+
+    /// Returns the Arrow data type.
+    #[cfg(feature = "connector-arrow")]
+    fn arrow_data_type(self) -> datafusion::arrow::datatypes::DataType {
+        use datafusion::arrow::datatypes::{DataType, TimeUnit};
+
+        match self {
+            ColumnType::Boolean => DataType::Boolean,
+            ColumnType::Int => DataType::Int32,
+            ColumnType::Long => DataType::Int64,
+            ColumnType::Float => DataType::Float32,
+            ColumnType::Double => DataType::Float64,
+            ColumnType::Bytes => DataType::Binary,
+            ColumnType::String | ColumnType::Uuid => DataType::Utf8,
+            ColumnType::Date => DataType::Date32,
+            ColumnType::Time => DataType::Time64(TimeUnit::Microsecond),
+            ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+        }
+    }
+}
+
+/// Parses an Avro value as a flag.
+fn parse_flag(value: &AvroValue) -> bool {
+    match value {
+        AvroValue::Boolean(b) => *b,
+        AvroValue::Int(i) => *i != 0,
+        AvroValue::Long(i) => *i != 0,
+        AvroValue::String(s) => {
+            matches!(
+                s.to_ascii_uppercase().as_str(),
+                "1" | "PRI" | "TRUE" | "Y" | "YES"
+            )
+        }
+        AvroValue::Union(_, value) => parse_flag(value),
+        _ => false,
+    }
+}
+
+/// Formats a name to be valid in Avro schemas.
+fn format_avro_name(name: &str) -> String {
+    let mut avro_name = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if !avro_name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        avro_name.insert(0, '_');
+    }
+    avro_name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_avro_name, ColumnType};
+
+    #[test]
+    fn it_parses_column_types() {
+        assert_eq!(ColumnType::parse("INTEGER"), ColumnType::Int);
+        assert_eq!(ColumnType::parse("int(10) unsigned"), ColumnType::Long);
+        assert_eq!(ColumnType::parse("Nullable(Float64)"), ColumnType::Double);
+        assert_eq!(ColumnType::parse("character varying"), ColumnType::String);
+        assert_eq!(ColumnType::parse("numeric(10,2)"), ColumnType::Double);
+        assert_eq!(
+            ColumnType::parse("timestamp with time zone"),
+            ColumnType::Timestamp
+        );
+        assert_eq!(ColumnType::parse("DateTime64(3)"), ColumnType::Timestamp);
+        assert_eq!(ColumnType::parse("bytea"), ColumnType::Bytes);
+    }
+
+    #[test]
+    fn it_formats_avro_names() {
+        assert_eq!(format_avro_name("user"), "user");
+        assert_eq!(format_avro_name("2023-orders"), "_2023_orders");
+    }
+}
@@ -1,16 +1,31 @@
 //! Crypto helpers.
 
 use aes_gcm_siv::{
-    aead::{generic_array::GenericArray, Aead},
+    aead::{generic_array::GenericArray, Aead, Payload},
     Aes256GcmSiv, Error, KeyInit, Nonce,
 };
 use rand::Rng;
 
+/// Size of the key in bytes.
+const KEY_SIZE: usize = 32;
+
+/// Size of the nonce in bytes.
+const NONCE_SIZE: usize = 12;
+
 /// Encrypts the plaintext using AES-GCM-SIV.
 pub(crate) fn encrypt(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
-    const KEY_SIZE: usize = 32;
-    const NONCE_SIZE: usize = 12;
+    encrypt_with_aad(key, plaintext, &[])
+}
+
+/// Decrypts the data using AES-GCM-SIV.
+pub(crate) fn decrypt(key: &[u8], data: &[u8]) -> Result<String, Error> {
+    let plaintext = decrypt_with_aad(key, data, &[])?;
+    Ok(String::from_utf8_lossy(&plaintext).into_owned())
+}
 
+/// Encrypts the plaintext with the associated data using AES-GCM-SIV.
+/// The nonce is appended to the ciphertext.
+pub(crate) fn encrypt_with_aad(key: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
     let key_padding = [key, &[0u8; KEY_SIZE]].concat();
     let cipher = Aes256GcmSiv::new(GenericArray::from_slice(&key_padding[0..KEY_SIZE]));
 
@@ -19,16 +34,17 @@ pub(crate) fn encrypt(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
     rng.fill(&mut bytes);
 
     let nonce = Nonce::from_slice(&bytes);
-    let mut ciphertext = cipher.encrypt(nonce, plaintext)?;
+    let payload = Payload {
+        msg: plaintext,
+        aad,
+    };
+    let mut ciphertext = cipher.encrypt(nonce, payload)?;
     ciphertext.extend_from_slice(&bytes);
     Ok(ciphertext)
 }
 
-/// Decrypts the data using AES-GCM-SIV.
-pub(crate) fn decrypt(key: &[u8], data: &[u8]) -> Result<String, Error> {
-    const KEY_SIZE: usize = 32;
-    const NONCE_SIZE: usize = 12;
-
+/// Decrypts the data with the associated data using AES-GCM-SIV.
+pub(crate) fn decrypt_with_aad(key: &[u8], data: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
     if data.len() <= NONCE_SIZE {
         return Err(Error);
     }
@@ -38,6 +54,9 @@ pub(crate) fn decrypt(key: &[u8], data: &[u8]) -> Result<String, Error> {
 
     let (ciphertext, bytes) = data.split_at(data.len() - NONCE_SIZE);
     let nonce = GenericArray::from_slice(bytes);
-    let plaintext = cipher.decrypt(nonce, ciphertext)?;
-    Ok(String::from_utf8_lossy(&plaintext).into_owned())
+    let payload = Payload {
+        msg: ciphertext,
+        aad,
+    };
+    cipher.decrypt(nonce, payload)
 }
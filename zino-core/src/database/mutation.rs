@@ -18,13 +18,18 @@ impl MutationExt<Postgres> for Mutation {
 
         let fields = self.fields();
         let permissive = fields.is_empty();
+        let writable = |key: &str| {
+            self.roles().map_or(true, |roles| {
+                M::get_column(key).is_some_and(|col| col.is_writable(roles))
+            })
+        };
         let mut mutations = Vec::new();
         for (key, value) in updates.iter() {
             match key.as_str() {
                     "$append" => {
                         if let Some(update) = value.as_object() {
                             for (key, value) in update.iter() {
-                                if fields.contains(key) && let Some(col) = M::get_column(key) {
+                                if fields.contains(key) && writable(key) && let Some(col) = M::get_column(key) {
                                     let value = Postgres::encode_value(col, Some(value));
                                     let mutation = format!("{key} = {key} || {value}");
                                     mutations.push(mutation);
@@ -35,7 +40,7 @@ impl MutationExt<Postgres> for Mutation {
                     "$preppend" => {
                         if let Some(update) = value.as_object() {
                             for (key, value) in update.iter() {
-                                if fields.contains(key) && let Some(col) = M::get_column(key) {
+                                if fields.contains(key) && writable(key) && let Some(col) = M::get_column(key) {
                                     let value = Postgres::encode_value(col, Some(value));
                                     let mutation = format!("{key} = {value} || {key}");
                                     mutations.push(mutation);
@@ -46,7 +51,7 @@ impl MutationExt<Postgres> for Mutation {
                     "$pull" => {
                         if let Some(update) = value.as_object() {
                             for (key, value) in update.iter() {
-                                if fields.contains(key) && let Some(col) = M::get_column(key) {
+                                if fields.contains(key) && writable(key) && let Some(col) = M::get_column(key) {
                                     let value = Postgres::encode_value(col, Some(value));
                                     let mutation = format!("{key} = array_remove({key}, {value})");
                                     mutations.push(mutation);
@@ -57,7 +62,7 @@ impl MutationExt<Postgres> for Mutation {
                     "$inc" => {
                         if let Some(update) = value.as_object() {
                             for (key, value) in update.iter() {
-                                if fields.contains(key) && let Some(col) = M::get_column(key) {
+                                if fields.contains(key) && writable(key) && let Some(col) = M::get_column(key) {
                                     let value = Postgres::encode_value(col, Some(value));
                                     let mutation = format!("{key} = {key} + {value}");
                                     mutations.push(mutation);
@@ -68,7 +73,7 @@ impl MutationExt<Postgres> for Mutation {
                     "$mul" => {
                         if let Some(update) = value.as_object() {
                             for (key, value) in update.iter() {
-                                if fields.contains(key) && let Some(col) = M::get_column(key) {
+                                if fields.contains(key) && writable(key) && let Some(col) = M::get_column(key) {
                                     let value = Postgres::encode_value(col, Some(value));
                                     let mutation = format!("{key} = {key} * {value}");
                                     mutations.push(mutation);
@@ -79,7 +84,7 @@ impl MutationExt<Postgres> for Mutation {
                     "$min" => {
                         if let Some(update) = value.as_object() {
                             for (key, value) in update.iter() {
-                                if fields.contains(key) && let Some(col) = M::get_column(key) {
+                                if fields.contains(key) && writable(key) && let Some(col) = M::get_column(key) {
                                     let value = Postgres::encode_value(col, Some(value));
                                     let mutation = format!("{key} = LEAST({key}, {value})");
                                     mutations.push(mutation);
@@ -90,7 +95,7 @@ impl MutationExt<Postgres> for Mutation {
                     "$max" => {
                         if let Some(update) = value.as_object() {
                             for (key, value) in update.iter() {
-                                if fields.contains(key) && let Some(col) = M::get_column(key) {
+                                if fields.contains(key) && writable(key) && let Some(col) = M::get_column(key) {
                                     let value = Postgres::encode_value(col, Some(value));
                                     let mutation = format!("{key} = GREATEST({key}, {value})");
                                     mutations.push(mutation);
@@ -99,7 +104,7 @@ impl MutationExt<Postgres> for Mutation {
                         }
                     }
                     _ => {
-                        if (permissive || fields.contains(key)) && let Some(col) = M::get_column(key) {
+                        if (permissive || fields.contains(key)) && writable(key) && let Some(col) = M::get_column(key) {
                             let value = Postgres::encode_value(col, Some(value));
                             let mutation = format!("{key} = {value}");
                             mutations.push(mutation);
@@ -5,12 +5,17 @@ use crate::{
     Map,
 };
 use serde_json::Value;
-use sqlx::Postgres;
+use sqlx::{Error, Postgres};
+use std::io;
 
 /// Extension trait for [`Query`](crate::model::Query).
 pub(super) trait QueryExt<DB> {
-    /// Formats projection fields.
-    fn format_fields(&self) -> String;
+    /// Checks that the projection fields, filters and sort order only refer to the columns
+    /// which are readable for the roles of the query.
+    fn check_readable<M: Schema>(&self) -> Result<(), Error>;
+
+    /// Formats projection fields which are readable for the roles of the query.
+    fn format_fields<M: Schema>(&self) -> String;
 
     /// Formats the query filters to generate SQL `WHERE` expression.
     fn format_filters<M: Schema>(&self) -> String;
@@ -29,9 +34,58 @@ pub(super) trait QueryExt<DB> {
 }
 
 impl QueryExt<Postgres> for Query {
-    #[inline]
-    fn format_fields(&self) -> String {
+    fn check_readable<M: Schema>(&self) -> Result<(), Error> {
+        let Some(roles) = self.roles() else {
+            return Ok(());
+        };
+        let unreadable_key = self
+            .fields()
+            .iter()
+            .find(|field| M::get_column(field).is_some_and(|col| !col.is_readable(roles)))
+            .cloned()
+            .or_else(|| {
+                let (sort_by, _) = self.sort_order();
+                let key = sort_by.split('.').next().unwrap_or_default();
+                (!key.is_empty() && !is_readable::<M>(key, roles)).then(|| sort_by.to_owned())
+            })
+            .or_else(|| find_unreadable_key::<M>(self.filters(), roles));
+        if let Some(key) = unreadable_key {
+            let message = format!("the field `{key}` is not readable for the roles");
+            Err(Error::Io(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                message,
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn format_fields<M: Schema>(&self) -> String {
         let fields = self.fields();
+        if let Some(roles) = self.roles() {
+            // Only the exact names of the readable columns are accepted.
+            let mut fields = fields
+                .iter()
+                .map(|field| field.as_str())
+                .filter(|field| is_readable::<M>(field, roles))
+                .collect::<Vec<_>>();
+            if fields.is_empty() {
+                let columns = M::columns();
+                if columns.iter().all(|col| col.is_readable(roles)) {
+                    return "*".to_owned();
+                }
+                fields = columns
+                    .iter()
+                    .filter(|col| col.is_readable(roles))
+                    .map(|col| col.name())
+                    .collect();
+            }
+            return if fields.is_empty() {
+                M::PRIMARY_KEY_NAME.to_owned()
+            } else {
+                fields.join(", ")
+            };
+        }
         if fields.is_empty() {
             "*".to_owned()
         } else {
@@ -204,3 +258,54 @@ impl QueryExt<Postgres> for Query {
         })
     }
 }
+
+/// Returns `true` if the key is a column of the model which is readable for the roles.
+fn is_readable<M: Schema>(key: &str, roles: &[String]) -> bool {
+    M::get_column(key).is_some_and(|col| col.is_readable(roles))
+}
+
+/// Finds the first key in the filters which refers to a column not readable for the roles.
+/// The raw `$join` expression is always rejected.
+fn find_unreadable_key<M: Schema>(filters: &Map, roles: &[String]) -> Option<String> {
+    for (key, value) in filters {
+        match key.as_str() {
+            "$and" | "$or" | "$not" | "$nor" | "having" => {
+                if let Some(selection) = value.as_object() {
+                    if let Some(key) = find_unreadable_key::<M>(selection, roles) {
+                        return Some(key);
+                    }
+                }
+            }
+            "$text" => {
+                let columns = value
+                    .as_object()
+                    .and_then(|filter| Validation::parse_array::<String>(filter.get("$columns")))
+                    .unwrap_or_default();
+                if let Some(col) = columns
+                    .into_iter()
+                    .find(|col| !is_readable::<M>(col, roles))
+                {
+                    return Some(col);
+                }
+            }
+            "group_by" => {
+                if let Some(group_by) = value.as_str() {
+                    let key = group_by
+                        .split(',')
+                        .map(|s| s.trim())
+                        .find(|key| !is_readable::<M>(key, roles));
+                    if let Some(key) = key {
+                        return Some(key.to_owned());
+                    }
+                }
+            }
+            "$join" => return Some(key.to_owned()),
+            _ => {
+                if M::get_column(key).is_some_and(|col| !col.is_readable(roles)) {
+                    return Some(key.to_owned());
+                }
+            }
+        }
+    }
+    None
+}
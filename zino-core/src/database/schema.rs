@@ -67,6 +67,14 @@ pub trait Schema: 'static + Send + Sync + Model {
         Self::columns().iter().find(|col| col.name() == key)
     }
 
+    /// Consumes the model and returns as a json object,
+    /// with the fields which are not readable for the roles removed.
+    fn into_restricted_map(self, roles: &[String]) -> Map {
+        let mut map = self.into_map();
+        map.retain(|key, _| Self::get_column(key).map_or(true, |col| col.is_readable(roles)));
+        map
+    }
+
     /// Initializes the model reader.
     #[inline]
     fn init_reader() -> Result<&'static ConnectionPool, Error> {
@@ -200,25 +208,36 @@ pub trait Schema: 'static + Send + Sync + Model {
 
     /// Inserts many models into the table.
     async fn insert_many(models: Vec<Self>) -> Result<u64, Error> {
+        let rows = models.into_iter().map(|model| model.into_map()).collect();
+        Self::insert_rows(rows).await
+    }
+
+    /// Inserts many rows of JSON objects into the table.
+    /// The entries which do not correspond to any column will be ignored.
+    async fn insert_rows(rows: Vec<Map>) -> Result<u64, Error> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
         let pool = Self::get_writer().await.ok_or(Error::PoolClosed)?.pool();
         let table_name = Self::table_name();
-        let mut columns = Vec::new();
-        let mut values = Vec::new();
-        for model in models.into_iter() {
-            let map = model.into_map();
+        let columns = Self::columns()
+            .iter()
+            .map(|col| col.name())
+            .collect::<Vec<_>>()
+            .join(",");
+        let mut values = Vec::with_capacity(rows.len());
+        for row in rows.iter() {
             let mut entries = Vec::new();
             for col in Self::columns() {
-                let column = col.name();
-                let value = Postgres::encode_value(col, map.get(column));
-                columns.push(column);
+                let value = Postgres::encode_value(col, row.get(col.name()));
                 entries.push(value);
             }
             values.push(format!("({})", entries.join(",")));
         }
 
-        let columns = columns.join(",");
         let values = values.join(",");
-        let sql = format!("INSERT INTO {table_name} ({columns}) VALUES ({values});");
+        let sql = format!("INSERT INTO {table_name} ({columns}) VALUES {values};");
         let query_result = sqlx::query(&sql).execute(pool).await?;
         Ok(query_result.rows_affected())
     }
@@ -386,7 +405,8 @@ pub trait Schema: 'static + Send + Sync + Model {
     async fn find<T: DecodeRow<PgRow, Error = Error>>(query: &Query) -> Result<Vec<T>, Error> {
         let pool = Self::get_reader().await.ok_or(Error::PoolClosed)?.pool();
         let table_name = Self::table_name();
-        let projection = query.format_fields();
+        query.check_readable::<Self>()?;
+        let projection = query.format_fields::<Self>();
         let filters = query.format_filters::<Self>();
         let sort = query.format_sort();
         let pagination = query.format_pagination();
@@ -413,7 +433,8 @@ pub trait Schema: 'static + Send + Sync + Model {
     ) -> Result<Option<T>, Error> {
         let pool = Self::get_reader().await.ok_or(Error::PoolClosed)?.pool();
         let table_name = Self::table_name();
-        let projection = query.format_fields();
+        query.check_readable::<Self>()?;
+        let projection = query.format_fields::<Self>();
         let filters = query.format_filters::<Self>();
         let sort = query.format_sort();
         let sql = format!("SELECT {projection} FROM {table_name} {filters} {sort} LIMIT 1;");
@@ -465,7 +486,8 @@ pub trait Schema: 'static + Send + Sync + Model {
             query.append_filters(&mut primary_key_filter);
         }
 
-        let projection = query.format_fields();
+        query.check_readable::<Self>()?;
+        let projection = query.format_fields::<Self>();
         let filters = query.format_filters::<Self>();
         let sql = format!("SELECT {projection} FROM {table_name} {filters};");
         let mut rows = sqlx::query(&sql).fetch(pool);
@@ -524,7 +546,8 @@ pub trait Schema: 'static + Send + Sync + Model {
             query.append_filters(&mut primary_key_filter);
         }
 
-        let projection = query.format_fields();
+        query.check_readable::<Self>()?;
+        let projection = query.format_fields::<Self>();
         let filters = query.format_filters::<Self>();
         let sql = format!("SELECT {projection} FROM {table_name} {filters};");
         let mut rows = sqlx::query(&sql).fetch(pool);
@@ -562,6 +585,7 @@ pub trait Schema: 'static + Send + Sync + Model {
     ) -> Result<T, Error> {
         let pool = Self::get_writer().await.ok_or(Error::PoolClosed)?.pool();
         let table_name = Self::table_name();
+        query.check_readable::<Self>()?;
         let filters = query.format_filters::<Self>();
         let projection = columns
             .iter()
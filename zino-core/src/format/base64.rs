@@ -13,6 +13,12 @@ pub(crate) fn decode(data: impl AsRef<[u8]>) -> Result<Vec<u8>, DecodeError> {
     STANDARD_NO_PAD.decode(data)
 }
 
+/// Encodes the data as URL-safe base64 string without padding.
+#[inline]
+pub(crate) fn encode_url_safe(data: impl AsRef<[u8]>) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data)
+}
+
 /// Encodes the data as base64-encoded data URL string.
 #[cfg(feature = "connector-arrow")]
 pub(crate) fn encode_data_url(data: impl AsRef<[u8]>) -> String {
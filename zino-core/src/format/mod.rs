@@ -1,4 +1,21 @@
 //! Utilities for formatting and parsing.
+//!
+//! # Query parameters
+//!
+//! The query parameters are represented as `${param}`. For the SQL data sources
+//! supporting prepared statements (DuckDB, MSSQL, MySQL, PostgreSQL and SQLite),
+//! the parameters are **bound** as values instead of being interpolated into the query:
+//!
+//! - a parameter should not be quoted, i.e. `name = ${name}` instead of `name = '${name}'`;
+//! - a parameter can not be used for identifiers such as the table and column names;
+//! - a string is always bound as text, so the type should be given explicitly
+//!   for the other column types, e.g. `id = ${id}::uuid` or `time > CAST(${time} AS TIMESTAMP)`;
+//! - an array is expanded into `(?, ?, ?)` for the `IN` operator, and PostgreSQL binds it
+//!   as a native array which can be used as `id = ANY(${ids})`, so `id IN ${ids}`
+//!   is rewritten as `id = ANY($1)`.
+//!
+//! The data sources without support for binding parameters encode the values as escaped
+//! SQL literals, and the other connectors interpolate the values verbatim.
 
 pub(crate) mod base64;
 
@@ -7,3 +24,18 @@ mod query;
 
 #[cfg(any(feature = "connector", feature = "orm"))]
 pub(crate) use query::format_query;
+
+#[cfg(feature = "connector-clickhouse")]
+pub(crate) use query::prepare_clickhouse_query;
+
+#[cfg(feature = "connector-taos")]
+pub(crate) use query::format_sql_query;
+
+#[cfg(any(
+    feature = "connector-duckdb",
+    feature = "connector-mssql",
+    feature = "connector-mysql",
+    feature = "connector-postgres",
+    feature = "connector-sqlite"
+))]
+pub(crate) use query::prepare_sql_query;
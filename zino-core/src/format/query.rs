@@ -28,6 +28,274 @@ pub(crate) fn format_query<'a>(query: &'a str, params: Option<&'a Map>) -> Cow<'
     }
 }
 
+/// Prepares the SQL query for binding parameters
+/// (`?` for MySQL and SQLite, `$N` for PostgreSQL and `@pN` for MSSQL).
+///
+/// The binding parameter is represented as `${param}` and replaced with the placeholder.
+/// The values are returned in the order of placeholders, and the missing ones are bound as `NULL`.
+/// Except for PostgreSQL which binds arrays natively, an array is expanded into
+/// a parenthesized list of placeholders such as `(?, ?, ?)`, which can be used in the `IN` operator.
+/// For PostgreSQL, `IN ${param}` with an array is rewritten as `= ANY($N)`,
+/// and `NOT IN ${param}` is rewritten as `<> ALL($N)`.
+#[cfg(any(
+    feature = "connector-duckdb",
+    feature = "connector-mssql",
+    feature = "connector-mysql",
+    feature = "connector-postgres",
+    feature = "connector-sqlite"
+))]
+pub(crate) fn prepare_sql_query<'a>(
+    query: &'a str,
+    params: Option<&'a Map>,
+    placeholder: char,
+) -> (Cow<'a, str>, Vec<&'a Value>) {
+    if !query.contains('$') {
+        return (Cow::Borrowed(query), Vec::new());
+    }
+
+    let query = if placeholder == '$' {
+        IN_PARAMETER_PATTERN.replace_all(query, |captures: &Captures| {
+            let negated = captures.get(1).is_some();
+            let param = &captures[2];
+            let value = params.and_then(|params| params.get(&captures[3]));
+            match (value, negated) {
+                (Some(Value::Array(_)), false) => format!("= ANY({param})"),
+                (Some(Value::Array(_)), true) => format!("<> ALL({param})"),
+                (_, false) => format!("IN ({param})"),
+                (_, true) => format!("NOT IN ({param})"),
+            }
+        })
+    } else {
+        Cow::Borrowed(query)
+    };
+
+    let mut values = Vec::new();
+    let sql = QUERY_PARAMETER_PATTERN.replace_all(&query, |captures: &Captures| {
+        let key = &captures[1];
+        let value = params.and_then(|params| params.get(key));
+        if placeholder != '$' && let Some(Value::Array(vec)) = value {
+            if vec.is_empty() {
+                return "(NULL)".to_owned();
+            }
+
+            let list = vec
+                .iter()
+                .map(|value| {
+                    values.push(value);
+                    format_placeholder(placeholder, values.len())
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({list})")
+        } else {
+            values.push(value.unwrap_or(&Value::Null));
+            format_placeholder(placeholder, values.len())
+        }
+    });
+    (Cow::Owned(sql.into_owned()), values)
+}
+
+/// Formats the placeholder with the 1-based index.
+#[cfg(any(
+    feature = "connector-duckdb",
+    feature = "connector-mssql",
+    feature = "connector-mysql",
+    feature = "connector-postgres",
+    feature = "connector-sqlite"
+))]
+fn format_placeholder(placeholder: char, index: usize) -> String {
+    match placeholder {
+        '$' => format!("${index}"),
+        '@' => format!("@p{index}"),
+        _ => placeholder.to_string(),
+    }
+}
+
+/// Prepares the ClickHouse query for binding parameters.
+///
+/// The binding parameter is represented as `${param}` and replaced with a native
+/// query parameter `{pN:Type}`, whose type is inferred from the value.
+/// The values are returned in the text format with the names `pN`, which should be sent
+/// as the `param_pN` settings. An array is bound as `Array(T)` which can be used in the `has`
+/// function, and a missing value is bound as `NULL` with the type `Nullable(String)`.
+#[cfg(feature = "connector-clickhouse")]
+pub(crate) fn prepare_clickhouse_query<'a>(
+    query: &'a str,
+    params: Option<&'a Map>,
+) -> (Cow<'a, str>, Vec<(String, String)>) {
+    if !query.contains('$') {
+        return (Cow::Borrowed(query), Vec::new());
+    }
+
+    let mut keys = Vec::new();
+    let mut values = Vec::new();
+    let query = QUERY_PARAMETER_PATTERN.replace_all(query, |captures: &Captures| {
+        let key = &captures[1];
+        let value = params
+            .and_then(|params| params.get(key))
+            .unwrap_or(&Value::Null);
+        let index = if let Some(index) = keys.iter().position(|k| k == key) {
+            index + 1
+        } else {
+            keys.push(key.to_owned());
+            values.push((format!("p{}", keys.len()), encode_clickhouse_param(value)));
+            keys.len()
+        };
+        format!("{{p{index}:{}}}", clickhouse_type(value))
+    });
+    (query, values)
+}
+
+/// Infers the ClickHouse type of the JSON value.
+#[cfg(feature = "connector-clickhouse")]
+fn clickhouse_type(value: &Value) -> String {
+    match value {
+        Value::Null => "Nullable(String)".to_owned(),
+        Value::Bool(_) => "Bool".to_owned(),
+        Value::Number(n) => {
+            if n.is_i64() {
+                "Int64".to_owned()
+            } else if n.is_u64() {
+                "UInt64".to_owned()
+            } else {
+                "Float64".to_owned()
+            }
+        }
+        Value::Array(vec) => {
+            let item_type = vec
+                .iter()
+                .find(|v| !v.is_null())
+                .map(clickhouse_type)
+                .unwrap_or_else(|| "String".to_owned());
+            if vec.iter().any(|v| v.is_null()) && !item_type.starts_with("Array") {
+                format!("Array(Nullable({item_type}))")
+            } else {
+                format!("Array({item_type})")
+            }
+        }
+        _ => "String".to_owned(),
+    }
+}
+
+/// Encodes the JSON value as a ClickHouse query parameter in the escaped text format.
+#[cfg(feature = "connector-clickhouse")]
+fn encode_clickhouse_param(value: &Value) -> String {
+    match value {
+        Value::Null => "\\N".to_owned(),
+        Value::String(s) => escape_clickhouse_string(s),
+        Value::Array(_) => encode_clickhouse_literal(value),
+        Value::Object(_) => escape_clickhouse_string(&value.to_string()),
+        _ => value.to_string(),
+    }
+}
+
+/// Encodes the JSON value as a ClickHouse literal in an array.
+#[cfg(feature = "connector-clickhouse")]
+fn encode_clickhouse_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_owned(),
+        Value::String(s) => escape_sql_string(s),
+        Value::Array(vec) => {
+            let list = vec
+                .iter()
+                .map(encode_clickhouse_literal)
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("[{list}]")
+        }
+        Value::Object(_) => escape_sql_string(&value.to_string()),
+        _ => value.to_string(),
+    }
+}
+
+/// Escapes the string in the ClickHouse escaped text format.
+#[cfg(feature = "connector-clickhouse")]
+fn escape_clickhouse_string(s: &str) -> String {
+    let mut text = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => text.push_str("\\\\"),
+            '\0' => text.push_str("\\0"),
+            '\t' => text.push_str("\\t"),
+            '\n' => text.push_str("\\n"),
+            '\r' => text.push_str("\\r"),
+            _ => text.push(c),
+        }
+    }
+    text
+}
+
+/// Formats the SQL query by encoding the parameters as escaped literals.
+///
+/// It is used for TDengine, whose statement interface only supports the insertions.
+/// Strings are single-quoted with the special characters escaped,
+/// and arrays are encoded as a parenthesized list which can be used in the `IN` operator.
+#[cfg(feature = "connector-taos")]
+pub(crate) fn format_sql_query<'a>(query: &'a str, params: Option<&'a Map>) -> Cow<'a, str> {
+    if !query.contains('$') {
+        return Cow::Borrowed(query);
+    }
+    QUERY_PARAMETER_PATTERN.replace_all(query, |captures: &Captures| {
+        let key = &captures[1];
+        params
+            .and_then(|params| params.get(key))
+            .map(encode_sql_literal)
+            .unwrap_or_else(|| "NULL".to_owned())
+    })
+}
+
+/// Encodes the JSON value as a SQL literal.
+#[cfg(feature = "connector-taos")]
+fn encode_sql_literal(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_owned(),
+        Value::Bool(b) => String::from(if *b { "TRUE" } else { "FALSE" }),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => escape_sql_string(s),
+        Value::Array(vec) => {
+            let list = vec
+                .iter()
+                .map(encode_sql_literal)
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({list})")
+        }
+        Value::Object(_) => escape_sql_string(&value.to_string()),
+    }
+}
+
+/// Escapes the string as a single-quoted SQL literal.
+#[cfg(any(feature = "connector-clickhouse", feature = "connector-taos"))]
+fn escape_sql_string(s: &str) -> String {
+    let mut literal = String::with_capacity(s.len() + 2);
+    literal.push('\'');
+    for c in s.chars() {
+        match c {
+            '\'' => literal.push_str("\\'"),
+            '\\' => literal.push_str("\\\\"),
+            '\0' => literal.push_str("\\0"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            _ => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+/// Pattern of the query parameter in the `IN` operator.
+#[cfg(any(
+    feature = "connector-duckdb",
+    feature = "connector-mssql",
+    feature = "connector-mysql",
+    feature = "connector-postgres",
+    feature = "connector-sqlite"
+))]
+static IN_PARAMETER_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(NOT\s+)?IN\s*(\$\{\s*([a-zA-Z]+[\w\.]*)\s*\})")
+        .expect("fail to create the `IN` parameter pattern")
+});
+
 /// Query parameter pattern.
 static QUERY_PARAMETER_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"\$\{\s*([a-zA-Z]+[\w\.]*)\s*\}")
@@ -36,8 +304,8 @@ static QUERY_PARAMETER_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
 
 #[cfg(test)]
 mod tests {
-    use super::format_query;
-    use crate::{extend::JsonObjectExt, Map};
+    use super::*;
+    use crate::extend::JsonObjectExt;
 
     #[test]
     fn it_formats_query_params() {
@@ -50,4 +318,109 @@ mod tests {
             "SELECT id, name, age FROM users WHERE name = 'alice' AND age >= 18;"
         );
     }
+
+    #[cfg(any(
+        feature = "connector-duckdb",
+        feature = "connector-mssql",
+        feature = "connector-mysql",
+        feature = "connector-postgres",
+        feature = "connector-sqlite"
+    ))]
+    #[test]
+    fn it_prepares_sql_query_params() {
+        let query = "SELECT * FROM users WHERE name = ${name} AND age >= ${age} OR id = ${id};";
+        let mut params = Map::new();
+        params.upsert("name", "alice");
+        params.upsert("age", 18);
+
+        let (sql, values) = prepare_sql_query(query, Some(&params), '?');
+        assert_eq!(
+            sql,
+            "SELECT * FROM users WHERE name = ? AND age >= ? OR id = ?;"
+        );
+        assert_eq!(values.len(), 3);
+        assert_eq!(*values[0], "alice");
+        assert_eq!(*values[1], 18);
+        assert!(values[2].is_null());
+
+        let (sql, _) = prepare_sql_query(query, Some(&params), '$');
+        assert_eq!(
+            sql,
+            "SELECT * FROM users WHERE name = $1 AND age >= $2 OR id = $3;"
+        );
+
+        let (sql, _) = prepare_sql_query(query, Some(&params), '@');
+        assert_eq!(
+            sql,
+            "SELECT * FROM users WHERE name = @p1 AND age >= @p2 OR id = @p3;"
+        );
+
+        let query = "SELECT * FROM users WHERE id IN ${ids} AND name = ${name};";
+        params.upsert("ids", vec![1, 2, 3]);
+
+        let (sql, values) = prepare_sql_query(query, Some(&params), '?');
+        assert_eq!(
+            sql,
+            "SELECT * FROM users WHERE id IN (?, ?, ?) AND name = ?;"
+        );
+        assert_eq!(values.len(), 4);
+        assert_eq!(*values[2], 3);
+        assert_eq!(*values[3], "alice");
+
+        let (sql, _) = prepare_sql_query(query, Some(&params), '@');
+        assert_eq!(
+            sql,
+            "SELECT * FROM users WHERE id IN (@p1, @p2, @p3) AND name = @p4;"
+        );
+
+        let (sql, values) = prepare_sql_query(query, Some(&params), '$');
+        assert_eq!(sql, "SELECT * FROM users WHERE id = ANY($1) AND name = $2;");
+        assert!(values[0].is_array());
+
+        let query = "SELECT * FROM users WHERE id NOT IN ${ids} AND name in ${name};";
+        let (sql, values) = prepare_sql_query(query, Some(&params), '$');
+        assert_eq!(
+            sql,
+            "SELECT * FROM users WHERE id <> ALL($1) AND name IN ($2);"
+        );
+        assert_eq!(values.len(), 2);
+    }
+
+    #[cfg(feature = "connector-clickhouse")]
+    #[test]
+    fn it_prepares_clickhouse_query_params() {
+        let query = "SELECT * FROM users WHERE name = ${name} AND has(${ages}, age) OR id = ${id} \
+            OR nickname = ${name};";
+        let mut params = Map::new();
+        params.upsert("name", "alice' OR '1' = '1\t");
+        params.upsert("ages", vec![18, 20]);
+
+        let (sql, values) = prepare_clickhouse_query(query, Some(&params));
+        assert_eq!(
+            sql,
+            "SELECT * FROM users WHERE name = {p1:String} AND has({p2:Array(Int64)}, age) \
+                OR id = {p3:Nullable(String)} OR nickname = {p1:String};"
+        );
+        assert_eq!(
+            values,
+            vec![
+                ("p1".to_owned(), "alice' OR '1' = '1\\t".to_owned()),
+                ("p2".to_owned(), "[18,20]".to_owned()),
+                ("p3".to_owned(), "\\N".to_owned()),
+            ]
+        );
+    }
+
+    #[cfg(feature = "connector-taos")]
+    #[test]
+    fn it_formats_sql_query_params() {
+        let query = "SELECT * FROM users WHERE name = ${name} AND age IN ${ages} OR id = ${id};";
+        let mut params = Map::new();
+        params.upsert("name", "alice' OR '1' = '1");
+        params.upsert("ages", vec![18, 20]);
+        assert_eq!(
+            format_sql_query(query, Some(&params)),
+            "SELECT * FROM users WHERE name = 'alice\\' OR \\'1\\' = \\'1' AND age IN (18, 20) OR id = NULL;"
+        );
+    }
 }
@@ -47,6 +47,7 @@ pub mod model;
 pub mod request;
 pub mod response;
 pub mod schedule;
+pub mod session;
 pub mod state;
 pub mod trace;
 
@@ -1,6 +1,8 @@
 use apache_avro::schema::{Name, Schema};
+use parking_lot::RwLock;
 use serde::Serialize;
 use serde_json::Value;
+use std::{collections::HashMap, sync::LazyLock};
 
 /// A model field with associated metadata.
 #[derive(Debug, Clone, Serialize)]
@@ -15,6 +17,10 @@ pub struct Column<'a> {
     not_null: bool,
     /// Index type.
     index_type: Option<&'a str>,
+    /// Roles which are permitted to read the column. It is unrestricted if empty.
+    read_roles: &'a [&'a str],
+    /// Roles which are permitted to write the column. It is unrestricted if empty.
+    write_roles: &'a [&'a str],
 }
 
 impl<'a> Column<'a> {
@@ -32,9 +38,23 @@ impl<'a> Column<'a> {
             default_value,
             not_null,
             index_type,
+            read_roles: &[],
+            write_roles: &[],
         }
     }
 
+    /// Sets the roles which are permitted to read the column.
+    #[inline]
+    pub fn set_read_roles(&mut self, roles: &'a [&'a str]) {
+        self.read_roles = roles;
+    }
+
+    /// Sets the roles which are permitted to write the column.
+    #[inline]
+    pub fn set_write_roles(&mut self, roles: &'a [&'a str]) {
+        self.write_roles = roles;
+    }
+
     /// Returns the name.
     #[inline]
     pub fn name(&self) -> &'a str {
@@ -65,6 +85,30 @@ impl<'a> Column<'a> {
         self.index_type
     }
 
+    /// Returns the roles which are permitted to read the column.
+    #[inline]
+    pub fn read_roles(&self) -> &'a [&'a str] {
+        self.read_roles
+    }
+
+    /// Returns the roles which are permitted to write the column.
+    #[inline]
+    pub fn write_roles(&self) -> &'a [&'a str] {
+        self.write_roles
+    }
+
+    /// Returns `true` if the column is readable for the roles.
+    #[inline]
+    pub fn is_readable(&self, roles: &[String]) -> bool {
+        has_any_role(roles, self.read_roles)
+    }
+
+    /// Returns `true` if the column is writable for the roles.
+    #[inline]
+    pub fn is_writable(&self, roles: &[String]) -> bool {
+        has_any_role(roles, self.write_roles)
+    }
+
     /// Returns the [Avro schema](apache_avro::schema::Schema).
     pub fn schema(&self) -> Schema {
         let type_name = self.type_name;
@@ -96,6 +140,87 @@ impl<'a> Column<'a> {
     }
 }
 
+impl Column<'static> {
+    /// Registers the read roles of the columns, which are used to restrict
+    /// the response data for the roles of the request context.
+    pub fn register_read_roles(columns: &[Column<'static>]) {
+        let mut registry = COLUMN_READ_ROLES.write();
+        for col in columns.iter().filter(|col| !col.read_roles.is_empty()) {
+            let read_roles = registry.entry(col.name).or_default();
+            if !read_roles.contains(&col.read_roles) {
+                read_roles.push(col.read_roles);
+            }
+        }
+    }
+}
+
+/// Returns `true` if any registered column has declared the read roles.
+pub(crate) fn has_read_roles() -> bool {
+    !COLUMN_READ_ROLES.read().is_empty()
+}
+
+/// Removes the fields which are not readable for the roles from the json value recursively.
+/// A field name declared by several models is readable only if all of them permit the roles.
+pub(crate) fn restrict_value(value: &mut Value, roles: &[String]) {
+    let registry = COLUMN_READ_ROLES.read();
+    if !registry.is_empty() {
+        restrict_value_with(value, roles, &registry);
+    }
+}
+
+/// Removes the fields which are not readable for the roles with the registry.
+fn restrict_value_with(value: &mut Value, roles: &[String], registry: &ReadRolesRegistry) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| {
+                registry.get(key.as_str()).map_or(true, |read_roles| {
+                    read_roles
+                        .iter()
+                        .all(|permitted_roles| has_any_role(roles, permitted_roles))
+                })
+            });
+            for value in map.values_mut() {
+                restrict_value_with(value, roles, registry);
+            }
+        }
+        Value::Array(vec) => {
+            for value in vec {
+                restrict_value_with(value, roles, registry);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Read roles of the columns keyed by the column name.
+type ReadRolesRegistry = HashMap<&'static str, Vec<&'static [&'static str]>>;
+
+/// Registry of the read roles declared by the columns.
+static COLUMN_READ_ROLES: LazyLock<RwLock<ReadRolesRegistry>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Returns `true` if the roles contain a permitted one or the permitted roles are empty.
+/// A role such as `admin:user` is permitted by `admin`, and vice versa.
+/// The `superuser` role is always permitted.
+fn has_any_role(roles: &[String], permitted_roles: &[&str]) -> bool {
+    if permitted_roles.is_empty() {
+        return true;
+    }
+    roles.iter().any(|role| {
+        role == "superuser"
+            || permitted_roles.iter().any(|&permitted_role| {
+                let (shorter, longer) = if role.len() <= permitted_role.len() {
+                    (role.as_str(), permitted_role)
+                } else {
+                    (permitted_role, role.as_str())
+                };
+                longer
+                    .strip_prefix(shorter)
+                    .is_some_and(|s| s.is_empty() || s.starts_with(':'))
+            })
+    })
+}
+
 /// A backend type for encoding the column.
 pub trait EncodeColumn<'a> {
     /// Returns the corresponding column type.
@@ -110,3 +235,43 @@ pub trait EncodeColumn<'a> {
     /// Formats a column filter.
     fn format_filter(column: &Column<'a>, key: &str, value: &Value) -> String;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Column;
+    use serde_json::json;
+
+    #[test]
+    fn it_checks_column_roles() {
+        let mut column = Column::new("password", "String", None, true, None);
+        assert!(column.is_readable(&[]));
+
+        column.set_read_roles(&["admin"]);
+        assert!(!column.is_readable(&[]));
+        assert!(!column.is_readable(&["user".to_owned()]));
+        assert!(!column.is_readable(&["administrator".to_owned()]));
+        assert!(column.is_readable(&["admin".to_owned()]));
+        assert!(column.is_readable(&["admin:user".to_owned()]));
+        assert!(column.is_readable(&["superuser".to_owned()]));
+        assert!(column.is_writable(&["user".to_owned()]));
+    }
+
+    #[test]
+    fn it_restricts_values() {
+        let mut column = Column::new("secret_note", "String", None, false, None);
+        column.set_read_roles(&["admin"]);
+        Column::register_read_roles(&[column]);
+
+        let mut value = json!({
+            "id": 1,
+            "secret_note": "note",
+            "data": [{ "name": "zino", "secret_note": "note" }],
+        });
+        super::restrict_value(&mut value, &["user".to_owned()]);
+        assert_eq!(value, json!({ "id": 1, "data": [{ "name": "zino" }] }));
+
+        let mut value = json!({ "id": 1, "secret_note": "note" });
+        super::restrict_value(&mut value, &["admin".to_owned()]);
+        assert_eq!(value, json!({ "id": 1, "secret_note": "note" }));
+    }
+}
@@ -9,6 +9,8 @@ mod query;
 mod row;
 
 pub use column::{Column, EncodeColumn};
+
+pub(crate) use column::{has_read_roles, restrict_value};
 pub use mutation::Mutation;
 pub use query::Query;
 pub use row::DecodeRow;
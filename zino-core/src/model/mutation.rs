@@ -7,6 +7,8 @@ pub struct Mutation {
     fields: Vec<String>,
     // Updates.
     updates: Map,
+    // Roles for the field-level access control.
+    roles: Option<Vec<String>>,
 }
 
 impl Mutation {
@@ -16,6 +18,7 @@ impl Mutation {
         Self {
             fields: Vec::new(),
             updates,
+            roles: None,
         }
     }
 
@@ -76,6 +79,13 @@ impl Mutation {
         self.updates.append(updates);
     }
 
+    /// Sets the roles for the field-level access control.
+    /// The editable fields are restricted by the write roles of the model columns.
+    #[inline]
+    pub fn set_roles(&mut self, roles: Vec<String>) {
+        self.roles = Some(roles);
+    }
+
     /// Returns a reference to the editable fields.
     #[inline]
     pub fn fields(&self) -> &[String] {
@@ -87,4 +97,10 @@ impl Mutation {
     pub fn updates(&self) -> &Map {
         &self.updates
     }
+
+    /// Returns the roles for the field-level access control.
+    #[inline]
+    pub fn roles(&self) -> Option<&[String]> {
+        self.roles.as_deref()
+    }
 }
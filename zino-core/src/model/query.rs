@@ -14,6 +14,8 @@ pub struct Query {
     limit: u64,
     // Offset.
     offset: u64,
+    // Roles for the field-level access control.
+    roles: Option<Vec<String>>,
 }
 
 impl Query {
@@ -26,6 +28,7 @@ impl Query {
             sort_order: (None, false),
             limit: 10,
             offset: 0,
+            roles: None,
         }
     }
 
@@ -161,6 +164,13 @@ impl Query {
         self.offset = offset;
     }
 
+    /// Sets the roles for the field-level access control.
+    /// The projection fields are restricted by the read roles of the model columns.
+    #[inline]
+    pub fn set_roles(&mut self, roles: Vec<String>) {
+        self.roles = Some(roles);
+    }
+
     /// Returns a reference to the projection fields.
     #[inline]
     pub fn fields(&self) -> &[String] {
@@ -191,6 +201,12 @@ impl Query {
     pub fn offset(&self) -> u64 {
         self.offset
     }
+
+    /// Returns the roles for the field-level access control.
+    #[inline]
+    pub fn roles(&self) -> Option<&[String]> {
+        self.roles.as_deref()
+    }
 }
 
 impl Default for Query {
@@ -202,6 +218,7 @@ impl Default for Query {
             sort_order: (None, false),
             limit: 10,
             offset: 0,
+            roles: None,
         }
     }
 }
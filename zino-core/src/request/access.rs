@@ -0,0 +1,71 @@
+use super::RequestContext;
+use crate::{extend::JsonObjectExt, response::Rejection, BoxFuture, Map};
+
+/// Access data extracted from a request context for a route-level guard.
+#[derive(Debug, Clone, Default)]
+pub struct AccessRequest {
+    /// Subject, which is the `user_id` in the session or the `sub` claim of the JWT.
+    subject: Option<String>,
+    /// Action, which is the lowercase request method.
+    action: String,
+    /// Resource, which is the request path without the leading `/`.
+    resource: String,
+}
+
+impl AccessRequest {
+    /// Creates a new instance.
+    #[inline]
+    pub fn new(subject: Option<String>, action: &str, resource: &str) -> Self {
+        Self {
+            subject,
+            action: action.to_ascii_lowercase(),
+            resource: resource.trim_start_matches('/').to_owned(),
+        }
+    }
+
+    /// Extracts the access data from the request context.
+    pub fn extract<Ctx: RequestContext + ?Sized>(ctx: &Ctx) -> Self {
+        let subject = ctx
+            .session()
+            .and_then(|session| session.get("user_id"))
+            .and_then(|value| value.as_str().map(|s| s.to_owned()))
+            .or_else(|| {
+                ctx.parse_jwt_claims::<Map>()
+                    .ok()
+                    .and_then(|claims| claims.get_str("sub").map(|s| s.to_owned()))
+            });
+        Self::new(subject, ctx.request_method().as_ref(), ctx.request_path())
+    }
+
+    /// Returns the subject.
+    #[inline]
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    /// Returns the action.
+    #[inline]
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    /// Returns the resource.
+    #[inline]
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+}
+
+/// A route-level guard which resolves the principal of a request
+/// and rejects the request if the action is not permitted.
+pub trait AccessGuard: Send + Sync {
+    /// Principal type, which is inserted into the request extensions for the handlers.
+    type Principal: Clone + Send + Sync + 'static;
+
+    /// Resolves the principal and checks the access. It should reject with `Unauthorized`
+    /// if the principal can not be resolved, or `Forbidden` if the action is not permitted.
+    fn check<'a>(
+        &'a self,
+        access: &'a AccessRequest,
+    ) -> BoxFuture<'a, Result<Self::Principal, Rejection<'static>>>;
+}
@@ -1,4 +1,4 @@
-use std::time::Instant;
+use std::{sync::OnceLock, time::Instant};
 use unic_langid::LanguageIdentifier;
 use uuid::Uuid;
 
@@ -17,6 +17,8 @@ pub struct Context {
     session_id: Option<String>,
     /// Locale.
     locale: Option<LanguageIdentifier>,
+    /// Roles of the authenticated user, which are computed once per request.
+    user_roles: OnceLock<Vec<String>>,
 }
 
 impl Context {
@@ -29,6 +31,7 @@ impl Context {
             trace_id: Uuid::nil(),
             session_id: None,
             locale: None,
+            user_roles: OnceLock::new(),
         }
     }
 
@@ -93,4 +96,11 @@ impl Context {
     pub fn locale(&self) -> Option<&LanguageIdentifier> {
         self.locale.as_ref()
     }
+
+    /// Returns the roles of the authenticated user,
+    /// initializing them with `f` if they have not been computed.
+    #[inline]
+    pub fn get_or_init_user_roles(&self, f: impl FnOnce() -> Vec<String>) -> &[String] {
+        self.user_roles.get_or_init(f)
+    }
 }
@@ -2,14 +2,17 @@
 
 use crate::{
     application::{self, http_client},
-    authentication::{Authentication, ParseSecurityTokenError, SecurityToken, SessionId},
+    authentication::{
+        Authentication, JsonWebToken, ParseSecurityTokenError, SecurityToken, SessionId,
+    },
     channel::{CloudEvent, Subscription},
     datetime::DateTime,
     error::Error,
     extend::HeaderMapExt,
     i18n,
-    model::{Model, Query},
+    model::{Model, Mutation, Query},
     response::{Rejection, Response, ResponseCode},
+    session::Session,
     trace::{TraceContext, TraceState},
     Map, SharedString, Uuid,
 };
@@ -25,9 +28,14 @@ use std::{
 use toml::value::Table;
 use unic_langid::LanguageIdentifier;
 
+#[cfg(feature = "accessor")]
+use crate::accessor::{GlobalAccessor, UploadOptions, UploadedFile};
+
+mod access;
 mod context;
 mod validation;
 
+pub use access::{AccessGuard, AccessRequest};
 pub use context::Context;
 pub use validation::Validation;
 
@@ -80,6 +88,18 @@ pub trait RequestContext {
     /// Returns a mutable reference to the request scoped state data.
     fn state_data_mut(&mut self) -> &mut Map;
 
+    /// Returns a reference to the server-side session.
+    #[inline]
+    fn session(&self) -> Option<&Session> {
+        None
+    }
+
+    /// Returns a mutable reference to the server-side session.
+    #[inline]
+    fn session_mut(&mut self) -> Option<&mut Session> {
+        None
+    }
+
     /// Attempts to send a message.
     fn try_send(&self, message: CloudEvent) -> Result<(), Rejection>;
 
@@ -325,6 +345,19 @@ pub trait RequestContext {
         }
     }
 
+    /// Parses the request body as a multipart and streams the files into the storage accessor.
+    #[cfg(feature = "accessor")]
+    async fn upload_files(
+        &mut self,
+        accessor: &str,
+        options: &UploadOptions,
+    ) -> Result<Vec<UploadedFile>, Rejection> {
+        let multipart = self.parse_multipart().await?;
+        GlobalAccessor::upload_multipart(accessor, multipart, options)
+            .await
+            .map_err(|err| Rejection::from_validation_entry("files", err).provide_context(self))
+    }
+
     /// Attempts to construct an instance of `Authentication` from an HTTP request.
     /// By default, the `Accept` header value is ignored and
     /// the canonicalized resource is set to the request path.
@@ -453,8 +486,36 @@ pub trait RequestContext {
             })
     }
 
+    /// Attempts to parse the claims of the JSON Web Token in the `authorization` header
+    /// with the `Bearer` scheme. The token is verified by [`JsonWebToken::shared()`].
+    fn parse_jwt_claims<T: DeserializeOwned>(&self) -> Result<T, Rejection> {
+        let token = self
+            .get_header("authorization")
+            .and_then(|authorization| authorization.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                Rejection::from_validation_entry("authorization", Error::new("should be nonempty"))
+                    .provide_context(self)
+            })?;
+        JsonWebToken::shared()
+            .verify(token.trim())
+            .map_err(|err| Rejection::unauthorized(err).provide_context(self))
+    }
+
+    /// Returns the roles of the authenticated user, which are read from the `roles` field
+    /// in the session data or in the claims of the JSON Web Token.
+    /// They are computed once per request and cached in the request context.
+    fn user_roles(&self) -> Vec<String> {
+        match self.get_context() {
+            Some(ctx) => ctx
+                .get_or_init_user_roles(|| parse_user_roles(self))
+                .to_vec(),
+            None => parse_user_roles(self),
+        }
+    }
+
     /// Returns a `Response` or `Rejection` from an SQL query validation.
-    /// The data is extracted from [`parse_query()`](RequestContext::parse_query).
+    /// The data is extracted from [`parse_query()`](RequestContext::parse_query),
+    /// and the projection fields are restricted by the [`user_roles()`](RequestContext::user_roles).
     fn query_validation<S: ResponseCode>(&self, query: &mut Query) -> Result<Response<S>, Rejection>
     where
         Self: Sized,
@@ -463,6 +524,7 @@ pub trait RequestContext {
             Ok(data) => {
                 let validation = query.read_map(&data);
                 if validation.is_success() {
+                    query.set_roles(self.user_roles());
                     Ok(Response::with_context(S::OK, self))
                 } else {
                     Err(Rejection::bad_request(validation).provide_context(self))
@@ -528,6 +590,26 @@ pub trait RequestContext {
         }
     }
 
+    /// Returns a `Response` or `Rejection` from a mutation validation.
+    /// The data is extracted from [`parse_body()`](RequestContext::parse_body),
+    /// and the editable fields are restricted by the [`user_roles()`](RequestContext::user_roles).
+    async fn mutation_validation<S: ResponseCode>(
+        &mut self,
+        mutation: &mut Mutation,
+    ) -> Result<Response<S>, Rejection>
+    where
+        Self: Sized,
+    {
+        let data = self.parse_body::<Map>().await?;
+        let validation = mutation.read_map(&data);
+        if validation.is_success() {
+            mutation.set_roles(self.user_roles());
+            Ok(Response::with_context(S::OK, self))
+        } else {
+            Err(Rejection::bad_request(validation).provide_context(self))
+        }
+    }
+
     /// Makes an HTTP request to the provided resource
     /// using [`reqwest`](https://crates.io/crates/reqwest).
     async fn fetch(
@@ -591,3 +673,24 @@ pub trait RequestContext {
         event
     }
 }
+
+/// Parses the roles of the authenticated user from the session data
+/// or the claims of the JSON Web Token.
+fn parse_user_roles<Ctx: RequestContext + ?Sized>(ctx: &Ctx) -> Vec<String> {
+    let roles = ctx
+        .session()
+        .and_then(|session| session.get("roles"))
+        .or_else(|| {
+            ctx.parse_jwt_claims::<Map>()
+                .ok()
+                .and_then(|mut claims| claims.remove("roles"))
+        });
+    match roles {
+        Some(Value::Array(roles)) => roles
+            .into_iter()
+            .filter_map(|role| role.as_str().map(|s| s.to_owned()))
+            .collect(),
+        Some(Value::String(roles)) => roles.split(',').map(|s| s.trim().to_owned()).collect(),
+        _ => Vec::new(),
+    }
+}
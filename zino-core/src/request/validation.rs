@@ -208,4 +208,37 @@ impl Validation {
     ) -> Option<Result<Ipv6Addr, AddrParseError>> {
         value.into().and_then(|v| v.as_str()).map(|s| s.parse())
     }
+
+    /// Checks the strength of a password. It should have at least `min_length` characters
+    /// and contain at least three of the classes: lowercase letters, uppercase letters,
+    /// digits and symbols.
+    pub fn check_password_strength(password: &str, min_length: usize) -> Result<(), Error> {
+        let length = password.chars().count();
+        if length < min_length {
+            let message = format!("the password should have at least {min_length} characters");
+            return Err(Error::new(message));
+        } else if length > 128 {
+            let message = "the password should have at most 128 characters";
+            return Err(Error::new(message));
+        }
+
+        let mut classes = [false; 4];
+        for c in password.chars() {
+            if c.is_lowercase() {
+                classes[0] = true;
+            } else if c.is_uppercase() {
+                classes[1] = true;
+            } else if c.is_numeric() {
+                classes[2] = true;
+            } else if !c.is_whitespace() && !c.is_control() {
+                classes[3] = true;
+            }
+        }
+        if classes.into_iter().filter(|&b| b).count() < 3 {
+            let message = "the password should contain at least three of the classes: \
+                lowercase letters, uppercase letters, digits and symbols";
+            return Err(Error::new(message));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,311 @@
+use crate::{datetime::DateTime, extend::TomlTableExt, state::State};
+use http::{
+    header::{self, HeaderMap},
+    Method, Uri,
+};
+use sha2::{Digest, Sha256};
+use std::{sync::LazyLock, time::Duration};
+use toml::Table;
+
+#[cfg(feature = "cache")]
+use crate::{cache::GlobalCache, format::base64};
+#[cfg(feature = "cache")]
+use bytes::Bytes;
+#[cfg(feature = "cache")]
+use http::header::{HeaderName, HeaderValue};
+#[cfg(feature = "cache")]
+use http_body::Full;
+#[cfg(feature = "cache")]
+use serde_json::{json, Value};
+
+/// HTTP caching policy of the routes.
+///
+/// The requests with the `authorization` or `cookie` header and the responses with
+/// the `set-cookie` header or the `private` or `no-store` cache directive are never
+/// cached in `GlobalCache`.
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    /// Route path. A trailing `*` matches any suffix.
+    path: String,
+    /// Value of the `cache-control` header.
+    cache_control: Option<String>,
+    /// Time-to-live of the responses cached in `GlobalCache`.
+    ttl: Option<Duration>,
+    /// Request headers which the cached responses vary on.
+    vary: Vec<String>,
+    /// Max size of the response bodies for computing the entity tags and caching.
+    max_body_size: u64,
+}
+
+impl CachePolicy {
+    /// Default max size of the response bodies for computing the entity tags and caching.
+    pub const DEFAULT_MAX_BODY_SIZE: u64 = 1024 * 1024;
+
+    /// Creates a new instance with the config.
+    pub fn with_config(config: &Table) -> Self {
+        let vary = config
+            .get_array("vary")
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| v.as_str().map(|s| s.to_ascii_lowercase()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            path: config.get_str("path").unwrap_or("*").to_owned(),
+            cache_control: config.get_str("cache-control").map(|s| s.to_owned()),
+            ttl: config.get_duration("ttl"),
+            vary,
+            max_body_size: config
+                .get_u64("max-body-size")
+                .unwrap_or(Self::DEFAULT_MAX_BODY_SIZE),
+        }
+    }
+
+    /// Returns the caching policy for the route path, which is configured by
+    /// the `[http-cache]` table and the `[[http-cache.route]]` entries.
+    pub fn get(path: &str) -> Option<&'static CachePolicy> {
+        CACHE_POLICIES.iter().find(|policy| policy.matches(path))
+    }
+
+    /// Returns `true` if the policy applies to the route path.
+    pub fn matches(&self, path: &str) -> bool {
+        if let Some(prefix) = self.path.strip_suffix('*') {
+            path.starts_with(prefix)
+        } else {
+            self.path == path
+        }
+    }
+
+    /// Returns the value of the `cache-control` header.
+    #[inline]
+    pub fn cache_control(&self) -> Option<&str> {
+        self.cache_control.as_deref()
+    }
+
+    /// Returns the time-to-live of the responses cached in `GlobalCache`.
+    #[inline]
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Returns the request headers which the cached responses vary on.
+    #[inline]
+    pub fn vary(&self) -> &[String] {
+        self.vary.as_slice()
+    }
+
+    /// Returns the max size of the response bodies for computing the entity tags and caching.
+    #[inline]
+    pub fn max_body_size(&self) -> u64 {
+        self.max_body_size
+    }
+
+    /// Returns the key of the cached response for the request,
+    /// or `None` if the request carries the credentials.
+    pub fn cache_key(&self, method: &Method, uri: &Uri, headers: &HeaderMap) -> Option<String> {
+        if headers.contains_key(header::AUTHORIZATION) || headers.contains_key(header::COOKIE) {
+            return None;
+        }
+
+        let mut key = format!("http:{method}:{}", uri.path());
+        if let Some(query) = uri.query() {
+            key.push('?');
+            key.push_str(query);
+        }
+        for name in self.vary.iter() {
+            let value = headers
+                .get(name.as_str())
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+            key.push('|');
+            key.push_str(value);
+        }
+        Some(key)
+    }
+
+    /// Loads the response cached in `GlobalCache` with the key.
+    #[cfg(feature = "cache")]
+    pub fn load_response(&self, key: &str) -> Option<http::Response<Full<Bytes>>> {
+        let cached_response = GlobalCache::get(key)?;
+        let status = cached_response.get("status")?.as_u64()?;
+        let body = cached_response.get("body")?.as_str()?;
+        let mut res = http::Response::builder()
+            .status(u16::try_from(status).ok()?)
+            .body(Full::from(base64::decode(body).ok()?))
+            .ok()?;
+        for header in cached_response.get("headers")?.as_array()? {
+            if let Some(Value::String(name)) = header.get(0)
+                && let Some(Value::String(value)) = header.get(1)
+                && let Ok(name) = HeaderName::try_from(name.as_str())
+                && let Ok(value) = HeaderValue::try_from(value.as_str())
+            {
+                res.headers_mut().append(name, value);
+            }
+        }
+        Some(res)
+    }
+
+    /// Stores the response in `GlobalCache` with the key if the TTL is specified
+    /// and the response is not private.
+    #[cfg(feature = "cache")]
+    pub fn store_response(&self, key: &str, parts: &http::response::Parts, body: &[u8]) {
+        if let Some(ttl) = self.ttl
+            && !is_private_response(&parts.headers)
+        {
+            let headers = parts
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    let value = value.to_str().ok()?;
+                    Some(json!([name.as_str(), value]))
+                })
+                .collect::<Vec<_>>();
+            let cached_response = json!({
+                "status": parts.status.as_u16(),
+                "headers": headers,
+                "body": base64::encode(body),
+            });
+            GlobalCache::put_with_ttl(key, cached_response, ttl);
+        }
+    }
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            path: "*".to_owned(),
+            cache_control: None,
+            ttl: None,
+            vary: Vec::new(),
+            max_body_size: Self::DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+}
+
+/// Returns `true` if the response should not be stored by the shared caches
+/// according to the `set-cookie` and `cache-control` headers.
+pub fn is_private_response(headers: &HeaderMap) -> bool {
+    if headers.contains_key(header::SET_COOKIE) {
+        return true;
+    }
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| {
+            let directive = directive.trim();
+            directive.eq_ignore_ascii_case("private") || directive.eq_ignore_ascii_case("no-store")
+        })
+}
+
+/// Computes the entity tag from the response body.
+pub fn compute_etag(body: &[u8]) -> String {
+    let digest = format!("{:x}", Sha256::digest(body));
+    format!("\"{}\"", &digest[..32])
+}
+
+/// Returns `true` if the resource has not been modified according to
+/// the `if-none-match` and `if-modified-since` headers of the request.
+/// The `if-modified-since` header is ignored if `if-none-match` is present.
+pub fn is_not_modified(
+    headers: &HeaderMap,
+    etag: Option<&str>,
+    last_modified: Option<&str>,
+) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        let Some(etag) = etag else {
+            return false;
+        };
+        let etag = etag.trim_start_matches("W/");
+        return if_none_match.to_str().map_or(false, |value| {
+            value.split(',').any(|tag| {
+                let tag = tag.trim();
+                tag == "*" || tag.trim_start_matches("W/") == etag
+            })
+        });
+    }
+    if let Some(if_modified_since) = headers.get(header::IF_MODIFIED_SINCE)
+        && let Some(last_modified) = last_modified
+        && let Ok(if_modified_since) = if_modified_since.to_str()
+        && let Ok(since) = DateTime::parse_utc_str(if_modified_since)
+        && let Ok(modified) = DateTime::parse_utc_str(last_modified)
+    {
+        return modified.timestamp() <= since.timestamp();
+    }
+    false
+}
+
+/// HTTP caching policies.
+static CACHE_POLICIES: LazyLock<Vec<CachePolicy>> = LazyLock::new(|| {
+    let mut policies = Vec::new();
+    if let Some(config) = State::shared().config().get_table("http-cache") {
+        if let Some(routes) = config.get_array("route") {
+            for route in routes.iter().filter_map(|v| v.as_table()) {
+                policies.push(CachePolicy::with_config(route));
+            }
+        }
+
+        let mut default_policy = CachePolicy::with_config(config);
+        default_policy.path = "*".to_owned();
+        policies.push(default_policy);
+    }
+    policies
+});
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_etag, is_not_modified, is_private_response, CachePolicy};
+    use http::{
+        header::{self, HeaderMap, HeaderValue},
+        Method, Uri,
+    };
+
+    #[test]
+    fn it_evaluates_conditional_requests() {
+        let etag = compute_etag(b"hello");
+        assert_eq!(etag.len(), 34);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&etag).unwrap());
+        assert!(is_not_modified(&headers, Some(&etag), None));
+        assert!(!is_not_modified(&headers, Some("\"other\""), None));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Wed, 01 Mar 2023 00:00:00 GMT"),
+        );
+        let last_modified = "Tue, 28 Feb 2023 12:00:00 GMT";
+        assert!(is_not_modified(&headers, None, Some(last_modified)));
+        let last_modified = "Thu, 02 Mar 2023 00:00:00 GMT";
+        assert!(!is_not_modified(&headers, None, Some(last_modified)));
+    }
+
+    #[test]
+    fn it_skips_private_requests_and_responses() {
+        let policy = CachePolicy::default();
+        let uri = Uri::from_static("/user/list?page=1");
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            policy.cache_key(&Method::GET, &uri, &headers).as_deref(),
+            Some("http:GET:/user/list?page=1")
+        );
+        headers.insert(header::COOKIE, HeaderValue::from_static("session-id=abc"));
+        assert!(policy.cache_key(&Method::GET, &uri, &headers).is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("max-age=60"),
+        );
+        assert!(!is_private_response(&headers));
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("max-age=60, Private"),
+        );
+        assert!(is_private_response(&headers));
+    }
+}
@@ -1,7 +1,30 @@
 //! Constructing responses and rejections.
+//!
+//! ## HTTP caching
+//!
+//! Successful `GET` and `HEAD` responses with a known and bounded body size get
+//! an `etag` header computed from the body, and conditional requests with `if-none-match`
+//! or `if-modified-since` are answered with `304 Not Modified`. The caching policies
+//! are configured by the `[http-cache]` table and the `[[http-cache.route]]` entries
+//! with the following fields:
+//!
+//! - `path`: route path, where a trailing `*` matches any suffix;
+//! - `cache-control`: value of the `cache-control` header;
+//! - `ttl`: time-to-live of the whole responses cached in `GlobalCache`,
+//!   which requires the `cache` feature;
+//! - `vary`: request headers which the cached responses vary on,
+//!   which are also sent as the `vary` header;
+//! - `max-body-size`: max size in bytes of the bodies to be buffered (1 MiB by default).
+//!
+//! The requests with the `authorization` or `cookie` header and the responses with
+//! the `set-cookie` header or the `private` or `no-store` cache directive are never
+//! cached in `GlobalCache`.
+//!
 
 use crate::{
+    datetime::DateTime,
     error::Error,
+    model,
     request::{RequestContext, Validation},
     trace::{ServerTiming, TimingMetric, TraceContext},
     SharedString, Uuid,
@@ -16,9 +39,11 @@ use std::{
     time::{Duration, Instant},
 };
 
+mod http_cache;
 mod rejection;
 mod response_code;
 
+pub use http_cache::{compute_etag, is_not_modified, is_private_response, CachePolicy};
 pub use rejection::{ExtractRejection, Rejection};
 pub use response_code::ResponseCode;
 
@@ -63,12 +88,25 @@ pub struct Response<S> {
     /// Content type.
     #[serde(skip)]
     content_type: Option<SharedString>,
+    /// Entity tag.
+    #[serde(skip)]
+    etag: Option<SharedString>,
+    /// Last modified time.
+    #[serde(skip)]
+    last_modified: Option<DateTime>,
+    /// Cache control directives.
+    #[serde(skip)]
+    cache_control: Option<SharedString>,
     /// Trace context.
     #[serde(skip)]
     trace_context: Option<TraceContext>,
     /// Server timing.
     #[serde(skip)]
     server_timing: ServerTiming,
+    /// Roles of the user for the field-level access control.
+    /// The response data is restricted to no roles if they are not provided.
+    #[serde(skip)]
+    roles: Vec<String>,
     /// Phantom type of response code.
     #[serde(skip)]
     phantom: PhantomData<S>,
@@ -92,8 +130,12 @@ impl<S: ResponseCode> Response<S> {
             request_id: Uuid::nil(),
             data: None,
             content_type: None,
+            etag: None,
+            last_modified: None,
+            cache_control: None,
             trace_context: None,
             server_timing: ServerTiming::new(),
+            roles: Vec::new(),
             phantom: PhantomData,
         };
         if success {
@@ -121,8 +163,12 @@ impl<S: ResponseCode> Response<S> {
             request_id: ctx.request_id(),
             data: None,
             content_type: None,
+            etag: None,
+            last_modified: None,
+            cache_control: None,
             trace_context: None,
             server_timing: ServerTiming::new(),
+            roles: user_roles(ctx),
             phantom: PhantomData,
         };
         if success {
@@ -140,6 +186,7 @@ impl<S: ResponseCode> Response<S> {
         self.start_time = ctx.start_time();
         self.request_id = ctx.request_id();
         self.trace_context = Some(ctx.new_trace_context());
+        self.roles = user_roles(ctx);
         self
     }
 
@@ -228,10 +275,18 @@ impl<S: ResponseCode> Response<S> {
         }
     }
 
-    /// Sets the response data.
-    #[inline]
+    /// Sets the response data. If any model has declared the read roles of its columns,
+    /// the fields which are not readable for the roles of the request context are removed.
     pub fn set_data<T: ?Sized + Serialize>(&mut self, data: &T) {
-        match serde_json::value::to_raw_value(data) {
+        let result = if model::has_read_roles() {
+            serde_json::to_value(data).and_then(|mut value| {
+                model::restrict_value(&mut value, &self.roles);
+                serde_json::value::to_raw_value(&value)
+            })
+        } else {
+            serde_json::value::to_raw_value(data)
+        };
+        match result {
             Ok(raw_value) => self.data = Some(raw_value),
             Err(err) => self.set_error_message(err),
         }
@@ -261,6 +316,24 @@ impl<S: ResponseCode> Response<S> {
         self.content_type = Some(content_type.into());
     }
 
+    /// Sets the entity tag. It should be a quoted string optionally prefixed by `W/`.
+    #[inline]
+    pub fn set_etag(&mut self, etag: impl Into<SharedString>) {
+        self.etag = Some(etag.into());
+    }
+
+    /// Sets the last modified time.
+    #[inline]
+    pub fn set_last_modified(&mut self, last_modified: DateTime) {
+        self.last_modified = Some(last_modified);
+    }
+
+    /// Sets the cache control directives, such as `public, max-age=60`.
+    #[inline]
+    pub fn set_cache_control(&mut self, cache_control: impl Into<SharedString>) {
+        self.cache_control = Some(cache_control.into());
+    }
+
     /// Sets the request ID.
     #[inline]
     pub(crate) fn set_request_id(&mut self, request_id: Uuid) {
@@ -434,6 +507,22 @@ impl<S: ResponseCode> From<Response<S>> for http::Response<Full<Bytes>> {
             res.headers_mut().insert("tracestate", header_value);
         }
 
+        if let Some(etag) = response.etag.take()
+            && let Ok(header_value) = HeaderValue::try_from(etag.as_ref())
+        {
+            res.headers_mut().insert(header::ETAG, header_value);
+        }
+        if let Some(last_modified) = response.last_modified.take()
+            && let Ok(header_value) = HeaderValue::try_from(last_modified.to_utc_string())
+        {
+            res.headers_mut().insert(header::LAST_MODIFIED, header_value);
+        }
+        if let Some(cache_control) = response.cache_control.take()
+            && let Ok(header_value) = HeaderValue::try_from(cache_control.as_ref())
+        {
+            res.headers_mut().insert(header::CACHE_CONTROL, header_value);
+        }
+
         let duration = response.start_time.elapsed();
         response.record_server_timing("total", None, Some(duration));
         if let Ok(header_value) = HeaderValue::try_from(response.server_timing.to_string()) {
@@ -460,3 +549,12 @@ impl<S: ResponseCode> From<Response<S>> for http::Response<Full<Bytes>> {
         res
     }
 }
+
+/// Returns the roles of the request context if any model has declared the read roles.
+fn user_roles<Ctx: RequestContext>(ctx: &Ctx) -> Vec<String> {
+    if model::has_read_roles() {
+        ctx.user_roles()
+    } else {
+        Vec::new()
+    }
+}
@@ -0,0 +1,185 @@
+//! Server-side sessions keyed by [`SessionId`].
+//!
+//! Sessions are enabled by the `[session]` table with the following fields:
+//!
+//! - `store`: `"cache"` for a namespace of the global cache, or `"accessor"` for
+//!   an operator of the global accessor;
+//! - `namespace`: name of the cache namespace, `"session"` by default, which should be
+//!   specified by the `[cache.namespaces.session]` table. The sessions are shared by
+//!   the instances if the namespace has a distributed tier;
+//! - `accessor`: name of the accessor for the `"accessor"` store;
+//! - `prefix`: prefix of the paths in the accessor, `"sessions"` by default;
+//! - `ttl`: idle timeout of the sessions, `"30m"` by default;
+//! - `cookie-name`: name of the signed cookie, `"session-id"` by default.
+//!
+//! The session is loaded before the handler and the changes are persisted automatically
+//! after the handler returns. A new session is saved only if some data have been inserted,
+//! and the expiry slides forward when the session is accessed after half of its idle timeout.
+//! The identifier should be rotated by [`Session::renew()`] on login and privilege changes
+//! to prevent session fixation.
+
+use crate::{application::APP_DOMAIN, authentication::SessionId, datetime::DateTime, Map};
+use parking_lot::Mutex;
+use serde_json::Value;
+use sha2::Sha256;
+use std::sync::Arc;
+
+#[cfg(any(feature = "accessor", feature = "cache"))]
+mod store;
+
+#[cfg(any(feature = "accessor", feature = "cache"))]
+pub use store::SessionStore;
+
+/// A server-side session. The clones share the same state, so that the changes made
+/// in a handler are visible to the middleware which persists them.
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Shared state.
+    state: Arc<Mutex<SessionState>>,
+}
+
+/// Session state.
+#[derive(Debug)]
+struct SessionState {
+    /// Session ID in the format of the session identification URI.
+    id: String,
+    /// Previous session ID which should be removed after rotation.
+    previous_id: Option<String>,
+    /// Session data.
+    data: Map,
+    /// Expiration time as a Unix timestamp in milliseconds, or `None` if it is not persisted.
+    expires_at: Option<i64>,
+    /// A flag to indicate whether the data have been modified.
+    modified: bool,
+    /// A flag to indicate whether the session has been destroyed.
+    destroyed: bool,
+}
+
+impl Session {
+    /// Creates a new session with a random identifier.
+    pub fn new() -> Self {
+        Self::with_state(new_session_id(), Map::new(), None)
+    }
+
+    /// Creates a new instance with the state.
+    fn with_state(id: String, data: Map, expires_at: Option<i64>) -> Self {
+        let state = SessionState {
+            id,
+            previous_id: None,
+            data,
+            expires_at,
+            modified: false,
+            destroyed: false,
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Returns the session ID.
+    #[inline]
+    pub fn id(&self) -> String {
+        self.state.lock().id.clone()
+    }
+
+    /// Returns a cloned value corresponding to the key.
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.state.lock().data.get(key).cloned()
+    }
+
+    /// Returns `true` if the session contains the key.
+    #[inline]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.state.lock().data.contains_key(key)
+    }
+
+    /// Returns a copy of the session data.
+    #[inline]
+    pub fn data(&self) -> Map {
+        self.state.lock().data.clone()
+    }
+
+    /// Inserts a key-value pair into the session data.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+        let mut state = self.state.lock();
+        state.modified = true;
+        state.data.insert(key.into(), value.into())
+    }
+
+    /// Removes a key from the session data, returning the value if it exists.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let mut state = self.state.lock();
+        let value = state.data.remove(key);
+        if value.is_some() {
+            state.modified = true;
+        }
+        value
+    }
+
+    /// Clears the session data.
+    pub fn clear(&mut self) {
+        let mut state = self.state.lock();
+        if !state.data.is_empty() {
+            state.data.clear();
+            state.modified = true;
+        }
+    }
+
+    /// Rotates the session ID while keeping the data.
+    /// The previous session will be removed from the store.
+    pub fn renew(&mut self) {
+        let mut state = self.state.lock();
+        let previous_id = std::mem::replace(&mut state.id, new_session_id());
+        if state.expires_at.is_some() && state.previous_id.is_none() {
+            state.previous_id = Some(previous_id);
+        }
+        state.modified = true;
+    }
+
+    /// Destroys the session. It will be removed from the store.
+    #[inline]
+    pub fn destroy(&mut self) {
+        self.state.lock().destroyed = true;
+    }
+
+    /// Returns the expiration time, or `None` if the session has not been persisted.
+    #[inline]
+    pub fn expires_at(&self) -> Option<DateTime> {
+        self.state
+            .lock()
+            .expires_at
+            .map(DateTime::from_timestamp_millis)
+    }
+
+    /// Returns `true` if the session has not been persisted.
+    #[inline]
+    pub fn is_new(&self) -> bool {
+        self.state.lock().expires_at.is_none()
+    }
+
+    /// Returns `true` if the session data have been modified.
+    #[inline]
+    pub fn is_modified(&self) -> bool {
+        self.state.lock().modified
+    }
+
+    /// Returns `true` if the session has been destroyed.
+    #[inline]
+    pub fn is_destroyed(&self) -> bool {
+        self.state.lock().destroyed
+    }
+}
+
+impl Default for Session {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Generates a new session ID.
+fn new_session_id() -> String {
+    let key = rand::random::<[u8; 32]>();
+    SessionId::new::<Sha256>(*APP_DOMAIN, key).to_string()
+}
@@ -0,0 +1,322 @@
+use super::Session;
+use crate::{
+    application::APP_DOMAIN, authentication::SessionId, datetime::DateTime, error::Error,
+    extend::TomlTableExt, state::State, Map,
+};
+use serde::{Deserialize, Serialize};
+use std::{sync::LazyLock, time::Duration};
+use toml::Table;
+
+#[cfg(feature = "accessor")]
+use crate::accessor::GlobalAccessor;
+#[cfg(feature = "accessor")]
+use opendal::{ErrorKind::NotFound, Operator};
+
+#[cfg(feature = "cache")]
+use crate::cache::{CacheNamespace, GlobalCache};
+
+/// Default idle timeout of the sessions.
+const DEFAULT_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Store of the server-side sessions.
+#[derive(Debug)]
+pub struct SessionStore {
+    /// Storage backend.
+    backend: SessionBackend,
+    /// Idle timeout of the sessions.
+    ttl: Duration,
+    /// Name of the cookie.
+    cookie_name: String,
+}
+
+/// Storage backend of the sessions.
+#[derive(Debug)]
+enum SessionBackend {
+    /// A namespace of the global cache.
+    #[cfg(feature = "cache")]
+    Cache(&'static CacheNamespace),
+    /// An operator of the global accessor with the prefix of the paths.
+    #[cfg(feature = "accessor")]
+    Accessor(Operator, String),
+}
+
+/// Session record in the storage backend.
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionRecord {
+    /// Session data.
+    data: Map,
+    /// Expiration time as a Unix timestamp in milliseconds.
+    expires_at: i64,
+}
+
+/// Changes of a session to be persisted.
+#[derive(Debug)]
+struct SessionChanges {
+    /// Session ID to be written or removed.
+    id: Option<String>,
+    /// Previous session ID to be removed.
+    previous_id: Option<String>,
+    /// Session record to be written, or `None` if the session should be removed.
+    record: Option<SessionRecord>,
+}
+
+impl SessionStore {
+    /// Attempts to create a new instance with the config.
+    pub fn try_new(config: &Table) -> Result<Self, Error> {
+        let default_store = if cfg!(feature = "cache") {
+            "cache"
+        } else {
+            "accessor"
+        };
+        let backend = match config.get_str("store").unwrap_or(default_store) {
+            #[cfg(feature = "cache")]
+            "cache" => {
+                let namespace = config.get_str("namespace").unwrap_or("session");
+                let namespace = GlobalCache::namespace(namespace).ok_or_else(|| {
+                    Error::new(format!("the cache namespace `{namespace}` does not exist"))
+                })?;
+                SessionBackend::Cache(namespace)
+            }
+            #[cfg(feature = "accessor")]
+            "accessor" => {
+                let accessor = config
+                    .get_str("accessor")
+                    .ok_or_else(|| Error::new("the `accessor` field should be a str"))?;
+                let operator = GlobalAccessor::get(accessor)
+                    .ok_or_else(|| Error::new(format!("accessor `{accessor}` does not exist")))?;
+                let prefix = config.get_str("prefix").unwrap_or("sessions");
+                SessionBackend::Accessor(operator.clone(), prefix.trim_matches('/').to_owned())
+            }
+            store => {
+                let message = format!("session store `{store}` is unsupported");
+                return Err(Error::new(message));
+            }
+        };
+        Ok(Self {
+            backend,
+            ttl: config.get_duration("ttl").unwrap_or(DEFAULT_TTL),
+            cookie_name: config
+                .get_str("cookie-name")
+                .unwrap_or("session-id")
+                .to_owned(),
+        })
+    }
+
+    /// Returns a reference to the shared store configured by the `[session]` table,
+    /// or `None` if the sessions are not enabled.
+    ///
+    /// # Panics
+    ///
+    /// It will panic if the config is invalid.
+    #[inline]
+    pub fn shared() -> Option<&'static Self> {
+        SHARED_SESSION_STORE.as_ref()
+    }
+
+    /// Returns the idle timeout of the sessions.
+    #[inline]
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns the name of the cookie.
+    #[inline]
+    pub fn cookie_name(&self) -> &str {
+        self.cookie_name.as_str()
+    }
+
+    /// Loads the session with the ID. It returns `None` if the session
+    /// does not exist, has expired or belongs to another realm.
+    pub async fn load(&self, id: &str) -> Result<Option<Session>, Error> {
+        let Some(key) = storage_key(id) else {
+            return Ok(None);
+        };
+        let Some(record) = self.read(&key).await? else {
+            return Ok(None);
+        };
+        if record.expires_at <= DateTime::now().timestamp_millis() {
+            self.remove(&key).await?;
+            return Ok(None);
+        }
+        let session = Session::with_state(id.to_owned(), record.data, Some(record.expires_at));
+        Ok(Some(session))
+    }
+
+    /// Persists the changes of the session. A destroyed session is removed,
+    /// and the expiry is extended when more than half of the idle timeout has elapsed.
+    /// It returns `true` if the session cookie should be updated.
+    pub async fn commit(&self, session: &Session) -> Result<bool, Error> {
+        let now = DateTime::now().timestamp_millis();
+        let ttl = i64::try_from(self.ttl.as_millis()).unwrap_or(i64::MAX);
+        let Some(changes) = take_changes(session, now, ttl) else {
+            return Ok(false);
+        };
+        if let Some(key) = changes.previous_id.as_deref().and_then(storage_key) {
+            self.remove(&key).await?;
+        }
+        match (changes.id.as_deref().and_then(storage_key), changes.record) {
+            (Some(key), Some(record)) => {
+                self.write(&key, &record).await?;
+                Ok(true)
+            }
+            (Some(key), None) => {
+                self.remove(&key).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Reads the session record.
+    async fn read(&self, key: &str) -> Result<Option<SessionRecord>, Error> {
+        match &self.backend {
+            #[cfg(feature = "cache")]
+            SessionBackend::Cache(namespace) => namespace
+                .load(key)
+                .await?
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(Error::from),
+            #[cfg(feature = "accessor")]
+            SessionBackend::Accessor(operator, prefix) => {
+                match operator.read(&format!("{prefix}/{key}")).await {
+                    Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+                    Err(err) if err.kind() == NotFound => Ok(None),
+                    Err(err) => Err(err.into()),
+                }
+            }
+        }
+    }
+
+    /// Writes the session record.
+    async fn write(&self, key: &str, record: &SessionRecord) -> Result<(), Error> {
+        match &self.backend {
+            #[cfg(feature = "cache")]
+            SessionBackend::Cache(namespace) => {
+                let value = serde_json::to_value(record)?;
+                namespace.store_with_ttl(key, value, Some(self.ttl)).await?;
+            }
+            #[cfg(feature = "accessor")]
+            SessionBackend::Accessor(operator, prefix) => {
+                let bytes = serde_json::to_vec(record)?;
+                operator.write(&format!("{prefix}/{key}"), bytes).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the session record.
+    async fn remove(&self, key: &str) -> Result<(), Error> {
+        match &self.backend {
+            #[cfg(feature = "cache")]
+            SessionBackend::Cache(namespace) => {
+                namespace.invalidate(key).await?;
+            }
+            #[cfg(feature = "accessor")]
+            SessionBackend::Accessor(operator, prefix) => {
+                operator.delete(&format!("{prefix}/{key}")).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Takes the changes of the session to be persisted at the time `now`,
+/// returning `None` if there are no changes.
+fn take_changes(session: &Session, now: i64, ttl: i64) -> Option<SessionChanges> {
+    let mut state = session.state.lock();
+    let previous_id = state.previous_id.take();
+    if state.destroyed {
+        let persisted = state.expires_at.take().is_some();
+        let changes = SessionChanges {
+            id: persisted.then(|| state.id.clone()),
+            previous_id,
+            record: None,
+        };
+        return Some(changes);
+    }
+
+    let is_expiring = state
+        .expires_at
+        .is_some_and(|expires_at| expires_at - now < ttl / 2);
+    let is_empty = state.expires_at.is_none() && state.data.is_empty();
+    if is_empty || !(state.modified || is_expiring || previous_id.is_some()) {
+        return None;
+    }
+
+    let expires_at = now.saturating_add(ttl);
+    state.expires_at = Some(expires_at);
+    state.modified = false;
+
+    let record = SessionRecord {
+        data: state.data.clone(),
+        expires_at,
+    };
+    let changes = SessionChanges {
+        id: Some(state.id.clone()),
+        previous_id,
+        record: Some(record),
+    };
+    Some(changes)
+}
+
+/// Returns the storage key of the session ID if it belongs to the realm of the application.
+/// The identifier is converted to the URL-safe base64 alphabet so that it can be used in paths.
+fn storage_key(id: &str) -> Option<String> {
+    let session_id = SessionId::parse(id).ok()?;
+    (session_id.realm() == *APP_DOMAIN)
+        .then(|| session_id.identifier().replace('+', "-").replace('/', "_"))
+}
+
+/// Shared session store.
+static SHARED_SESSION_STORE: LazyLock<Option<SessionStore>> = LazyLock::new(|| {
+    let config = State::shared().config().get_table("session")?;
+    let store = SessionStore::try_new(config)
+        .unwrap_or_else(|err| panic!("fail to create the session store: {err}"));
+    Some(store)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::take_changes;
+    use crate::{extend::JsonObjectExt, session::Session, Map};
+
+    #[test]
+    fn it_takes_session_changes() {
+        let now = 1_700_000_000_000;
+        let ttl = 30 * 60 * 1000;
+        let mut session = Session::with_state("a".to_owned(), Map::new(), None);
+        assert!(take_changes(&session, now, ttl).is_none());
+
+        session.insert("user_id", "alice");
+        let changes = take_changes(&session, now, ttl).unwrap();
+        assert_eq!(changes.id.as_deref(), Some("a"));
+        assert_eq!(changes.record.unwrap().expires_at, now + ttl);
+        assert!(!session.is_modified());
+        assert!(take_changes(&session, now + ttl / 2, ttl).is_none());
+
+        let later = now + ttl / 2 + 1;
+        let changes = take_changes(&session, later, ttl).unwrap();
+        let record = changes.record.unwrap();
+        assert_eq!(record.expires_at, later + ttl);
+        assert_eq!(record.data.get_str("user_id"), Some("alice"));
+
+        {
+            let mut state = session.state.lock();
+            state.previous_id = Some(std::mem::replace(&mut state.id, "b".to_owned()));
+        }
+        let changes = take_changes(&session, later, ttl).unwrap();
+        assert_eq!(changes.id.as_deref(), Some("b"));
+        assert_eq!(changes.previous_id.as_deref(), Some("a"));
+        assert!(session.state.lock().previous_id.is_none());
+
+        session.destroy();
+        let changes = take_changes(&session, later, ttl).unwrap();
+        assert_eq!(changes.id.as_deref(), Some("b"));
+        assert!(changes.record.is_none());
+        assert!(session.is_new());
+
+        let changes = take_changes(&session, later, ttl).unwrap();
+        assert!(changes.id.is_none() && changes.record.is_none());
+    }
+}
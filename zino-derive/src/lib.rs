@@ -20,6 +20,9 @@ use syn::{parse_macro_input, Data, DeriveInput, Fields};
 mod parser;
 
 /// Derive the `Schema` trait.
+///
+/// The field-level access control is specified by the `read_roles` and `write_roles`
+/// attributes as a list of roles, e.g. `#[schema(read_roles = ["admin", "auditor"])]`.
 #[proc_macro_derive(Schema, attributes(schema))]
 pub fn schema_macro(item: TokenStream) -> TokenStream {
     /// Integer types
@@ -65,6 +68,8 @@ pub fn schema_macro(item: TokenStream) -> TokenStream {
                 let mut default_value = None;
                 let mut not_null = false;
                 let mut index_type = None;
+                let mut read_roles = Vec::new();
+                let mut write_roles = Vec::new();
                 for attr in field.attrs.iter() {
                     for (key, value) in parser::parse_attr(attr).into_iter() {
                         if key == "type_name" {
@@ -77,6 +82,14 @@ pub fn schema_macro(item: TokenStream) -> TokenStream {
                             default_value = value;
                         } else if key == "index" {
                             index_type = value;
+                        } else if key == "read_roles" {
+                            if let Some(value) = value {
+                                read_roles = parser::parse_str_list(&value);
+                            }
+                        } else if key == "write_roles" {
+                            if let Some(value) = value {
+                                write_roles = parser::parse_str_list(&value);
+                            }
                         }
                     }
                 }
@@ -107,8 +120,23 @@ pub fn schema_macro(item: TokenStream) -> TokenStream {
                 } else {
                     quote! { None }
                 };
+                let quote_read_roles = if read_roles.is_empty() {
+                    quote! {}
+                } else {
+                    quote! { column.set_read_roles(&[#(#read_roles),*]); }
+                };
+                let quote_write_roles = if write_roles.is_empty() {
+                    quote! {}
+                } else {
+                    quote! { column.set_write_roles(&[#(#write_roles),*]); }
+                };
                 let column = quote! {
-                    zino_core::model::Column::new(#name, #type_name, #quote_value, #not_null, #quote_index)
+                    {
+                        let mut column = zino_core::model::Column::new(#name, #type_name, #quote_value, #not_null, #quote_index);
+                        #quote_read_roles
+                        #quote_write_roles
+                        column
+                    }
                 };
                 columns.push(column);
             }
@@ -170,7 +198,9 @@ pub fn schema_macro(item: TokenStream) -> TokenStream {
             }
         });
         static #schema_columns: LazyLock<[Column; #columns_len]> = LazyLock::new(|| {
-            [#(#columns),*]
+            let columns = [#(#columns),*];
+            Column::register_read_roles(&columns);
+            columns
         });
         static #schema_reader: OnceLock<&ConnectionPool> = OnceLock::new();
         static #schema_writer: OnceLock<&ConnectionPool> = OnceLock::new();
@@ -1,4 +1,11 @@
-use syn::{Attribute, GenericArgument, Lit, Meta, NestedMeta, PathArguments, Type};
+use syn::{
+    bracketed,
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    token::Bracket,
+    Attribute, GenericArgument, Ident, Lit, PathArguments, Token, Type,
+};
 
 /// Returns the Postgres type name as a str.
 pub(crate) fn get_type_name(ty: &Type) -> String {
@@ -15,33 +22,67 @@ pub(crate) fn get_type_name(ty: &Type) -> String {
     }
 }
 
+/// An argument of the `schema` attribute, which is a flag such as `not_null`,
+/// or a key-value pair such as `index = "gin"` and `read_roles = ["admin"]`.
+struct SchemaArgument {
+    /// Key.
+    key: String,
+    /// Optional value. A list of values is joined with commas.
+    value: Option<String>,
+}
+
+impl Parse for SchemaArgument {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key = input.call(Ident::parse_any)?.to_string();
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            if input.peek(Bracket) {
+                let content;
+                bracketed!(content in input);
+                let values = Punctuated::<Lit, Token![,]>::parse_terminated(&content)?;
+                let values = values.iter().filter_map(parse_lit).collect::<Vec<_>>();
+                Some(values.join(","))
+            } else {
+                parse_lit(&input.parse()?)
+            }
+        } else {
+            None
+        };
+        Ok(Self { key, value })
+    }
+}
+
 /// Parses an attribute and returns a list of arguments
 pub(crate) fn parse_attr(attr: &Attribute) -> Vec<(String, Option<String>)> {
-    if let Ok(meta) = attr.parse_meta() {
-        if let Some(ident) = meta.path().get_ident() && *ident != "schema" {
-            return Vec::new();
-        }
-        if let Meta::List(list) = meta {
-            let mut arguments = Vec::new();
-            for nested_meta in list.nested.iter() {
-                if let NestedMeta::Meta(meta) = nested_meta {
-                    if let Some(ident) = meta.path().get_ident() {
-                        let key = ident.to_string();
-                        let value = match meta {
-                            Meta::NameValue(value) => match value.lit {
-                                Lit::Str(ref lit_str) => Some(lit_str.value()),
-                                Lit::Bool(ref lit_bool) => Some(lit_bool.value.to_string()),
-                                Lit::Int(ref lit_int) => Some(lit_int.base10_digits().to_string()),
-                                _ => None,
-                            },
-                            _ => None,
-                        };
-                        arguments.push((key, value));
-                    }
-                }
-            }
-            return arguments;
-        }
+    if !attr.path.is_ident("schema") {
+        return Vec::new();
     }
-    Vec::new()
+    attr.parse_args_with(Punctuated::<SchemaArgument, Token![,]>::parse_terminated)
+        .map(|arguments| {
+            arguments
+                .into_iter()
+                .map(|argument| (argument.key, argument.value))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parses a literal as a `String`.
+fn parse_lit(lit: &Lit) -> Option<String> {
+    match lit {
+        Lit::Str(lit_str) => Some(lit_str.value()),
+        Lit::Bool(lit_bool) => Some(lit_bool.value.to_string()),
+        Lit::Int(lit_int) => Some(lit_int.base10_digits().to_string()),
+        _ => None,
+    }
+}
+
+/// Parses a comma-separated str and returns a list of nonempty values.
+pub(crate) fn parse_str_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_owned())
+        .collect()
 }
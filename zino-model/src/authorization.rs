@@ -0,0 +1,410 @@
+use crate::{Group, ModelAccessor, Policy, User};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    sync::{Arc, LazyLock, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use toml::Table;
+use zino_core::{
+    cache::CacheNamespace,
+    database::Schema,
+    datetime::DateTime,
+    error::Error,
+    extend::{JsonObjectExt, TomlTableExt},
+    model::Query,
+    request::{AccessGuard, AccessRequest, RequestContext},
+    response::Rejection,
+    state::State,
+    BoxFuture, Map, Uuid,
+};
+
+/// Default capacity of the decision cache.
+const DEFAULT_CAPACITY: usize = 10000;
+
+/// Default time-to-live of the cached decisions.
+const DEFAULT_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Number of rows per page to load the policies and groups.
+const PAGE_SIZE: u64 = 1000;
+
+/// A policy decision point which evaluates the [`Policy`] models for the users.
+///
+/// A policy applies to a user if its `group_id` is nil or refers to a [`Group`]
+/// containing the user, and the `roles` array in its `content` is empty or
+/// contains a role of the user. A `deny` policy takes precedence over an `allow` policy,
+/// and the action is denied if no policy applies. Superusers are always allowed.
+pub struct Authorizer {
+    /// Policies and group memberships.
+    policy_set: RwLock<Option<Arc<PolicySet>>>,
+    /// Cached decisions.
+    decisions: CacheNamespace,
+    /// Interval for reloading the policies and group memberships.
+    refresh_interval: Duration,
+    /// Lock for coalescing the concurrent reloads.
+    reload_lock: Mutex<()>,
+}
+
+/// Policies and group memberships.
+struct PolicySet {
+    /// Active policies.
+    policies: Vec<Policy>,
+    /// Group IDs of the members.
+    memberships: HashMap<Uuid, Vec<Uuid>>,
+    /// Loading time.
+    loaded_at: Instant,
+}
+
+impl Authorizer {
+    /// Creates a new instance.
+    pub fn new() -> Self {
+        let capacity = NonZeroUsize::new(DEFAULT_CAPACITY).unwrap_or(NonZeroUsize::MIN);
+        let mut decisions = CacheNamespace::new("authorization", capacity);
+        decisions.set_ttl(DEFAULT_TTL);
+        Self {
+            policy_set: RwLock::new(None),
+            decisions,
+            refresh_interval: DEFAULT_TTL,
+            reload_lock: Mutex::new(()),
+        }
+    }
+
+    /// Creates a new instance with the config.
+    /// The decision cache is specified by `capacity` and `ttl`, and the policies are
+    /// reloaded from the database every `refresh-interval`, both of which are `5m` by default.
+    pub fn with_config(config: &Table) -> Self {
+        let mut decisions = CacheNamespace::with_config("authorization", config);
+        if decisions.ttl().is_none() {
+            decisions.set_ttl(DEFAULT_TTL);
+        }
+        Self {
+            policy_set: RwLock::new(None),
+            decisions,
+            refresh_interval: config
+                .get_duration("refresh-interval")
+                .unwrap_or(DEFAULT_TTL),
+            reload_lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns a reference to the shared instance configured by the `[authorization]` table.
+    #[inline]
+    pub fn shared() -> &'static Self {
+        LazyLock::force(&SHARED_AUTHORIZER)
+    }
+
+    /// Reloads the active policies and group memberships from the database,
+    /// and clears the cached decisions.
+    pub async fn reload(&self) -> Result<(), Error> {
+        let mut filters = Map::new();
+        filters.upsert("status", "active");
+        let policies = find_all::<Policy>(filters.clone()).await?;
+
+        filters.upsert("subject", User::model_name());
+        let groups = find_all::<Group>(filters).await?;
+        self.set_policies(policies, groups);
+        Ok(())
+    }
+
+    /// Sets the policies and group memberships, and clears the cached decisions.
+    pub fn set_policies(&self, policies: Vec<Policy>, groups: Vec<Group>) {
+        let mut memberships = HashMap::<Uuid, Vec<Uuid>>::new();
+        for group in groups.iter() {
+            for &member_id in group.members() {
+                memberships.entry(member_id).or_default().push(group.id());
+            }
+        }
+
+        let policy_set = PolicySet {
+            policies,
+            memberships,
+            loaded_at: Instant::now(),
+        };
+        match self.policy_set.write() {
+            Ok(mut guard) => *guard = Some(Arc::new(policy_set)),
+            Err(err) => *err.into_inner() = Some(Arc::new(policy_set)),
+        }
+        self.decisions.clear();
+    }
+
+    /// Returns `true` if the user is allowed to perform the action on the resource.
+    /// The policies are loaded if they are missing or stale.
+    pub async fn is_allowed(
+        &self,
+        user: &User,
+        resource: &str,
+        action: &str,
+    ) -> Result<bool, Error> {
+        if user.is_locked() || user.is_deleted() {
+            return Ok(false);
+        } else if user.is_superuser() {
+            return Ok(true);
+        }
+
+        let key = format!(
+            "{}:{}:{action}:{resource}",
+            user.id(),
+            user.roles().join(",")
+        );
+        if let Some(Value::Bool(allowed)) = self.decisions.get(&key) {
+            return Ok(allowed);
+        }
+
+        let policy_set = match self.fresh_policy_set() {
+            Some(policy_set) => policy_set,
+            None => {
+                // Only one of the concurrent requests reloads the policies,
+                // and the others wait for it and reuse the result.
+                let _guard = self.reload_lock.lock().await;
+                match self.fresh_policy_set() {
+                    Some(policy_set) => policy_set,
+                    None => {
+                        self.reload().await?;
+                        self.policy_set()
+                            .ok_or_else(|| Error::new("fail to load the policies"))?
+                    }
+                }
+            }
+        };
+        let allowed = policy_set.decide(user, resource, action, DateTime::now());
+        self.decisions.put(key, allowed);
+        Ok(allowed)
+    }
+
+    /// Checks whether the user is allowed to perform the action on the resource.
+    /// It rejects with `Forbidden` if the action is not permitted.
+    async fn check_action(
+        &self,
+        user: &User,
+        resource: &str,
+        action: &str,
+    ) -> Result<(), Rejection<'static>> {
+        match self.is_allowed(user, resource, action).await {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                let message = format!("the action `{action}` on `{resource}` is not permitted");
+                Err(Rejection::forbidden(Error::new(message)))
+            }
+            Err(err) => Err(Rejection::internal_server_error(err)),
+        }
+    }
+
+    /// Returns the policies and group memberships if they are not stale.
+    fn fresh_policy_set(&self) -> Option<Arc<PolicySet>> {
+        self.policy_set()
+            .filter(|policy_set| policy_set.loaded_at.elapsed() < self.refresh_interval)
+    }
+
+    /// Returns the policies and group memberships.
+    fn policy_set(&self) -> Option<Arc<PolicySet>> {
+        match self.policy_set.read() {
+            Ok(guard) => guard.clone(),
+            Err(err) => err.into_inner().clone(),
+        }
+    }
+}
+
+impl Default for Authorizer {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PolicySet {
+    /// Decides whether the user is allowed to perform the action on the resource at the time.
+    fn decide(&self, user: &User, resource: &str, action: &str, time: DateTime) -> bool {
+        let group_ids = self
+            .memberships
+            .get(&user.id())
+            .map(|ids| ids.as_slice())
+            .unwrap_or_default();
+        let mut allowed = false;
+        for policy in self.policies.iter() {
+            let group_id = policy.group_id();
+            if (group_id.is_nil() || group_ids.contains(&group_id))
+                && policy.applies_to_roles(user)
+                && policy.is_valid_at(time)
+                && policy.matches(resource, action)
+            {
+                if policy.is_deny() {
+                    return false;
+                }
+                allowed = true;
+            }
+        }
+        allowed
+    }
+}
+
+impl AccessGuard for Authorizer {
+    type Principal = User;
+
+    fn check<'a>(
+        &'a self,
+        access: &'a AccessRequest,
+    ) -> BoxFuture<'a, Result<User, Rejection<'static>>> {
+        Box::pin(async move {
+            let user = resolve_user(access.subject()).await?;
+            self.check_action(&user, access.resource(), access.action())
+                .await?;
+            Ok(user)
+        })
+    }
+}
+
+/// Extension trait for authorizing the requests with the shared [`Authorizer`].
+pub trait AuthorizeRequest: RequestContext {
+    /// Resolves the current user, which is identified by the `user_id` in the session
+    /// or the `sub` claim of the JSON Web Token.
+    /// It rejects with `Unauthorized` if the user can not be resolved.
+    async fn current_user(&self) -> Result<User, Rejection> {
+        let access = AccessRequest::extract(self);
+        resolve_user(access.subject())
+            .await
+            .map_err(|rejection| rejection.provide_context(self))
+    }
+
+    /// Resolves the current user and authorizes the request.
+    /// It rejects with `Unauthorized` if the user can not be resolved,
+    /// or `Forbidden` if the action is not permitted.
+    async fn authorize_user(&self) -> Result<User, Rejection> {
+        let user = self.current_user().await?;
+        self.authorize(&user).await?;
+        Ok(user)
+    }
+
+    /// Authorizes the user to perform the action on the resource,
+    /// which are the lowercase request method and the request path.
+    async fn authorize(&self, user: &User) -> Result<(), Rejection> {
+        let access = AccessRequest::extract(self);
+        self.authorize_action(user, access.resource(), access.action())
+            .await
+    }
+
+    /// Authorizes the user to perform the action on the resource.
+    /// It rejects with `Forbidden` if the action is not permitted.
+    async fn authorize_action(
+        &self,
+        user: &User,
+        resource: &str,
+        action: &str,
+    ) -> Result<(), Rejection> {
+        Authorizer::shared()
+            .check_action(user, resource, action)
+            .await
+            .map_err(|rejection| rejection.provide_context(self))
+    }
+}
+
+impl<T: RequestContext + ?Sized> AuthorizeRequest for T {}
+
+/// Finds all the models matching the filters page by page.
+async fn find_all<M: Schema + DeserializeOwned>(filters: Map) -> Result<Vec<M>, Error> {
+    let mut query = Query::new(filters);
+    query.set_sort_order("id".to_owned(), true);
+    query.set_limit(PAGE_SIZE);
+
+    let mut models = Vec::new();
+    let mut offset = 0;
+    loop {
+        query.set_offset(offset);
+        let page = M::find_as::<M>(&query).await?;
+        let num_rows = page.len() as u64;
+        models.extend(page);
+        if num_rows < PAGE_SIZE {
+            break;
+        }
+        offset += num_rows;
+    }
+    Ok(models)
+}
+
+/// Resolves the user identified by the subject.
+/// It rejects with `Unauthorized` if the user can not be resolved.
+async fn resolve_user(subject: Option<&str>) -> Result<User, Rejection<'static>> {
+    let Some(user_id) = subject.and_then(|s| s.parse::<Uuid>().ok()) else {
+        let err = Error::new("the user is not authenticated");
+        return Err(Rejection::unauthorized(err));
+    };
+    let mut filters = Map::new();
+    filters.upsert("id", user_id.to_string());
+    match User::find_one_as::<User>(&Query::new(filters)).await {
+        Ok(Some(user)) if !user.is_deleted() => Ok(user),
+        Ok(_) => {
+            let message = format!("the user `{user_id}` does not exist");
+            Err(Rejection::unauthorized(Error::new(message)))
+        }
+        Err(err) => Err(Rejection::internal_server_error(err)),
+    }
+}
+
+/// Shared authorizer.
+static SHARED_AUTHORIZER: LazyLock<Authorizer> = LazyLock::new(|| {
+    let state = State::default();
+    if let Some(config) = state.config().get_table("authorization") {
+        Authorizer::with_config(config)
+    } else {
+        Authorizer::new()
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::PolicySet;
+    use crate::{Group, ModelAccessor, Policy, User};
+    use std::{collections::HashMap, time::Instant};
+    use zino_core::{datetime::DateTime, extend::JsonObjectExt, model::Model, Map};
+
+    #[test]
+    fn it_decides_by_policies() {
+        let mut alice = User::new();
+        let mut data = Map::new();
+        data.upsert("name", "alice");
+        data.upsert("roles", vec!["worker"]);
+        assert!(alice.read_map(&data).is_success());
+
+        let mut group = Group::new();
+        let mut data = Map::new();
+        data.upsert("name", "workers");
+        data.upsert("members", vec![alice.id().to_string()]);
+        assert!(group.read_map(&data).is_success());
+
+        let mut allow_policy = Policy::new();
+        let mut data = Map::new();
+        data.upsert("name", "allow-tasks");
+        data.upsert("group_id", group.id().to_string());
+        data.upsert("resource", "tasks/*");
+        data.upsert("actions", vec!["get", "post"]);
+        data.upsert("effect", "allow");
+        assert!(allow_policy.read_map(&data).is_success());
+
+        let mut deny_policy = Policy::new();
+        let mut data = Map::new();
+        data.upsert("name", "deny-archived-tasks");
+        data.upsert("resource", "tasks/archived/*");
+        data.upsert("actions", vec!["*"]);
+        data.upsert("effect", "deny");
+        assert!(deny_policy.read_map(&data).is_success());
+
+        let mut memberships = HashMap::new();
+        memberships.insert(alice.id(), vec![group.id()]);
+        let policy_set = PolicySet {
+            policies: vec![allow_policy, deny_policy],
+            memberships,
+            loaded_at: Instant::now(),
+        };
+        let now = DateTime::now();
+        assert!(policy_set.decide(&alice, "tasks/1", "get", now));
+        assert!(policy_set.decide(&alice, "tasks/1", "post", now));
+        assert!(!policy_set.decide(&alice, "tasks/1", "delete", now));
+        assert!(!policy_set.decide(&alice, "tasks/archived/1", "get", now));
+        assert!(!policy_set.decide(&alice, "users/1", "get", now));
+
+        let bob = User::new();
+        assert!(!policy_set.decide(&bob, "tasks/1", "get", now));
+    }
+}
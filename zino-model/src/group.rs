@@ -67,6 +67,12 @@ impl Model for Group {
         if self.name.is_empty() {
             validation.record("name", "should be nonempty");
         }
+        if let Some(subject) = Validation::parse_string(data.get("subject")) {
+            self.subject = subject;
+        }
+        if let Some(members) = Validation::parse_array(data.get("members")) {
+            self.members = members;
+        }
         validation
     }
 }
@@ -89,3 +95,23 @@ super::impl_model_accessor!(
     version,
     edition
 );
+
+impl Group {
+    /// Returns the `subject` field.
+    #[inline]
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Returns the `members` field.
+    #[inline]
+    pub fn members(&self) -> &[Uuid] {
+        self.members.as_slice()
+    }
+
+    /// Returns `true` if the group contains the member.
+    #[inline]
+    pub fn has_member(&self, member_id: Uuid) -> bool {
+        self.members.contains(&member_id)
+    }
+}
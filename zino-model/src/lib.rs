@@ -18,6 +18,7 @@
 
 use zino_core::{datetime::DateTime, extend::JsonObjectExt, model::Model, Map, Uuid};
 
+mod authorization;
 mod group;
 mod policy;
 mod resource;
@@ -35,6 +36,7 @@ mod task;
 mod log;
 mod record;
 
+pub use authorization::{AuthorizeRequest, Authorizer};
 pub use group::Group;
 pub use policy::Policy;
 pub use resource::Resource;
@@ -1,7 +1,14 @@
+use crate::User;
 use serde::{Deserialize, Serialize};
-use zino_core::{datetime::DateTime, model::Model, request::Validation, Map, Uuid};
+use zino_core::{
+    datetime::DateTime, extend::JsonObjectExt, model::Model, request::Validation, Map, Uuid,
+};
 use zino_derive::Schema;
 
+/// Unix timestamp of `9999-12-31T23:59:59Z`, which is the default `expires_at`
+/// for the policies never expiring.
+const NEVER_EXPIRES_AT: i64 = 253402300799;
+
 /// The policy model.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, Schema)]
 #[serde(rename_all = "snake_case")]
@@ -22,9 +29,11 @@ pub struct Policy {
 
     // Info fields.
     tenant_id: Uuid, // group.id, group.namespace = "*:policy", group.subject = "user"
+    group_id: Uuid,  // group.id, group.subject = "user"
     #[schema(not_null)]
     resource: String,
     actions: Vec<String>,
+    #[schema(default = "allow")]
     effect: String,
     valid_from: DateTime,
     expires_at: DateTime,
@@ -51,6 +60,7 @@ impl Model for Policy {
     fn new() -> Self {
         Self {
             id: Uuid::new_v4(),
+            expires_at: DateTime::from_timestamp(NEVER_EXPIRES_AT),
             ..Self::default()
         }
     }
@@ -69,6 +79,46 @@ impl Model for Policy {
         if self.name.is_empty() {
             validation.record("name", "should be nonempty");
         }
+        if let Some(result) = Validation::parse_uuid(data.get("tenant_id")) {
+            match result {
+                Ok(tenant_id) => self.tenant_id = tenant_id,
+                Err(err) => validation.record_fail("tenant_id", err),
+            }
+        }
+        if let Some(result) = Validation::parse_uuid(data.get("group_id")) {
+            match result {
+                Ok(group_id) => self.group_id = group_id,
+                Err(err) => validation.record_fail("group_id", err),
+            }
+        }
+        if let Some(resource) = Validation::parse_string(data.get("resource")) {
+            self.resource = resource;
+        }
+        if self.resource.is_empty() {
+            validation.record("resource", "should be nonempty");
+        }
+        if let Some(actions) = Validation::parse_array(data.get("actions")) {
+            self.actions = actions;
+        }
+        if let Some(effect) = Validation::parse_string(data.get("effect")) {
+            if effect == "allow" || effect == "deny" {
+                self.effect = effect;
+            } else {
+                validation.record("effect", "should be `allow` or `deny`");
+            }
+        }
+        if let Some(result) = Validation::parse_datetime(data.get("valid_from")) {
+            match result {
+                Ok(valid_from) => self.valid_from = valid_from,
+                Err(err) => validation.record_fail("valid_from", err),
+            }
+        }
+        if let Some(result) = Validation::parse_datetime(data.get("expires_at")) {
+            match result {
+                Ok(expires_at) => self.expires_at = expires_at,
+                Err(err) => validation.record_fail("expires_at", err),
+            }
+        }
         validation
     }
 }
@@ -91,3 +141,138 @@ super::impl_model_accessor!(
     version,
     edition
 );
+
+impl Policy {
+    /// Returns the `tenant_id` field.
+    #[inline]
+    pub fn tenant_id(&self) -> Uuid {
+        self.tenant_id
+    }
+
+    /// Returns the `group_id` field, which refers to a group of the users
+    /// the policy applies to.
+    #[inline]
+    pub fn group_id(&self) -> Uuid {
+        self.group_id
+    }
+
+    /// Returns the `resource` field.
+    #[inline]
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+
+    /// Returns the `actions` field.
+    #[inline]
+    pub fn actions(&self) -> &[String] {
+        self.actions.as_slice()
+    }
+
+    /// Returns the `effect` field.
+    #[inline]
+    pub fn effect(&self) -> &str {
+        &self.effect
+    }
+
+    /// Returns `true` if the `effect` is `deny`.
+    #[inline]
+    pub fn is_deny(&self) -> bool {
+        self.effect == "deny"
+    }
+
+    /// Returns `true` if the policy is valid at the time. The policy is expired
+    /// if `expires_at` is not later than both `valid_from` and `created_at`.
+    /// A new policy never expires unless `expires_at` is specified.
+    pub fn is_valid_at(&self, time: DateTime) -> bool {
+        let valid_from = self.valid_from.timestamp_millis();
+        let expires_at = self.expires_at.timestamp_millis();
+        let created_at = self.created_at.timestamp_millis();
+        let time = time.timestamp_millis();
+        time >= valid_from && time < expires_at && expires_at > valid_from.max(created_at)
+    }
+
+    /// Returns `true` if the policy applies to the resource and action.
+    /// The resource pattern supports the `*` wildcard, and the action `*` matches any action.
+    pub fn matches(&self, resource: &str, action: &str) -> bool {
+        let action_matched = self
+            .actions
+            .iter()
+            .any(|a| a == "*" || a.eq_ignore_ascii_case(action));
+        action_matched && match_wildcard(&self.resource, resource)
+    }
+
+    /// Returns `true` if the policy applies to the roles of the user.
+    /// The roles can be restricted by the `roles` array in the `content` field.
+    pub fn applies_to_roles(&self, user: &User) -> bool {
+        match self.content.get_array("roles") {
+            Some(roles) if !roles.is_empty() => roles
+                .iter()
+                .filter_map(|v| v.as_str())
+                .any(|role| user.has_role(role)),
+            _ => true,
+        }
+    }
+}
+
+/// Returns `true` if the text matches the pattern, in which `*` matches any sequence.
+fn match_wildcard(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return text.is_empty();
+    };
+    let Some(mut remainder) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        return remainder.is_empty();
+    };
+    for part in middle {
+        match remainder.find(part) {
+            Some(index) => remainder = &remainder[index + part.len()..],
+            None => return false,
+        }
+    }
+    remainder.len() >= last.len() && remainder.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{match_wildcard, Policy};
+    use zino_core::{datetime::DateTime, extend::JsonObjectExt, model::Model, Map};
+
+    #[test]
+    fn it_checks_policy_expiry() {
+        let now = DateTime::now();
+        let policy = Policy::new();
+        assert!(policy.is_valid_at(now));
+
+        let mut policy = Policy::new();
+        let mut data = Map::new();
+        data.upsert("name", "expired");
+        data.upsert("resource", "*");
+        data.upsert("valid_from", "2023-01-01T00:00:00Z");
+        data.upsert("expires_at", "2023-01-01T00:00:00Z");
+        assert!(policy.read_map(&data).is_success());
+        assert!(!policy.is_valid_at(now));
+        assert!(!policy.is_valid_at(DateTime::from_timestamp(1672531200)));
+
+        data.upsert("expires_at", "2999-01-01T00:00:00Z");
+        assert!(policy.read_map(&data).is_success());
+        assert!(policy.is_valid_at(now));
+        assert!(!policy.is_valid_at(DateTime::from_timestamp(32472144000)));
+    }
+
+    #[test]
+    fn it_matches_wildcard_resources() {
+        assert!(match_wildcard("*", "users/1"));
+        assert!(match_wildcard("users/*", "users/1"));
+        assert!(match_wildcard("users/*/tags", "users/1/tags"));
+        assert!(match_wildcard("users/*/tags/*", "users/1/tags/2"));
+        assert!(match_wildcard("users", "users"));
+        assert!(!match_wildcard("users", "users/1"));
+        assert!(!match_wildcard("users/*", "tasks/1"));
+        assert!(!match_wildcard("users/*/tags", "users/1/groups"));
+        assert!(!match_wildcard("a*a", "a"));
+    }
+}
@@ -64,6 +64,15 @@ impl Model for Record {
         if self.name.is_empty() {
             validation.record("name", "should be nonempty");
         }
+        if let Some(integrity) = Validation::parse_string(data.get("integrity")) {
+            self.integrity = integrity;
+        }
+        if let Some(signature) = Validation::parse_string(data.get("signature")) {
+            self.signature = signature;
+        }
+        if let Some(content) = Validation::parse_object(data.get("content")) {
+            self.content = content.clone();
+        }
         validation
     }
 }
@@ -68,6 +68,18 @@ impl Model for Resource {
         if self.name.is_empty() {
             validation.record("name", "should be nonempty");
         }
+        if let Some(category) = Validation::parse_string(data.get("category")) {
+            self.category = category;
+        }
+        if let Some(mime_type) = Validation::parse_string(data.get("mime_type")) {
+            self.mime_type = mime_type;
+        }
+        if let Some(location) = Validation::parse_string(data.get("location")) {
+            self.location = location;
+        }
+        if let Some(content) = Validation::parse_object(data.get("content")) {
+            self.content = content.clone();
+        }
         validation
     }
 }
@@ -1,8 +1,13 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
 use zino_core::{
-    authentication::AccessKeyId, datetime::DateTime, error::Error, model::Model,
-    request::Validation, Map, Uuid,
+    authentication::{AccessKeyId, Credentials},
+    datetime::DateTime,
+    error::Error,
+    model::Model,
+    request::Validation,
+    Map, Uuid,
 };
 use zino_derive::Schema;
 
@@ -25,19 +30,26 @@ pub struct User {
     description: String,
 
     // Info fields.
-    #[schema(not_null)]
+    #[schema(not_null, read_roles = ["admin"])]
     access_key_id: String,
     #[schema(not_null)]
     account: String,
-    #[schema(not_null)]
+    #[schema(not_null, read_roles = ["admin"])]
     password: String,
     mobile: String,
     email: String,
     avatar: String,
+    #[schema(write_roles = ["admin"])]
     roles: Vec<String>,
     #[schema(index = "gin")]
     tags: Vec<Uuid>, // tag.id, tag.namespace = "*:user"
 
+    // Security.
+    #[schema(write_roles = ["admin"])]
+    failed_login_attempts: u32,
+    #[schema(default = "now", write_roles = ["admin"])]
+    locked_until: DateTime,
+
     // Extensions.
     content: Map,
     metrics: Map,
@@ -77,6 +89,15 @@ impl Model for User {
         if self.name.is_empty() {
             validation.record("name", "should be nonempty");
         }
+        if let Some(account) = Validation::parse_string(data.get("account")) {
+            self.account = account;
+        }
+        if let Some(email) = Validation::parse_string(data.get("email")) {
+            self.email = email;
+        }
+        if let Some(avatar) = Validation::parse_string(data.get("avatar")) {
+            self.avatar = avatar;
+        }
         if let Some(roles) = Validation::parse_array(data.get("roles")) {
             if let Err(err) = self.set_roles(roles) {
                 validation.record_fail("roles", err);
@@ -85,6 +106,9 @@ impl Model for User {
         if self.roles.is_empty() && !validation.contains_key("roles") {
             validation.record("roles", "should be nonempty");
         }
+        if let Some(content) = Validation::parse_object(data.get("content")) {
+            self.content = content.clone();
+        }
         validation
     }
 }
@@ -127,6 +151,74 @@ impl User {
         Ok(())
     }
 
+    /// Sets the `password` of the user. The password is checked for the strength
+    /// and hashed by the shared [`Credentials`] on a blocking thread.
+    /// A value which is already a hash in the PHC string format is skipped.
+    ///
+    /// The `password` field is not read by [`read_map()`](Model::read_map),
+    /// so this method should be called to set the password.
+    pub async fn set_password_async(&mut self, password: &str) -> Result<(), Error> {
+        let credentials = Credentials::shared();
+        if credentials.is_password_hash(password) {
+            return Ok(());
+        }
+        credentials.check_strength(password)?;
+        self.password = credentials.hash_password_async(password).await?;
+        Ok(())
+    }
+
+    /// Verifies the password of the user on a separate thread. The password is rehashed
+    /// if the parameters have been changed or it is a legacy plaintext password,
+    /// and the consecutive failures are tracked to lock the account temporarily.
+    /// The user should be updated afterwards.
+    pub async fn verify_password(&mut self, password: &str) -> Result<bool, Error> {
+        let now = DateTime::now();
+        if self.locked_until() > Some(now) {
+            return Err(Error::new("the account is temporarily locked"));
+        }
+
+        let credentials = Credentials::shared();
+        if credentials
+            .verify_password_async(password, &self.password)
+            .await?
+        {
+            if credentials.needs_rehash(&self.password) {
+                self.password = credentials.hash_password_async(password).await?;
+            }
+            self.failed_login_attempts = 0;
+            Ok(true)
+        } else {
+            let failures = self.failed_login_attempts + 1;
+            if failures >= credentials.max_failures() {
+                self.failed_login_attempts = 0;
+                self.locked_until = now + credentials.lockout_duration();
+            } else {
+                self.failed_login_attempts = failures;
+            }
+            Ok(false)
+        }
+    }
+
+    /// Returns the `password` hash.
+    #[inline]
+    pub fn password(&self) -> &str {
+        self.password.as_str()
+    }
+
+    /// Returns the number of the consecutive failures of the password verification.
+    #[inline]
+    pub fn failed_login_attempts(&self) -> u32 {
+        self.failed_login_attempts
+    }
+
+    /// Returns the time until which the account is locked due to the failures
+    /// of the password verification.
+    #[inline]
+    pub fn locked_until(&self) -> Option<DateTime> {
+        let locked_until = self.locked_until;
+        (locked_until > DateTime::now()).then_some(locked_until)
+    }
+
     /// Returns the `roles` field.
     #[inline]
     pub fn roles(&self) -> &[String] {
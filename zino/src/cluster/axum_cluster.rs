@@ -111,6 +111,20 @@ impl Application for AxumCluster {
                         "/websocket",
                         routing::get(crate::endpoint::axum_websocket::websocket_handler),
                     );
+                #[cfg(feature = "cache")]
+                {
+                    app = app.route(
+                        "/cache/events",
+                        routing::post(crate::endpoint::axum_cache::cache_event_handler),
+                    );
+                }
+                #[cfg(feature = "connector")]
+                {
+                    app = app.route(
+                        "/stats/connectors",
+                        routing::get(crate::endpoint::axum_connector::connector_status_handler),
+                    );
+                }
                 for route in &routes {
                     app = app.merge(route.clone());
                 }
@@ -143,6 +157,12 @@ impl Application for AxumCluster {
                             .layer(middleware::from_fn(
                                 crate::middleware::axum_context::request_context,
                             ))
+                            .layer(middleware::from_fn(
+                                crate::middleware::axum_cache::http_cache,
+                            ))
+                            .layer(middleware::from_fn(
+                                crate::middleware::axum_session::session,
+                            ))
                             .layer(HandleErrorLayer::new(|err: BoxError| async move {
                                 let status_code = if err.is::<Elapsed>() {
                                     StatusCode::REQUEST_TIMEOUT
@@ -0,0 +1,22 @@
+use axum::{
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+};
+use zino_core::{
+    cache::{GlobalCache, EVENT_SIGNATURE_HEADER},
+    extend::HeaderMapExt,
+};
+
+/// Cache invalidation events endpoint handler.
+pub(crate) async fn cache_event_handler(headers: HeaderMap, body: Bytes) -> StatusCode {
+    let Some(signature) = headers.get_str(EVENT_SIGNATURE_HEADER) else {
+        return StatusCode::UNAUTHORIZED;
+    };
+    match GlobalCache::handle_signed_event(&body, signature) {
+        Ok(_) => StatusCode::ACCEPTED,
+        Err(err) => {
+            tracing::warn!("fail to handle the cache invalidation: {err}");
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use zino_core::{
+    connector::GlobalConnector, error::Error, request::RequestContext, response::Rejection,
+};
+
+/// Data source status endpoint handler.
+/// It is only permitted for the users with the `admin` or `superuser` role.
+pub(crate) async fn connector_status_handler(req: crate::Request) -> crate::Result {
+    let roles = req.user_roles();
+    if roles.is_empty() {
+        let err = Error::new("the user is not authenticated");
+        return Err(Rejection::unauthorized(err).provide_context(&req).into());
+    } else if !roles
+        .iter()
+        .any(|role| role == "admin" || role == "superuser")
+    {
+        let err = Error::new("the data source status is only permitted for the admins");
+        return Err(Rejection::forbidden(err).provide_context(&req).into());
+    }
+
+    let mut res = crate::Response::default().provide_context(&req);
+    res.set_data(&GlobalConnector::status());
+    Ok(res.into())
+}
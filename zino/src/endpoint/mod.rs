@@ -1,3 +1,9 @@
+#[cfg(all(feature = "axum", feature = "cache"))]
+pub(crate) mod axum_cache;
+
+#[cfg(all(feature = "axum", feature = "connector"))]
+pub(crate) mod axum_connector;
+
 #[cfg(feature = "axum")]
 pub(crate) mod axum_sse;
 
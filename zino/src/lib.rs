@@ -36,6 +36,7 @@
 
 #![feature(async_fn_in_trait)]
 #![feature(doc_auto_cfg)]
+#![feature(let_chains)]
 #![feature(once_cell)]
 #![feature(result_option_inspect)]
 #![feature(string_leak)]
@@ -64,6 +65,8 @@ pub use zino_core::{
 #[cfg(feature = "axum")]
 pub use cluster::axum_cluster::AxumCluster;
 #[cfg(feature = "axum")]
+pub use middleware::axum_guard::access_guard;
+#[cfg(feature = "axum")]
 pub use request::axum_request::AxumExtractor;
 
 /// A specialized request extractor for `axum`.
@@ -0,0 +1,111 @@
+use axum::{
+    body::{self, Body, BoxBody, Full, HttpBody},
+    http::{
+        header, response::Parts, HeaderMap, HeaderValue, Method, Request, Response, StatusCode,
+    },
+    middleware::Next,
+};
+use zino_core::response::{self, CachePolicy};
+
+pub(crate) async fn http_cache(
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Result<Response<BoxBody>, StatusCode> {
+    let method = req.method().clone();
+    if method != Method::GET && method != Method::HEAD {
+        return Ok(next.run(req).await);
+    }
+
+    let headers = req.headers().clone();
+    let policy = CachePolicy::get(req.uri().path());
+
+    // Serve the cached response.
+    #[cfg(feature = "cache")]
+    let cache_key = policy
+        .filter(|policy| policy.ttl().is_some())
+        .and_then(|policy| policy.cache_key(&method, req.uri(), &headers));
+    #[cfg(feature = "cache")]
+    if let Some(policy) = policy
+        && let Some(key) = cache_key.as_deref()
+        && let Some(res) = policy.load_response(key)
+    {
+        let (parts, body) = res.into_parts();
+        return Ok(respond(&headers, parts, body::boxed(body)));
+    }
+
+    let res = next.run(req).await;
+    let is_stream = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map_or(false, |s| s.starts_with("text/event-stream"));
+    if res.status() != StatusCode::OK || is_stream {
+        return Ok(res);
+    }
+
+    let (mut parts, body) = res.into_parts();
+    if let Some(cache_control) = policy.and_then(|policy| policy.cache_control())
+        && !parts.headers.contains_key(header::CACHE_CONTROL)
+        && let Ok(cache_control) = HeaderValue::try_from(cache_control)
+    {
+        parts.headers.insert(header::CACHE_CONTROL, cache_control);
+    }
+    if let Some(policy) = policy
+        && !policy.vary().is_empty()
+        && let Ok(vary) = HeaderValue::try_from(policy.vary().join(", "))
+    {
+        parts.headers.append(header::VARY, vary);
+    }
+
+    // Only the bodies with a known and bounded size are buffered.
+    let max_body_size = policy.map_or(CachePolicy::DEFAULT_MAX_BODY_SIZE, |policy| {
+        policy.max_body_size()
+    });
+    let content_length = parts
+        .headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+        .or_else(|| body.size_hint().exact());
+    if !content_length.is_some_and(|len| len <= max_body_size) {
+        return Ok(Response::from_parts(parts, body));
+    }
+
+    let bytes = hyper::body::to_bytes(body)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !parts.headers.contains_key(header::ETAG)
+        && let Ok(etag) = HeaderValue::try_from(response::compute_etag(&bytes))
+    {
+        parts.headers.insert(header::ETAG, etag);
+    }
+
+    #[cfg(feature = "cache")]
+    if let Some(policy) = policy
+        && let Some(key) = cache_key.as_deref()
+    {
+        policy.store_response(key, &parts, &bytes);
+    }
+
+    Ok(respond(&headers, parts, body::boxed(Full::from(bytes))))
+}
+
+/// Responds with `304 Not Modified` if the conditional request matches.
+fn respond(headers: &HeaderMap, mut parts: Parts, body: BoxBody) -> Response<BoxBody> {
+    let etag = parts
+        .headers
+        .get(header::ETAG)
+        .and_then(|v| v.to_str().ok());
+    let last_modified = parts
+        .headers
+        .get(header::LAST_MODIFIED)
+        .and_then(|v| v.to_str().ok());
+    if response::is_not_modified(headers, etag, last_modified) {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::CONTENT_TYPE);
+        Response::from_parts(parts, body::boxed(Full::default()))
+    } else {
+        Response::from_parts(parts, body)
+    }
+}
@@ -0,0 +1,33 @@
+use axum::{
+    body::{Body, BoxBody, Bytes, Full},
+    extract::State,
+    http::{Request, Response},
+    middleware::Next,
+    response::IntoResponse,
+};
+use zino_core::request::{AccessGuard, AccessRequest};
+
+/// A route-level middleware which resolves the principal by the guard and rejects
+/// the request if the action is not permitted. The principal is inserted into
+/// the request extensions for the handlers.
+///
+/// It should be used with `axum::middleware::from_fn_with_state`.
+pub async fn access_guard<G: AccessGuard + 'static>(
+    State(guard): State<&'static G>,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Response<BoxBody> {
+    let request = crate::AxumExtractor(req);
+    let access = AccessRequest::extract(&request);
+    match guard.check(&access).await {
+        Ok(principal) => {
+            let mut req = request.0;
+            req.extensions_mut().insert(principal);
+            next.run(req).await
+        }
+        Err(rejection) => {
+            let res = Response::<Full<Bytes>>::from(rejection.provide_context(&request));
+            res.into_response()
+        }
+    }
+}
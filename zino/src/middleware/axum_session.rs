@@ -0,0 +1,79 @@
+use axum::{
+    body::{Body, BoxBody},
+    http::{Request, Response, StatusCode},
+    middleware::Next,
+};
+
+#[cfg(any(feature = "accessor", feature = "cache"))]
+use std::sync::LazyLock;
+#[cfg(any(feature = "accessor", feature = "cache"))]
+use tower_cookies::Cookies;
+#[cfg(any(feature = "accessor", feature = "cache"))]
+use zino_core::{
+    request::{Context, RequestContext},
+    session::{Session, SessionStore},
+};
+
+pub(crate) async fn session(
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Result<Response<BoxBody>, StatusCode> {
+    #[cfg(any(feature = "accessor", feature = "cache"))]
+    if let Some(store) = SessionStore::shared() {
+        return Ok(handle_session(store, req, next).await);
+    }
+    Ok(next.run(req).await)
+}
+
+/// Loads the session before the handler and persists the changes after it returns.
+#[cfg(any(feature = "accessor", feature = "cache"))]
+async fn handle_session(
+    store: &'static SessionStore,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Response<BoxBody> {
+    let mut request = crate::AxumExtractor(req);
+    let session_id = request
+        .get_cookie(store.cookie_name())
+        .map(|cookie| cookie.value().to_owned());
+    let session = if let Some(session_id) = session_id {
+        store.load(&session_id).await.unwrap_or_else(|err| {
+            tracing::error!("fail to load the session: {err}");
+            None
+        })
+    } else {
+        None
+    }
+    .unwrap_or_default();
+    if !session.is_new()
+        && let Some(ctx) = request.extensions_mut().get_mut::<Context>()
+        && ctx.session_id().is_none()
+    {
+        ctx.set_session_id(Some(session.id()));
+    }
+
+    let mut cookie = request.new_cookie(store.cookie_name().to_owned(), "", Some(store.ttl()));
+    cookie.set_path("/");
+
+    let cookies = request.extensions().get::<Cookies>().cloned();
+    request.extensions_mut().insert(session.clone());
+
+    let res = next.run(request.0).await;
+    match store.commit(&session).await {
+        Ok(true) => {
+            if let Some(cookies) = cookies {
+                let key = LazyLock::force(&crate::request::axum_request::COOKIE_PRIVATE_KEY);
+                let signed_cookies = cookies.signed(key);
+                if session.is_destroyed() {
+                    signed_cookies.remove(cookie);
+                } else {
+                    cookie.set_value(session.id());
+                    signed_cookies.add(cookie);
+                }
+            }
+        }
+        Ok(false) => (),
+        Err(err) => tracing::error!("fail to persist the session: {err}"),
+    }
+    res
+}
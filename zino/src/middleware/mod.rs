@@ -1,6 +1,15 @@
+#[cfg(feature = "axum")]
+pub(crate) mod axum_cache;
+
 #[cfg(feature = "axum")]
 pub(crate) mod axum_context;
 
+#[cfg(feature = "axum")]
+pub(crate) mod axum_guard;
+
+#[cfg(feature = "axum")]
+pub(crate) mod axum_session;
+
 #[cfg(feature = "axum")]
 pub(crate) mod tower_cors;
 
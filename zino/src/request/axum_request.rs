@@ -21,6 +21,7 @@ use zino_core::{
     extend::HeaderMapExt,
     request::{Context, RequestContext},
     response::Rejection,
+    session::Session,
     state::State,
     Map,
 };
@@ -149,6 +150,16 @@ impl RequestContext for AxumExtractor<Request<Body>> {
         state.data_mut()
     }
 
+    #[inline]
+    fn session(&self) -> Option<&Session> {
+        self.extensions().get::<Session>()
+    }
+
+    #[inline]
+    fn session_mut(&mut self) -> Option<&mut Session> {
+        self.extensions_mut().get_mut::<Session>()
+    }
+
     #[inline]
     fn try_send(&self, message: CloudEvent) -> Result<(), Rejection> {
         crate::channel::axum_channel::MessageChannel::shared()
@@ -175,8 +186,22 @@ impl FromRequest<(), Body> for AxumExtractor<Request<Body>> {
     }
 }
 
+impl From<Request<Body>> for AxumExtractor<Request<Body>> {
+    #[inline]
+    fn from(request: Request<Body>) -> Self {
+        Self(request)
+    }
+}
+
+impl From<AxumExtractor<Request<Body>>> for Request<Body> {
+    #[inline]
+    fn from(extractor: AxumExtractor<Request<Body>>) -> Self {
+        extractor.0
+    }
+}
+
 /// Private key for cookie signing.
-static COOKIE_PRIVATE_KEY: LazyLock<Key> = LazyLock::new(|| {
+pub(crate) static COOKIE_PRIVATE_KEY: LazyLock<Key> = LazyLock::new(|| {
     let secret_key = crate::AxumCluster::secret_key();
     Key::try_from(secret_key).unwrap_or_else(|_| Key::generate())
 });